@@ -1,6 +1,7 @@
 import { ipcWindowControl } from "#/api/ipc/window.api";
 import { WindowOperation } from "#/interface/enum";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 /** 关闭指定窗口 */
 export const closeWindow = () => ipcWindowControl(WindowOperation.Close);
@@ -60,3 +61,252 @@ export const setSelfConfig = async (keys: Array<string>, value: unknown) => {
 export const deleteSelfConfig = async (keys: Array<string>) => {
   return invoke("mh_delete_self_config", { keys });
 };
+
+/** 生命周期钩子 */
+export type LifecycleHook = "onInstall" | "onUpgrade" | "onUninstall" | "onStartup";
+
+/** 生命周期钩子信息，previousVersion 只有 onUpgrade 时有值 */
+export type LifecycleEvent = {
+  hook: LifecycleHook;
+  previousVersion: string | null;
+};
+
+/**
+ * 获取当前页面作为生命周期钩子加载时的钩子信息，插件窗口未打开时宿主在隐藏窗口中加载钩子页面
+ * @returns 钩子信息，不是钩子页面时返回 null
+ */
+export const getLifecycleHook = (): LifecycleEvent | null => {
+  const params = new URLSearchParams(window.location.search);
+  const hook = params.get("hook") as LifecycleHook | null;
+  return hook ? { hook, previousVersion: params.get("previousVersion") } : null;
+};
+
+/**
+ * 监听插件窗口打开时触发的生命周期钩子
+ * @returns 取消监听函数
+ *
+ * @example
+ * await onLifecycle(async ({ hook }) => {
+ *   if (hook === 'onUninstall') await cleanup();
+ *   await lifecycleDone();
+ * });
+ */
+export const onLifecycle = (handler: (event: LifecycleEvent) => void) => {
+  return listen<LifecycleEvent>("plugin:lifecycle", (event) =>
+    handler(event.payload),
+  );
+};
+
+/** 通知宿主生命周期钩子已执行完成，隐藏窗口中的钩子页面随后被关闭 */
+export const lifecycleDone = async () => {
+  return invoke("mh_plugin_lifecycle_done");
+};
+
+/**
+ * 监听清单 hotkeys 中声明的快捷键，只有插件已启用且窗口已打开时才会收到
+ * @param handler 回调参数为触发的快捷键标识
+ * @returns 取消监听函数
+ */
+export const onHotkey = (handler: (hotkeyId: string) => void) => {
+  return listen<string>("plugin:hotkey", (event) => handler(event.payload));
+};
+
+/** 宿主命令的统一响应 */
+type ApiResponse<T> = { code: number; data: T | null; message: string };
+
+const unwrap = async <T>(response: Promise<ApiResponse<T>>) => {
+  const { code, data, message } = await response;
+  if (code !== 0) throw new Error(message);
+  return data as T;
+};
+
+/** 消息总线上的消息 */
+export type BusMessage<T = unknown> = {
+  topic: string;
+  /** 发布消息的窗口 */
+  sender: string;
+  payload: T;
+};
+
+/** 其他窗口发起的方法调用 */
+type BusRequest = {
+  id: number;
+  caller: string;
+  method: string;
+  payload: unknown;
+};
+
+/** 当前窗口每个主题的订阅数，最后一个订阅取消时才通知宿主 */
+const busSubscriptions = new Map<string, number>();
+
+const matchesTopic = (pattern: string, topic: string) =>
+  pattern.endsWith("/*")
+    ? topic.startsWith(pattern.slice(0, -1))
+    : pattern === topic;
+
+/**
+ * 订阅消息总线上的主题
+ * @param topic 主题，格式为 `<命名空间>/<主题名>`，`<命名空间>/*` 表示订阅整个命名空间。
+ * 插件的命名空间为其 windowId，宿主的命名空间为 `host`。
+ * 插件可以订阅自身命名空间，以及其他插件在清单 bus.topics 中允许订阅的单个主题
+ * @returns 取消订阅函数
+ *
+ * @example
+ * const unsubscribe = await subscribe('translator/done', ({ payload }) => console.log(payload));
+ */
+export const subscribe = async <T = unknown>(
+  topic: string,
+  handler: (message: BusMessage<T>) => void,
+) => {
+  await unwrap(invoke<ApiResponse<null>>("mh_bus_subscribe", { topics: [topic] }));
+  busSubscriptions.set(topic, (busSubscriptions.get(topic) ?? 0) + 1);
+  const unlisten = await listen<BusMessage<T>>("bus:message", (event) => {
+    if (matchesTopic(topic, event.payload.topic)) handler(event.payload);
+  });
+
+  return async () => {
+    unlisten();
+    const count = (busSubscriptions.get(topic) ?? 1) - 1;
+    if (count > 0) {
+      busSubscriptions.set(topic, count);
+      return;
+    }
+    busSubscriptions.delete(topic);
+    await invoke("mh_bus_unsubscribe", { topics: [topic] });
+  };
+};
+
+/**
+ * 发布消息，插件可以发布自身命名空间下的主题，以及其他插件在清单 bus.topics 中允许的主题
+ * @returns 收到消息的窗口数
+ */
+export const publish = async (topic: string, payload?: unknown) => {
+  return unwrap(
+    invoke<ApiResponse<number>>("mh_bus_publish", { topic, payload: payload ?? null }),
+  );
+};
+
+/**
+ * 调用其他插件在清单 bus.methods 中声明的方法，调用方需要在该方法的 callers 中，目标插件窗口需要已打开
+ * @param target 提供方法的插件 windowId
+ * @param timeoutMs 超时时间，默认 10 秒，最长 60 秒
+ * @returns 方法的返回值，调用失败或超时时抛出错误
+ *
+ * @example
+ * const text = await request<string>('translator', 'translate', { text: 'hello' });
+ */
+export const request = async <T = unknown>(
+  target: string,
+  method: string,
+  payload?: unknown,
+  timeoutMs?: number,
+) => {
+  return unwrap(
+    invoke<ApiResponse<T>>("mh_bus_request", {
+      target,
+      method,
+      payload: payload ?? null,
+      timeoutMs,
+    }),
+  );
+};
+
+/**
+ * 提供清单 bus.methods 中声明的方法，处理函数的返回值或抛出的错误返回给调用方
+ * @param handler 参数为调用参数和调用方窗口
+ * @returns 取消提供函数
+ *
+ * @example
+ * await expose('translate', async ({ text }) => translate(text));
+ */
+export const expose = (
+  method: string,
+  handler: (payload: any, caller: string) => unknown | Promise<unknown>,
+) => {
+  return listen<BusRequest>("bus:request", async (event) => {
+    const { id, caller, payload } = event.payload;
+    if (event.payload.method !== method) return;
+    try {
+      const result = await handler(payload, caller);
+      await invoke("mh_bus_respond", { id, result: result ?? null });
+    } catch (e) {
+      await invoke("mh_bus_respond", {
+        id,
+        error: e instanceof Error ? e.message : String(e),
+      });
+    }
+  });
+};
+
+/** 宿主存储的值，二进制数据使用 Base64 编码 */
+type StorageValue =
+  | { type: "json"; value: unknown }
+  | { type: "binary"; value: string };
+
+/** 存储项的元数据 */
+export type StorageEntry = {
+  key: string;
+  /** 键和值占用的字节数 */
+  size: number;
+  binary: boolean;
+  updatedAt: string;
+};
+
+const toStorageValue = (value: unknown): StorageValue => {
+  if (!(value instanceof Uint8Array)) return { type: "json", value: value ?? null };
+  let binary = "";
+  value.forEach((byte) => (binary += String.fromCharCode(byte)));
+  return { type: "binary", value: btoa(binary) };
+};
+
+const fromStorageValue = ({ type, value }: StorageValue) =>
+  type === "binary"
+    ? Uint8Array.from(atob(value), (char) => char.charCodeAt(0))
+    : value;
+
+/**
+ * 读取插件键值存储，不存在的键不会出现在结果中，二进制数据返回 Uint8Array
+ *
+ * @example
+ * const { token, avatar } = await getStorage(['token', 'avatar']);
+ */
+export const getStorage = async (keys: Array<string>) => {
+  const values = await unwrap(
+    invoke<ApiResponse<Record<string, StorageValue>>>("mh_storage_get", { keys }),
+  );
+  return Object.fromEntries(
+    Object.entries(values).map(([key, value]) => [key, fromStorageValue(value)]),
+  ) as Record<string, unknown>;
+};
+
+/**
+ * 批量写入插件键值存储，Uint8Array 按二进制保存，其余值按 JSON 保存。
+ * 所有键值一起写入，超出存储配额时全部不生效并抛出错误
+ *
+ * @example
+ * await setStorage({ token: 'abc', avatar: new Uint8Array(buffer) });
+ */
+export const setStorage = async (entries: Record<string, unknown>) => {
+  const values = Object.fromEntries(
+    Object.entries(entries).map(([key, value]) => [key, toStorageValue(value)]),
+  );
+  await unwrap(invoke<ApiResponse<null>>("mh_storage_set", { entries: values }));
+};
+
+/**
+ * 批量删除插件键值存储
+ * @returns 实际删除的键数量
+ */
+export const deleteStorage = async (keys: Array<string>) => {
+  return unwrap(invoke<ApiResponse<number>>("mh_storage_delete", { keys }));
+};
+
+/**
+ * 列出插件键值存储中以 prefix 开头的键，不返回值
+ *
+ * @example
+ * const drafts = await listStorage('draft.');
+ */
+export const listStorage = async (prefix = "") => {
+  return unwrap(invoke<ApiResponse<StorageEntry[]>>("mh_storage_list", { prefix }));
+};
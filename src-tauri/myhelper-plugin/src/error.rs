@@ -0,0 +1,24 @@
+use plugin_manifest::error::ManifestError;
+
+#[derive(Debug)]
+pub enum AppError {
+    Error(String),
+}
+
+impl std::error::Error for AppError {}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Error(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<ManifestError> for AppError {
+    fn from(error: ManifestError) -> Self {
+        AppError::Error(error.to_string())
+    }
+}
+
+pub type AppResult<T> = Result<T, AppError>;
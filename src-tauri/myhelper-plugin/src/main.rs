@@ -0,0 +1,111 @@
+//! MyHelper 插件校验、签名和打包工具
+//!
+//! 清单规则与宿主共用 `plugin-manifest`，与安装插件包和同步插件目录时的校验一致
+
+mod error;
+mod package;
+mod sign;
+
+use crate::error::{AppError, AppResult};
+use crate::package::PluginDir;
+use clap::{Parser, Subcommand};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "myhelper-plugin",
+    version,
+    about = "MyHelper 插件校验、签名和打包工具"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 校验插件目录
+    Check {
+        /// 插件目录，根目录下需要有 mhPlugin.json
+        dir: PathBuf,
+    },
+    /// 校验插件目录并打包为 ZIP
+    Pack {
+        /// 插件目录，根目录下需要有 mhPlugin.json
+        dir: PathBuf,
+        /// 输出文件，默认为当前目录下的 <windowId>-<version>.zip
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Ed25519 私钥文件，指定时对插件包签名
+        #[arg(short, long)]
+        key: Option<PathBuf>,
+    },
+    /// 生成用于签名插件包的 Ed25519 密钥
+    Keygen {
+        /// 私钥输出文件
+        output: PathBuf,
+        /// 覆盖已存在的私钥文件
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> AppResult<()> {
+    match command {
+        Command::Check { dir } => {
+            let plugin = PluginDir::load(&dir)?;
+            println!(
+                "{} {} 校验通过，共 {} 个文件，{} 字节",
+                plugin.manifest.window_id,
+                plugin.manifest.version,
+                plugin.files.len(),
+                plugin.size()?
+            );
+        }
+        Command::Pack { dir, output, key } => {
+            let mut plugin = PluginDir::load(&dir)?;
+            let output = output.unwrap_or_else(|| {
+                PathBuf::from(format!(
+                    "{}-{}.zip",
+                    plugin.manifest.window_id, plugin.manifest.version
+                ))
+            });
+            // 输出到插件目录内时不打包上一次生成的插件包
+            plugin.exclude(&output);
+
+            let bytes = plugin.pack()?;
+            let signature = key.map(|key| sign::sign(&key, &bytes)).transpose()?;
+            fs::write(&output, &bytes)
+                .map_err(|e| AppError::Error(format!("写入 {} 失败: {}", output.display(), e)))?;
+
+            println!("windowId: {}", plugin.manifest.window_id);
+            println!("version: {}", plugin.manifest.version);
+            println!("file: {}", output.display());
+            println!("size: {}", bytes.len());
+            println!("sha256: {:x}", Sha256::digest(&bytes));
+            if let Some((signature, public_key)) = signature {
+                println!("signature: {}", signature);
+                println!("publicKey: {}", public_key);
+            }
+        }
+        Command::Keygen { output, force } => {
+            let public_key = sign::keygen(&output, force)?;
+            println!("私钥已写入 {}，请妥善保管", output.display());
+            println!("publicKey: {}", public_key);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use crate::error::{AppError, AppResult};
+use ignore::WalkBuilder;
+use plugin_manifest::manifest::{
+    PluginManifest, MANIFEST_FILE, MAX_UNPACKED_SIZE, MAX_ZIP_ENTRIES, MAX_ZIP_SIZE,
+};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Cursor};
+use std::path::{Path, PathBuf};
+use zip::read::ZipArchive;
+use zip::write::{SimpleFileOptions, ZipWriter};
+use zip::{CompressionMethod, DateTime};
+
+/// 待打包的插件目录
+pub struct PluginDir {
+    pub manifest: PluginManifest,
+    /// 包内路径及对应的本地文件，按包内路径排序
+    pub files: Vec<(String, PathBuf)>,
+}
+
+impl PluginDir {
+    /// 读取并校验插件目录
+    ///
+    /// 清单按宿主同步插件目录时的规则校验，入口文件、生命周期页面和本地图标必须存在。
+    /// 隐藏文件和 node_modules 不会被打包
+    pub fn load(root: &Path) -> AppResult<Self> {
+        let manifest_bytes = fs::read(root.join(MANIFEST_FILE))
+            .map_err(|e| AppError::Error(format!("读取 {} 失败: {}", MANIFEST_FILE, e)))?;
+        let manifest = PluginManifest::parse(&manifest_bytes, None)?;
+        let files = collect_files(root)?;
+
+        let names: HashSet<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        let mut errors = Vec::new();
+        if !names.contains(manifest.entry.as_str()) {
+            errors.push(format!("缺少入口文件: {}", manifest.entry));
+        }
+        for (name, page) in manifest.lifecycle.pages() {
+            if !names.contains(page) {
+                errors.push(format!("缺少生命周期页面 {}: {}", name, page));
+            }
+        }
+        if let Some(icon) = manifest.icon.as_deref().filter(|icon| {
+            !icon.is_empty() && !icon.starts_with("http://") && !icon.starts_with("https://")
+        }) {
+            if !names.contains(icon.trim_start_matches("./")) {
+                errors.push(format!("缺少图标文件: {}", icon));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AppError::Error(format!(
+                "插件目录 {} 校验失败: {}",
+                root.display(),
+                errors.join("; ")
+            )));
+        }
+
+        Ok(Self { manifest, files })
+    }
+
+    /// 从待打包的文件中移除指定文件
+    pub fn exclude(&mut self, path: &Path) {
+        let Ok(path) = path.canonicalize() else {
+            return;
+        };
+        self.files
+            .retain(|(_, file)| file.canonicalize().map_or(true, |file| file != path));
+    }
+
+    /// 待打包文件的总大小
+    pub fn size(&self) -> AppResult<u64> {
+        self.files.iter().try_fold(0, |total, (name, path)| {
+            fs::metadata(path)
+                .map(|metadata| total + metadata.len())
+                .map_err(|e| AppError::Error(format!("读取 {} 失败: {}", name, e)))
+        })
+    }
+
+    /// 生成插件包
+    ///
+    /// 文件按包内路径排序写入，修改时间和权限固定，相同的文件内容总是生成相同的插件包。
+    /// 生成后按安装插件包时的规则重新校验
+    pub fn pack(&self) -> AppResult<Vec<u8>> {
+        if self.files.len() > MAX_ZIP_ENTRIES {
+            return Err(AppError::Error(format!(
+                "插件包包含 {} 个文件，超过上限 {}",
+                self.files.len(),
+                MAX_ZIP_ENTRIES
+            )));
+        }
+        let size = self.size()?;
+        if size > MAX_UNPACKED_SIZE {
+            return Err(AppError::Error(format!(
+                "插件文件总大小 {} 字节超过上限 {} 字节",
+                size, MAX_UNPACKED_SIZE
+            )));
+        }
+
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(DateTime::default())
+            .unix_permissions(0o644);
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, path) in &self.files {
+            writer
+                .start_file(name.as_str(), options)
+                .map_err(|e| AppError::Error(format!("写入 {} 失败: {}", name, e)))?;
+            let mut file = File::open(path)
+                .map_err(|e| AppError::Error(format!("读取 {} 失败: {}", name, e)))?;
+            io::copy(&mut file, &mut writer)
+                .map_err(|e| AppError::Error(format!("写入 {} 失败: {}", name, e)))?;
+        }
+        let bytes = writer
+            .finish()
+            .map_err(|e| AppError::Error(format!("生成插件包失败: {}", e)))?
+            .into_inner();
+
+        if bytes.len() as u64 > MAX_ZIP_SIZE {
+            return Err(AppError::Error(format!(
+                "插件包大小 {} 字节超过上限 {} 字节",
+                bytes.len(),
+                MAX_ZIP_SIZE
+            )));
+        }
+        let mut archive = ZipArchive::new(Cursor::new(bytes.as_slice()))
+            .map_err(|e| AppError::Error(format!("无效的ZIP文件: {}", e)))?;
+        PluginManifest::from_zip(&mut archive, Some(&self.manifest.window_id))?;
+
+        Ok(bytes)
+    }
+}
+
+/// 收集插件目录中的文件，包内路径使用 `/` 分隔并按字典序排序
+fn collect_files(root: &Path) -> AppResult<Vec<(String, PathBuf)>> {
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .hidden(true)
+        .filter_entry(|entry| entry.file_name() != "node_modules")
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| AppError::Error(format!("读取插件目录失败: {}", e)))?;
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .map_err(|e| AppError::Error(format!("读取插件目录失败: {}", e)))?;
+        let segments = relative
+            .components()
+            .map(|component| {
+                component.as_os_str().to_str().ok_or_else(|| {
+                    AppError::Error(format!("文件名不是有效的 UTF-8: {}", relative.display()))
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        files.push((segments.join("/"), entry.into_path()));
+    }
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(files)
+}
@@ -0,0 +1,48 @@
+use crate::error::{AppError, AppResult};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ed25519_dalek::{Signer, SigningKey};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// 生成 Ed25519 私钥并写入文件，返回 Base64 编码的公钥
+///
+/// 公钥用于在宿主中添加信任的发布者，私钥文件只对当前用户可读
+pub fn keygen(path: &Path, force: bool) -> AppResult<String> {
+    if path.exists() && !force {
+        return Err(AppError::Error(format!(
+            "{} 已存在，使用 --force 覆盖",
+            path.display()
+        )));
+    }
+    let signing_key = SigningKey::from_bytes(&rand::random());
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(STANDARD.encode(signing_key.to_bytes()).as_bytes()))
+        .map_err(|e| AppError::Error(format!("写入私钥失败: {}", e)))?;
+
+    Ok(STANDARD.encode(signing_key.verifying_key().to_bytes()))
+}
+
+/// 使用私钥文件对插件包签名，返回 Base64 编码的签名和公钥
+pub fn sign(key_path: &Path, bytes: &[u8]) -> AppResult<(String, String)> {
+    let content = fs::read_to_string(key_path)
+        .map_err(|e| AppError::Error(format!("读取私钥失败: {}", e)))?;
+    let key_bytes = STANDARD
+        .decode(content.trim())
+        .map_err(|e| AppError::Error(format!("私钥格式无效: {}", e)))?;
+    let key_bytes: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|_| AppError::Error("私钥长度应为 32 字节".into()))?;
+    let signing_key = SigningKey::from_bytes(&key_bytes);
+
+    Ok((
+        STANDARD.encode(signing_key.sign(bytes).to_bytes()),
+        STANDARD.encode(signing_key.verifying_key().to_bytes()),
+    ))
+}
@@ -1,23 +1,67 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::punctuated::Punctuated;
-use syn::{parse::Parse, parse::ParseStream, parse_macro_input, FnArg, LitStr, Pat, Token};
+use syn::{
+    parse::Parse, parse::ParseStream, parse_macro_input, FnArg, Ident, LitStr, Pat, Path, Token,
+};
 
-// 定义一个解析多个字符串的结构
+// 权限参数：窗口标识或 `cap = "能力"`
+enum PermissionArg {
+    Window(LitStr),
+    Capability(LitStr),
+}
+
+impl Parse for PermissionArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            return Ok(PermissionArg::Window(input.parse()?));
+        }
+        let name: Ident = input.parse()?;
+        if name != "cap" {
+            return Err(syn::Error::new(
+                name.span(),
+                "只支持窗口标识字符串或 cap = \"能力\"",
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(PermissionArg::Capability(input.parse()?))
+    }
+}
+
+// 定义一个解析多个权限参数的结构
 struct PermissionInput {
-    windows: Punctuated<LitStr, Token![,]>,
+    windows: Vec<LitStr>,
+    capability: Option<LitStr>,
 }
 
 impl Parse for PermissionInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let args: Punctuated<PermissionArg, Token![,]> = Punctuated::parse_terminated(input)?;
+        let mut windows = Vec::new();
+        let mut capability = None;
+        for arg in args {
+            match arg {
+                PermissionArg::Window(lit) => windows.push(lit),
+                PermissionArg::Capability(lit) => {
+                    if capability.is_some() {
+                        return Err(syn::Error::new(lit.span(), "cap 只能指定一次"));
+                    }
+                    capability = Some(lit);
+                }
+            }
+        }
         Ok(PermissionInput {
-            windows: Punctuated::parse_terminated(input)?,
+            windows,
+            capability,
         })
     }
 }
 
 /// 窗口权限检查宏
 ///
+/// 指定 `cap` 时按权限策略检查窗口是否拥有该能力：内置窗口按策略表授权，插件窗口按用户授予的能力授权。
+/// 被拒绝的调用记录到审计日志，返回 `ApiResponse` 的命令以 `ErrPermission` 响应拒绝
+///
 /// # 示例
 /// ```rust
 /// #[permission("main", "label")]  // 允许 main 和 label 窗口调用
@@ -25,10 +69,16 @@ impl Parse for PermissionInput {
 /// pub fn get_plugin_config(keys: Vec<String>) -> AppResult<Option<Value>> {
 ///     // 函数实现
 /// }
+///
+/// #[permission(cap = "config.read")]  // 允许策略中拥有 config.read 能力的窗口调用
+/// #[tauri::command]
+/// pub fn get_config(keys: Vec<String>) -> AppResult<Option<Value>> {
+///     // 函数实现
+/// }
 /// ```
 #[proc_macro_attribute]
 pub fn permission(attr: TokenStream, item: TokenStream) -> TokenStream {
-    // 解析属性中的窗口标识列表
+    // 解析属性中的窗口标识列表和能力
     let input = parse_macro_input!(attr as PermissionInput);
     let window_labels: Vec<String> = input.windows.iter().map(|lit| lit.value()).collect();
 
@@ -52,18 +102,73 @@ pub fn permission(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attrs = &input_fn.attrs;
     let asyncness = &input_fn.sig.asyncness; // 获取函数的异步标记
 
+    // 返回 ApiResponse 的命令以 ErrPermission 响应拒绝，其余命令返回错误
+    let returns_api_response = quote!(#fn_return).to_string().contains("ApiResponse");
+    let deny = if returns_api_response {
+        quote! {
+            return Ok(crate::utils::response::ApiResponse::permission_denied(denied.to_string()));
+        }
+    } else {
+        quote! {
+            return Err(::core::convert::Into::into(denied));
+        }
+    };
+
+    // 能力名在编译期校验，窗口拥有的能力在运行时按权限策略解析
+    let permission_check = match &input.capability {
+        Some(capability) => quote! {
+            const _: () = assert!(
+                crate::services::permission::is_defined_capability(#capability),
+                "未定义的能力"
+            );
+
+            // 检查窗口权限
+            let window_label = window.label();
+            if ![#(#window_labels),*].contains(&window_label) {
+                if let Err(denied) = crate::services::permission::PermissionPolicy::check(
+                    window_label,
+                    stringify!(#fn_name),
+                    #capability,
+                ) {
+                    #deny
+                }
+            }
+        },
+        None => quote! {
+            // 检查窗口权限
+            let window_label = window.label();
+            if ![#(#window_labels),*].contains(&window_label) {
+                let denied = crate::services::permission::PermissionPolicy::deny(
+                    window_label,
+                    stringify!(#fn_name),
+                    None,
+                );
+                #deny
+            }
+        },
+    };
+
+    // 命令的权限策略标记，注册命令时由 `handlers!` 检查
+    let policy_marker = policy_marker_ident(fn_name);
+    let cfg_attrs = attrs.iter().filter(|attr| attr.path().is_ident("cfg"));
+    let policy = match &input.capability {
+        Some(capability) => quote! { Some(#capability) },
+        None => quote! { None },
+    };
+    let marker = quote! {
+        #(#cfg_attrs)*
+        #[doc(hidden)]
+        pub const #policy_marker: Option<&str> = #policy;
+    };
+
     let expanded = if has_window_param {
         // 如果函数已经有 window 参数，只添加权限检查
         quote! {
+            #marker
+
             #(#attrs)*
             pub #asyncness fn #fn_name(#fn_args) #fn_return {
-                // 检查窗口权限
-                let window_label = window.label();
-                if ![#(#window_labels),*].contains(&window_label) {
-                    return Err(crate::utils::error::AppError::Error(
-                        format!("该窗口没有权限执行此操作")
-                    ));
-                }
+                #permission_check
 
                 // 执行原始函数体
                 #fn_body
@@ -72,15 +177,11 @@ pub fn permission(attr: TokenStream, item: TokenStream) -> TokenStream {
     } else {
         // 如果函数没有 window 参数，注入 window 参数并添加权限检查
         quote! {
+            #marker
+
             #(#attrs)*
             pub #asyncness fn #fn_name(window: tauri::Window, #fn_args) #fn_return {
-                // 检查窗口权限
-                let window_label = window.label();
-                if ![#(#window_labels),*].contains(&window_label) {
-                    return Err(crate::utils::error::AppError::Error(
-                        format!("该窗口没有权限执行此操作")
-                    ));
-                }
+                #permission_check
 
                 // 执行原始函数体
                 #fn_body
@@ -90,3 +191,39 @@ pub fn permission(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+fn policy_marker_ident(fn_name: &Ident) -> Ident {
+    format_ident!("__PERMISSION_{}", fn_name.to_string().to_uppercase())
+}
+
+/// 注册命令处理器，同 `tauri::generate_handler!`
+///
+/// 每个命令都必须使用 `#[permission]` 声明权限策略，否则编译失败，
+/// 错误信息为找不到该命令的 `__PERMISSION_<命令名>` 标记
+///
+/// # 示例
+/// ```rust
+/// permission_macro::handlers![get_config, set_config]
+/// ```
+#[proc_macro]
+pub fn handlers(item: TokenStream) -> TokenStream {
+    let commands = parse_macro_input!(item with Punctuated::<Path, Token![,]>::parse_terminated);
+
+    // 未声明权限策略的命令找不到对应的 __PERMISSION_ 标记，编译时报错
+    let markers = commands.iter().map(|command| {
+        let mut marker = command.clone();
+        if let Some(last) = marker.segments.last_mut() {
+            last.ident = policy_marker_ident(&last.ident);
+        }
+        marker
+    });
+    let commands = commands.iter();
+
+    quote! {{
+        const _: () = {
+            #(let _: Option<&str> = #markers;)*
+        };
+        tauri::generate_handler![#(#commands),*]
+    }}
+    .into()
+}
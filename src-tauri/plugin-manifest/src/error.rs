@@ -0,0 +1,17 @@
+/// 读取或校验插件清单失败，消息可以直接展示给用户
+#[derive(Debug)]
+pub enum ManifestError {
+    Invalid(String),
+}
+
+impl std::error::Error for ManifestError {}
+
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+pub type ManifestResult<T> = Result<T, ManifestError>;
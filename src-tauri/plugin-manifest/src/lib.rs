@@ -0,0 +1,6 @@
+//! 宿主和插件打包工具共用的插件清单及校验规则
+//!
+//! 宿主通过 `mh_plugin::manifest` 重新导出，[`error::ManifestError`] 在宿主中转换为 `AppError`
+
+pub mod error;
+pub mod manifest;
@@ -0,0 +1,591 @@
+use crate::error::{ManifestError, ManifestResult};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{Read, Seek};
+use zip::read::ZipArchive;
+
+/// 插件清单文件名
+pub const MANIFEST_FILE: &str = "mhPlugin.json";
+/// 当前支持的清单格式版本
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// 插件窗口尺寸上限
+const MAX_WINDOW_SIZE: u32 = 10000;
+/// 插件包大小上限
+pub const MAX_ZIP_SIZE: u64 = 15 * 1024 * 1024; // 15MB
+/// 插件包解压后的总大小上限
+pub const MAX_UNPACKED_SIZE: u64 = 200 * 1024 * 1024; // 200MB
+/// 插件包中的文件数上限
+pub const MAX_ZIP_ENTRIES: usize = 10000;
+/// 插件清单文件大小上限
+const MAX_MANIFEST_SIZE: u64 = 1024 * 1024; // 1MB
+
+/// 插件可以在 mhPlugin.json 的 permissions 中申请的能力及其说明
+pub const PLUGIN_CAPABILITIES: [(&str, &str); 7] = [
+    ("clipboard.read", "读取剪贴板内容"),
+    ("clipboard.write", "写入剪贴板"),
+    ("input.paste", "向当前应用模拟粘贴"),
+    ("file.search", "搜索和检查本地文件"),
+    ("url.open", "打开网址或本地应用"),
+    ("config.read", "读取应用配置"),
+    ("config.write", "修改应用配置"),
+];
+
+/// 内置窗口的标签，插件不能使用这些窗口ID
+pub const BUILTIN_WINDOW_LABELS: [&str; 5] = ["main", "setting", "my", "pluginMarket", "label"];
+/// 消息总线上内置窗口的命名空间，插件不能使用该窗口ID
+pub const HOST_NAMESPACE: &str = "host";
+
+/// 是否为保留给宿主的窗口ID
+pub fn is_reserved_window_id(window_id: &str) -> bool {
+    window_id == HOST_NAMESPACE || BUILTIN_WINDOW_LABELS.contains(&window_id)
+}
+
+/// 判断是否为插件可以申请的能力
+pub fn is_known_capability(name: &str) -> bool {
+    PLUGIN_CAPABILITIES.iter().any(|(capability, _)| *capability == name)
+}
+
+/// 插件声明的快捷键
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginHotkey {
+    /// 快捷键标识，在同一插件内唯一
+    pub id: String,
+    /// 默认按键，如 `Ctrl+Shift+K`
+    pub key: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// 插件生命周期钩子，取值为插件目录内的页面
+///
+/// 插件窗口已打开时宿主向该窗口发送 `plugin:lifecycle` 事件，否则在隐藏窗口中加载对应页面，
+/// 页面地址带有 `hook` 和 `previousVersion` 查询参数，执行完成后调用 `mh_plugin_lifecycle_done`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleHooks {
+    /// 首次安装后
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_install: Option<String>,
+    /// 升级到新版本后
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_upgrade: Option<String>,
+    /// 卸载前，插件目录在钩子完成或超时后删除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_uninstall: Option<String>,
+    /// 应用启动后
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_startup: Option<String>,
+}
+
+impl LifecycleHooks {
+    /// 已声明的钩子及其页面
+    pub fn pages(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("onInstall", &self.on_install),
+            ("onUpgrade", &self.on_upgrade),
+            ("onUninstall", &self.on_uninstall),
+            ("onStartup", &self.on_startup),
+        ]
+        .into_iter()
+        .filter_map(|(name, page)| page.as_deref().map(|page| (name, page)))
+        .collect()
+    }
+}
+
+/// 插件命名空间下的消息主题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusTopic {
+    /// 主题名，不含命名空间
+    pub name: String,
+    /// 除插件自身外允许发布该主题的插件，`*` 表示所有插件
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    /// 除插件自身外允许订阅该主题的插件，`*` 表示所有插件
+    #[serde(default)]
+    pub subscribers: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// 插件提供给其他插件调用的方法
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMethod {
+    pub name: String,
+    /// 允许调用的插件，`*` 表示所有插件，为空时只有宿主可以调用
+    #[serde(default)]
+    pub callers: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// 插件在消息总线上声明的主题和方法
+///
+/// 插件总是可以发布和订阅自身命名空间下的主题，这里只需声明允许其他插件发布或订阅的主题
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusDeclaration {
+    #[serde(default)]
+    pub topics: Vec<BusTopic>,
+    #[serde(default)]
+    pub methods: Vec<BusMethod>,
+}
+
+/// 插件类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PluginKind {
+    #[default]
+    Plugin,
+    /// 共享运行库，不能打开窗口，依赖它的插件通过 `../<windowId>/` 引用其中的文件
+    Library,
+}
+
+/// 插件清单 mhPlugin.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    /// 清单格式版本，缺省为 1
+    #[serde(default = "default_manifest_version")]
+    pub manifest_version: u32,
+    pub window_id: String,
+    pub title: String,
+    /// 插件版本，遵循 semver
+    pub version: Version,
+    /// 插件要求的最低宿主版本
+    #[serde(default)]
+    pub min_host_version: Option<Version>,
+    /// 入口页面，相对插件目录的路径
+    #[serde(default = "default_entry")]
+    pub entry: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// 窗口大小 [宽, 高]
+    #[serde(default = "default_size")]
+    pub size: [u32; 2],
+    /// 窗口位置 [x, y]，-1 表示居中
+    #[serde(default = "default_position")]
+    pub position: [i32; 2],
+    #[serde(default)]
+    pub always_on_top: bool,
+    #[serde(default = "default_resizable")]
+    pub resizable: bool,
+    /// 插件申请的能力，安装时由用户授权
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    #[serde(default)]
+    pub hotkeys: Vec<PluginHotkey>,
+    #[serde(default)]
+    pub lifecycle: LifecycleHooks,
+    #[serde(default)]
+    pub bus: BusDeclaration,
+    #[serde(default)]
+    pub kind: PluginKind,
+    /// 依赖的插件或共享运行库，键为 windowId，值为 semver 版本范围，安装时自动从插件市场安装
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, VersionReq>,
+}
+
+fn default_manifest_version() -> u32 {
+    1
+}
+
+fn default_entry() -> String {
+    "index.html".to_string()
+}
+
+fn default_size() -> [u32; 2] {
+    [800, 600]
+}
+
+fn default_position() -> [i32; 2] {
+    [-1, -1]
+}
+
+fn default_resizable() -> bool {
+    true
+}
+
+impl PluginManifest {
+    /// 解析并校验清单内容
+    ///
+    /// `expected_window_id` 不为空时要求清单中的 windowId 与之一致
+    pub fn parse(bytes: &[u8], expected_window_id: Option<&str>) -> ManifestResult<Self> {
+        let manifest: PluginManifest = serde_json::from_slice(bytes)
+            .map_err(|e| ManifestError::Invalid(format!("{} 格式错误: {}", MANIFEST_FILE, e)))?;
+        manifest.validate(expected_window_id)?;
+        Ok(manifest)
+    }
+
+    /// 从插件包根目录读取并校验清单，同时检查入口文件是否存在
+    pub fn from_zip<R: Read + Seek>(
+        archive: &mut ZipArchive<R>,
+        expected_window_id: Option<&str>,
+    ) -> ManifestResult<Self> {
+        let mut buffer = Vec::new();
+        archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| ManifestError::Invalid(format!("插件包根目录缺少 {}", MANIFEST_FILE)))?
+            .take(MAX_MANIFEST_SIZE + 1)
+            .read_to_end(&mut buffer)
+            .map_err(|e| ManifestError::Invalid(format!("读取 {} 失败: {}", MANIFEST_FILE, e)))?;
+        if buffer.len() as u64 > MAX_MANIFEST_SIZE {
+            return Err(ManifestError::Invalid(format!(
+                "{} 超过大小上限 {} 字节",
+                MANIFEST_FILE, MAX_MANIFEST_SIZE
+            )));
+        }
+
+        let manifest = Self::parse(&buffer, expected_window_id)?;
+        if archive.index_for_name(&manifest.entry).is_none() {
+            return Err(ManifestError::Invalid(format!(
+                "插件包中缺少入口文件: {}",
+                manifest.entry
+            )));
+        }
+        for (name, page) in manifest.lifecycle.pages() {
+            if archive.index_for_name(page).is_none() {
+                return Err(ManifestError::Invalid(format!(
+                    "插件包中缺少生命周期页面 {}: {}",
+                    name, page
+                )));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// 校验字段取值和宿主兼容性，所有问题合并为一条错误消息
+    pub fn validate(&self, expected_window_id: Option<&str>) -> ManifestResult<()> {
+        let mut errors = Vec::new();
+
+        if self.manifest_version == 0 || self.manifest_version > MANIFEST_VERSION {
+            errors.push(format!(
+                "manifestVersion: 不支持的清单版本 {}，当前支持 1-{}",
+                self.manifest_version, MANIFEST_VERSION
+            ));
+        }
+        if !is_valid_window_id(&self.window_id) {
+            errors.push("windowId: 只能包含 ASCII 字母、数字、连字符或下划线".to_string());
+        } else if is_reserved_window_id(&self.window_id) {
+            errors.push(format!(
+                "windowId: {} 是内置窗口或宿主保留的标识",
+                self.window_id
+            ));
+        } else if let Some(expected) = expected_window_id.filter(|id| *id != self.window_id) {
+            errors.push(format!(
+                "windowId: 清单中为 {}，与安装的插件 {} 不一致",
+                self.window_id, expected
+            ));
+        }
+        if self.title.trim().is_empty() {
+            errors.push("title: 不能为空".to_string());
+        }
+        if let Some(min_host_version) = &self.min_host_version {
+            let host_version = host_version();
+            if host_version < *min_host_version {
+                errors.push(format!(
+                    "minHostVersion: 插件要求宿主版本不低于 {}，当前为 {}",
+                    min_host_version, host_version
+                ));
+            }
+        }
+        if !is_relative_path(&self.entry) {
+            errors.push(format!("entry: {} 不是插件目录内的相对路径", self.entry));
+        }
+        // 图标可以是插件目录内的相对路径或网络地址
+        if let Some(icon) = self.icon.as_deref().filter(|icon| !icon.is_empty()) {
+            let is_url = icon.starts_with("http://") || icon.starts_with("https://");
+            if !is_url && !is_relative_path(icon.trim_start_matches("./")) {
+                errors.push(format!("icon: {} 不是插件目录内的相对路径", icon));
+            }
+        }
+        for (index, length) in self.size.iter().enumerate() {
+            if *length == 0 || *length > MAX_WINDOW_SIZE {
+                errors.push(format!(
+                    "size[{}]: 取值范围为 1-{}",
+                    index, MAX_WINDOW_SIZE
+                ));
+            }
+        }
+        let mut permissions = HashSet::new();
+        for (index, permission) in self.permissions.iter().enumerate() {
+            if !is_known_capability(permission) {
+                let known: Vec<&str> = PLUGIN_CAPABILITIES.iter().map(|(name, _)| *name).collect();
+                errors.push(format!(
+                    "permissions[{}]: 未知的能力 {}，可选值: {}",
+                    index,
+                    permission,
+                    known.join(", ")
+                ));
+            } else if !permissions.insert(permission.as_str()) {
+                errors.push(format!("permissions[{}]: {} 重复", index, permission));
+            }
+        }
+        for (name, page) in self.lifecycle.pages() {
+            if !is_relative_path(page) {
+                errors.push(format!("lifecycle.{}: {} 不是插件目录内的相对路径", name, page));
+            }
+        }
+        let mut hotkey_ids = HashSet::new();
+        for (index, hotkey) in self.hotkeys.iter().enumerate() {
+            if hotkey.id.trim().is_empty() {
+                errors.push(format!("hotkeys[{}].id: 不能为空", index));
+            } else if !hotkey_ids.insert(hotkey.id.as_str()) {
+                errors.push(format!("hotkeys[{}].id: {} 重复", index, hotkey.id));
+            }
+            if hotkey.key.trim().is_empty() {
+                errors.push(format!("hotkeys[{}].key: 不能为空", index));
+            }
+        }
+
+        if self.kind == PluginKind::Library && !self.hotkeys.is_empty() {
+            errors.push("hotkeys: 共享运行库没有窗口，不能声明快捷键".to_string());
+        }
+        for dependency in self.dependencies.keys() {
+            if !is_valid_window_id(dependency) || is_reserved_window_id(dependency) {
+                errors.push(format!("dependencies: {} 不是有效的插件窗口ID", dependency));
+            } else if *dependency == self.window_id {
+                errors.push("dependencies: 插件不能依赖自身".to_string());
+            }
+        }
+
+        validate_bus_names(
+            "bus.topics",
+            self.bus.topics.iter().map(|topic| {
+                (
+                    &topic.name,
+                    topic.publishers.iter().chain(&topic.subscribers),
+                )
+            }),
+            &mut errors,
+        );
+        validate_bus_names(
+            "bus.methods",
+            self.bus
+                .methods
+                .iter()
+                .map(|method| (&method.name, method.callers.iter())),
+            &mut errors,
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ManifestError::Invalid(format!(
+                "{} 校验失败: {}",
+                MANIFEST_FILE,
+                errors.join("; ")
+            )))
+        }
+    }
+}
+
+/// 当前宿主版本，取自工作区版本
+pub fn host_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).unwrap_or_else(|_| Version::new(0, 0, 0))
+}
+
+/// 窗口ID只能包含 ASCII 字母、数字、连字符或下划线
+///
+/// 窗口ID同时用作窗口标签、插件目录名和消息总线命名空间
+pub fn is_valid_window_id(window_id: &str) -> bool {
+    !window_id.is_empty()
+        && window_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 消息主题和方法名只能包含字母、数字、点、连字符或下划线
+pub fn is_valid_bus_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_')
+}
+
+/// 校验主题或方法的名称以及允许的插件列表
+fn validate_bus_names<'a, I: Iterator<Item = &'a String>>(
+    field: &str,
+    entries: impl Iterator<Item = (&'a String, I)>,
+    errors: &mut Vec<String>,
+) {
+    let mut names = HashSet::new();
+    for (index, (name, window_ids)) in entries.enumerate() {
+        if !is_valid_bus_name(name) {
+            errors.push(format!(
+                "{}[{}].name: 只能包含字母、数字、点、连字符或下划线",
+                field, index
+            ));
+        } else if !names.insert(name.as_str()) {
+            errors.push(format!("{}[{}].name: {} 重复", field, index, name));
+        }
+        for window_id in window_ids {
+            if window_id != "*" && !is_valid_window_id(window_id) {
+                errors.push(format!(
+                    "{}[{}]: {} 不是有效的插件窗口ID",
+                    field, index, window_id
+                ));
+            }
+        }
+    }
+}
+
+fn is_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && !path.starts_with('\\')
+        && !path.contains(':')
+        && path.split(['/', '\\']).all(|segment| segment != "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ManifestResult<PluginManifest> {
+        PluginManifest::parse(json.as_bytes(), None)
+    }
+
+    fn error_of(json: &str) -> String {
+        parse(json).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_minimal_manifest_with_defaults() {
+        let manifest = parse(r#"{"windowId":"demo","title":"Demo","version":"1.2.0"}"#).unwrap();
+        assert_eq!(manifest.manifest_version, 1);
+        assert_eq!(manifest.entry, "index.html");
+        assert_eq!(manifest.size, [800, 600]);
+        assert_eq!(manifest.kind, PluginKind::Plugin);
+    }
+
+    #[test]
+    fn rejects_window_id_mismatch_and_builtin_labels() {
+        let json = r#"{"windowId":"demo","title":"Demo","version":"1.0.0"}"#;
+        let error = PluginManifest::parse(json.as_bytes(), Some("other"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("与安装的插件 other 不一致"), "{}", error);
+
+        let error = error_of(r#"{"windowId":"main","title":"Main","version":"1.0.0"}"#);
+        assert!(error.contains("内置窗口"), "{}", error);
+    }
+
+    #[test]
+    fn collects_all_errors_in_one_message() {
+        let error = error_of(
+            r#"{"windowId":"demo","title":" ","version":"1.0.0","size":[0,600],"permissions":["nope"]}"#,
+        );
+        assert!(error.contains("title"), "{}", error);
+        assert!(error.contains("size[0]"), "{}", error);
+        assert!(error.contains("permissions[0]"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_newer_host_version() {
+        let error = error_of(
+            r#"{"windowId":"demo","title":"Demo","version":"1.0.0","minHostVersion":"999.0.0"}"#,
+        );
+        assert!(error.contains("minHostVersion"), "{}", error);
+    }
+
+    #[test]
+    fn relative_path_rejects_parent_and_absolute_paths() {
+        for path in [
+            "index.html",
+            "dist/index.html",
+            "a..b/c.html",
+            "./index.html",
+        ] {
+            assert!(is_relative_path(path), "{}", path);
+        }
+        for path in [
+            "",
+            "/index.html",
+            "\\index.html",
+            "C:/index.html",
+            "../index.html",
+            "dist/../../index.html",
+            "dist\\..\\index.html",
+            "..",
+        ] {
+            assert!(!is_relative_path(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn rejects_entry_icon_and_lifecycle_outside_plugin_dir() {
+        let error = error_of(
+            r#"{"windowId":"demo","title":"Demo","version":"1.0.0","entry":"../index.html","icon":"../icon.png","lifecycle":{"onInstall":"/tmp/x.html"}}"#,
+        );
+        assert!(error.contains("entry: ../index.html"), "{}", error);
+        assert!(error.contains("icon: ../icon.png"), "{}", error);
+        assert!(error.contains("lifecycle.onInstall"), "{}", error);
+
+        let manifest = parse(
+            r#"{"windowId":"demo","title":"Demo","version":"1.0.0","icon":"https://example.com/icon.png"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.icon.as_deref(),
+            Some("https://example.com/icon.png")
+        );
+    }
+
+    #[test]
+    fn validates_bus_names_and_window_ids() {
+        let error = error_of(
+            r#"{"windowId":"demo","title":"Demo","version":"1.0.0","bus":{"topics":[{"name":"a b"},{"name":"x","subscribers":["bad id"]}],"methods":[{"name":"m"},{"name":"m","callers":["*"]}]}}"#,
+        );
+        assert!(error.contains("bus.topics[0].name"), "{}", error);
+        assert!(error.contains("bad id"), "{}", error);
+        assert!(error.contains("bus.methods[1].name: m 重复"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_self_dependency() {
+        let error = error_of(
+            r#"{"windowId":"demo","title":"Demo","version":"1.0.0","dependencies":{"demo":"^1"}}"#,
+        );
+        assert!(error.contains("插件不能依赖自身"), "{}", error);
+    }
+
+    #[test]
+    fn window_id_is_ascii_only() {
+        assert!(is_valid_window_id("demo-plugin_2"));
+        for window_id in ["", "插件", "démo", "demo１", "a b", "a/b"] {
+            assert!(!is_valid_window_id(window_id), "{}", window_id);
+        }
+        let error = error_of(r#"{"windowId":"插件","title":"Demo","version":"1.0.0"}"#);
+        assert!(error.contains("windowId"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_reserved_window_ids() {
+        for window_id in ["host", "main", "setting"] {
+            let error = error_of(&format!(
+                r#"{{"windowId":"{}","title":"Demo","version":"1.0.0"}}"#,
+                window_id
+            ));
+            assert!(error.contains("宿主保留的标识"), "{}", error);
+        }
+        let error = error_of(
+            r#"{"windowId":"demo","title":"Demo","version":"1.0.0","dependencies":{"host":"^1"}}"#,
+        );
+        assert!(error.contains("dependencies: host"), "{}", error);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::services::backup::{BackupInfo, BackupService, RestoreReport};
+use crate::utils::error::AppError;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use std::path::{Path, PathBuf};
+
+/// 创建备份
+///
+/// # Arguments
+///
+/// * `target_path` - 备份文件保存路径，为空时保存到数据目录的 Backup 文件夹
+///
+/// # Returns
+///
+/// * `ApiResponse<String>` - 备份文件路径
+#[permission_macro::permission(cap = "backup.manage")]
+#[tauri::command]
+pub async fn backup_create(target_path: Option<String>) -> Result<ApiResponse<String>, AppError> {
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        BackupService::create(target_path.map(PathBuf::from))
+    })
+    .await
+    .map_err(|e| AppError::Error(format!("备份任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(path) => Ok(ApiResponse::success(
+            path.to_string_lossy().replace('\\', "/"),
+        )),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrFileWrite,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 从备份恢复
+///
+/// # Arguments
+///
+/// * `file_path` - 备份文件路径
+/// * `dry_run` - 为 true 时只校验并返回将要发生的变更，不修改任何数据
+///
+/// # Returns
+///
+/// * `ApiResponse<RestoreReport>` - 恢复报告
+#[permission_macro::permission(cap = "backup.manage")]
+#[tauri::command]
+pub async fn backup_restore(
+    file_path: String,
+    dry_run: Option<bool>,
+) -> Result<ApiResponse<RestoreReport>, AppError> {
+    if !Path::new(&file_path).is_file() {
+        return Ok(ApiResponse::error(
+            ApiStatusCode::ErrFileNotFound,
+            "备份文件不存在",
+        ));
+    }
+
+    let dry_run = dry_run.unwrap_or(false);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        BackupService::restore(Path::new(&file_path), dry_run)
+    })
+    .await
+    .map_err(|e| AppError::Error(format!("恢复任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(report) => Ok(ApiResponse::success(report)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrFileRead,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 列出备份目录中的备份
+#[permission_macro::permission(cap = "backup.manage")]
+#[tauri::command]
+pub fn backup_list() -> Result<ApiResponse<Vec<BackupInfo>>, AppError> {
+    match BackupService::list() {
+        Ok(list) => Ok(ApiResponse::success(list)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrFileRead,
+            e.to_string(),
+        )),
+    }
+}
@@ -1,10 +1,15 @@
 use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::dependency::PluginDependencyService;
+use crate::mh_plugin::dev::PluginDevService;
+use crate::mh_plugin::lifecycle::PluginLifecycleService;
+use crate::mh_plugin::state::PluginStateService;
 use crate::utils::error::AppError;
 use crate::utils::reqwest::create_web_client;
-use crate::utils::response::ApiResponse;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
 use serde_json::Value;
 use std::path::Path;
 use tauri::{image::Image, LogicalSize, Manager, WebviewUrl, WebviewWindowBuilder};
+use url::Url;
 
 /// 参考分辨率的宽度（2560x1440为基准）
 const REFERENCE_WIDTH: f64 = 2560.0;
@@ -19,6 +24,7 @@ const SMALL_SCREEN_SCALE_FACTOR: f64 = 1.3;
 ///
 /// * `width` - 基于参考分辨率的目标宽度
 /// * `height` - 基于参考分辨率的目标高度
+#[permission_macro::permission(cap = "window.resize")]
 #[tauri::command]
 pub async fn set_window_size(width: f64, height: f64) -> Result<ApiResponse<()>, AppError> {
     let app_handle = AppHandleManager::clone()
@@ -81,6 +87,7 @@ pub async fn set_window_size(width: f64, height: f64) -> Result<ApiResponse<()>,
 /// * `resizable` - 是否可调整大小
 /// * `icon` - 窗口图标路径
 /// * `loading` - 是否显示加载状态
+#[permission_macro::permission(cap = "window.create")]
 #[tauri::command]
 pub async fn create_new_window(
     window_id: String,
@@ -96,6 +103,33 @@ pub async fn create_new_window(
     let app_handle = AppHandleManager::clone()
         .ok_or_else(|| AppError::Error("获取AppHandle失败".to_string()))?;
 
+    // 已禁用的插件不能打开窗口
+    if PluginStateService::is_disabled(&window_id) {
+        return Ok(ApiResponse::error(
+            ApiStatusCode::ErrPermission,
+            format!("插件 {} 已禁用", window_id),
+        ));
+    }
+
+    // 清单无效的插件需要修复或重新安装后才能打开
+    if let Some(reason) = PluginStateService::invalid_reason(&window_id) {
+        return Ok(ApiResponse::error(
+            ApiStatusCode::ErrPermission,
+            format!("插件 {} 的清单无效: {}", window_id, reason),
+        ));
+    }
+
+    // 共享运行库没有窗口
+    if PluginDependencyService::is_library(&window_id) {
+        return Ok(ApiResponse::error(
+            ApiStatusCode::ErrPermission,
+            format!("{} 是共享运行库，不能打开窗口", window_id),
+        ));
+    }
+
+    // 插件的生命周期钩子正在隐藏窗口中执行时，先结束钩子再打开插件窗口
+    PluginLifecycleService::interrupt(&window_id);
+
     // 检查窗口是否已存在
     if let Some(existing_window) = app_handle.get_webview_window(&window_id) {
         // 如果窗口已存在，则显示并聚焦该窗口
@@ -185,18 +219,34 @@ pub async fn create_new_window(
         }
     };
 
+    // 只有开发模式插件可以从其开发服务器加载，其余页面按应用地址加载
+    let webview_url = match Url::parse(&url) {
+        Ok(parsed)
+            if matches!(parsed.scheme(), "http" | "https")
+                && parsed.host_str() != Some("asset.localhost") =>
+        {
+            if let Err(e) = PluginDevService::authorize_external_url(&window_id, &parsed) {
+                return Ok(ApiResponse::error(
+                    ApiStatusCode::ErrPermission,
+                    e.to_string(),
+                ));
+            }
+            WebviewUrl::External(parsed)
+        }
+        _ => WebviewUrl::App(url.into()),
+    };
+
     // 构建基础窗口配置
-    let mut builder =
-        WebviewWindowBuilder::new(&app_handle, &window_id, WebviewUrl::App(url.into()))
-            .title(title)
-            .shadow(false)
-            .transparent(true)
-            .visible(loading.unwrap_or(false))
-            .decorations(false)
-            .always_on_top(always_on_top.unwrap_or(false))
-            .resizable(resizable.unwrap_or(true))
-            .inner_size(width, height)
-            .position(x, y);
+    let mut builder = WebviewWindowBuilder::new(&app_handle, &window_id, webview_url)
+        .title(title)
+        .shadow(false)
+        .transparent(true)
+        .visible(loading.unwrap_or(false))
+        .decorations(false)
+        .always_on_top(always_on_top.unwrap_or(false))
+        .resizable(resizable.unwrap_or(true))
+        .inner_size(width, height)
+        .position(x, y);
 
     // 处理图标设置
     if let Some(icon_path) = icon {
@@ -281,6 +331,7 @@ impl TryFrom<i32> for WindowOperation {
 ///   - 2: 最大化窗口
 ///   - 3: 还原窗口
 ///   - 4: 切换窗口置顶状态
+#[permission_macro::permission(cap = "window.self")]
 #[tauri::command]
 pub async fn window_control(
     window: tauri::Window,
@@ -329,6 +380,7 @@ pub async fn window_control(
     Ok(ApiResponse::success(()))
 }
 
+#[permission_macro::permission(cap = "window.devtools")]
 #[tauri::command]
 pub fn open_devtools() -> Result<ApiResponse<()>, AppError> {
     if let Some(app_handle) = AppHandleManager::clone() {
@@ -352,6 +404,7 @@ pub fn open_devtools() -> Result<ApiResponse<()>, AppError> {
 /// # Returns
 ///
 /// * `bool` - 文件是否存在
+#[permission_macro::permission(cap = "file.search")]
 #[tauri::command]
 pub fn file_exists(path: String) -> Result<ApiResponse<bool>, AppError> {
     Ok(ApiResponse::success(Path::new(&path).exists()))
@@ -0,0 +1,38 @@
+use crate::services::config_events::{ConfigEvents, ConfigTable};
+use crate::utils::error::AppError;
+use crate::services::permission::PermissionPolicy;
+use crate::utils::response::ApiResponse;
+
+/// 订阅配置变更事件
+///
+/// # Arguments
+///
+/// * `table` - 配置所在的存储
+/// * `keys` - 要订阅的键，空数组表示订阅该存储的全部键
+///
+/// 只能订阅当前窗口有读取权限的存储，变更通过 `config:changed` 事件推送到当前窗口
+#[permission_macro::permission(cap = "config.read")]
+#[tauri::command]
+pub fn subscribe_config_changes(
+    table: ConfigTable,
+    keys: Vec<String>,
+) -> Result<ApiResponse<()>, AppError> {
+    if let Err(denied) = PermissionPolicy::check(
+        window.label(),
+        "subscribe_config_changes",
+        table.read_capability(),
+    ) {
+        return Ok(ApiResponse::permission_denied(denied.to_string()));
+    }
+
+    ConfigEvents::subscribe(window.label(), table, keys);
+    Ok(ApiResponse::success(()))
+}
+
+/// 取消订阅配置变更事件，`table` 为空时取消当前窗口的全部订阅
+#[permission_macro::permission(cap = "config.read")]
+#[tauri::command]
+pub fn unsubscribe_config_changes(table: Option<ConfigTable>) -> Result<ApiResponse<()>, AppError> {
+    ConfigEvents::unsubscribe(window.label(), table);
+    Ok(ApiResponse::success(()))
+}
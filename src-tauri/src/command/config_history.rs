@@ -0,0 +1,72 @@
+use crate::services::config_events::{ConfigEvents, ConfigTable};
+use crate::services::config_history::{ConfigChange, ConfigHistory, RestoredValue};
+use crate::utils::error::AppError;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use chrono::NaiveDateTime;
+
+/// 默认返回的变更记录条数
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+/// 获取最近的配置变更记录，按时间倒序
+///
+/// # Arguments
+///
+/// * `limit` - 返回的最大条数，默认 100
+/// * `key` - 只返回指定配置键的变更
+#[permission_macro::permission(cap = "config.manage")]
+#[tauri::command]
+pub fn config_history_list(
+    limit: Option<u32>,
+    key: Option<String>,
+) -> Result<ApiResponse<Vec<ConfigChange>>, AppError> {
+    match ConfigHistory::list(limit.unwrap_or(DEFAULT_HISTORY_LIMIT), key.as_deref()) {
+        Ok(changes) => Ok(ApiResponse::success(changes)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 撤销指定的配置变更，把对应键恢复为该次变更前的值
+#[permission_macro::permission(cap = "config.manage")]
+#[tauri::command]
+pub fn config_history_revert(id: i64) -> Result<ApiResponse<RestoredValue>, AppError> {
+    match ConfigHistory::revert(id, window.label()) {
+        Ok(restored) => {
+            ConfigEvents::notify(ConfigTable::Config, &restored.key, restored.value.clone());
+            Ok(ApiResponse::success(restored))
+        }
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 把配置恢复到指定时间点的状态
+///
+/// # Arguments
+///
+/// * `timestamp` - 时间点，格式 `YYYY-MM-DD HH:MM:SS`
+/// * `dry_run` - 为 true 时只返回将要恢复的配置，不修改数据
+#[permission_macro::permission(cap = "config.manage")]
+#[tauri::command]
+pub fn config_history_restore(
+    timestamp: String,
+    dry_run: Option<bool>,
+) -> Result<ApiResponse<Vec<RestoredValue>>, AppError> {
+    if NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%d %H:%M:%S").is_err() {
+        return Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            "时间格式应为 YYYY-MM-DD HH:MM:SS",
+        ));
+    }
+
+    let dry_run = dry_run.unwrap_or(false);
+    match ConfigHistory::restore_as_of(&timestamp, dry_run, window.label()) {
+        Ok(restored) => {
+            if !dry_run {
+                for entry in &restored {
+                    ConfigEvents::notify(ConfigTable::Config, &entry.key, entry.value.clone());
+                }
+            }
+            Ok(ApiResponse::success(restored))
+        }
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
@@ -0,0 +1,64 @@
+use crate::services::config_transfer::{
+    ConfigDocument, ConfigTransfer, ExportSelection, ImportMode, ImportReport,
+};
+use crate::utils::error::AppError;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use serde_json::Value;
+
+/// 导出配置为可移植的 JSON 文档
+///
+/// # Arguments
+///
+/// * `selection` - 导出范围，未指定的部分不导出，空数组表示导出该部分的全部条目
+///
+/// # Returns
+///
+/// * `ApiResponse<ConfigDocument>` - 导出的配置文档
+#[permission_macro::permission(cap = "config.manage")]
+#[tauri::command]
+pub fn config_export(selection: ExportSelection) -> Result<ApiResponse<ConfigDocument>, AppError> {
+    match ConfigTransfer::export(&selection) {
+        Ok(document) => Ok(ApiResponse::success(document)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 导入配置文档
+///
+/// # Arguments
+///
+/// * `document` - 由 `config_export` 导出的配置文档
+/// * `mode` - 导入模式：merge 冲突时保留本地值，replace 覆盖本地值
+/// * `dry_run` - 为 true 时只返回导入报告，不写入数据
+///
+/// # Returns
+///
+/// * `ApiResponse<ImportReport>` - 导入报告，包含新增、覆盖和冲突的条目
+#[permission_macro::permission(cap = "config.manage")]
+#[tauri::command]
+pub fn config_import(
+    document: Value,
+    mode: ImportMode,
+    dry_run: Option<bool>,
+) -> Result<ApiResponse<ImportReport>, AppError> {
+    let document: ConfigDocument = match serde_json::from_value(document) {
+        Ok(document) => document,
+        Err(e) => {
+            return Ok(ApiResponse::error(
+                ApiStatusCode::ErrParamsInvalid,
+                format!("配置文档格式无效: {}", e),
+            ))
+        }
+    };
+
+    match ConfigTransfer::import(document, mode, dry_run.unwrap_or(false), window.label()) {
+        Ok(report) => Ok(ApiResponse::success(report)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
@@ -1,38 +1,39 @@
-use crate::services::database::get_db_pool;
+use crate::services::config::ConfigStore;
+use crate::services::config_events::{ConfigEvents, ConfigTable};
+use crate::services::config_history::ConfigHistory;
+use crate::services::config_schema::{ConfigSchema, RepairReport, Validated};
+use crate::services::database::{execute_write, get_db_pool, PLUGIN_HOST_INFO_KEYS};
 use crate::utils::error::AppError;
 use crate::utils::response::{ApiResponse, ApiStatusCode};
+use rusqlite::{params, OptionalExtension};
 use serde_json::{json, Value};
 use simd_json;
 
-#[permission_macro::permission("main", "setting", "my", "label")]
+#[permission_macro::permission(cap = "config.write")]
 #[tauri::command]
 pub fn set_config_value(key: &str, value: Value) -> Result<ApiResponse<()>, AppError> {
-    let pool = get_db_pool();
+    if let Err(errors) = ConfigSchema::validate(key, &value) {
+        return Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            ConfigSchema::format_errors(&errors),
+        ));
+    }
 
-    let json_value = match serde_json::to_string(&value) {
-        Ok(v) => v,
-        Err(e) => {
-            return Ok(ApiResponse::error(
-                ApiStatusCode::ErrDatabase,
-                format!("序列化配置值失败: {}", e),
-            ))
+    let owned_key = key.to_string();
+    let owned_value = value.clone();
+    let source = window.label().to_string();
+    match execute_write(move |conn| {
+        ConfigHistory::write(conn, &owned_key, Some(&owned_value), &source)
+    }) {
+        Ok(_) => {
+            ConfigEvents::notify(ConfigTable::Config, key, Some(value));
+            Ok(ApiResponse::success(()))
         }
-    };
-
-    let conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-    match conn.execute(
-        "INSERT OR REPLACE INTO config (key, value) VALUES (?1, ?2)",
-        [key, &json_value],
-    ) {
-        Ok(_) => Ok(ApiResponse::success(())),
-        Err(e) => Ok(ApiResponse::error(
-            ApiStatusCode::ErrDatabase,
-            format!("保存配置值失败: {}", e),
-        )),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
     }
 }
 
-#[permission_macro::permission("main", "setting", "my", "pluginMarket", "label")]
+#[permission_macro::permission(cap = "config.read")]
 #[tauri::command]
 pub fn get_config_value(key: &str) -> Result<ApiResponse<Option<Value>>, AppError> {
     let pool = get_db_pool();
@@ -101,7 +102,7 @@ pub fn get_config_value(key: &str) -> Result<ApiResponse<Option<Value>>, AppErro
     }
 }
 
-#[permission_macro::permission("main", "setting", "my", "pluginMarket", "label")]
+#[permission_macro::permission(cap = "config.read")]
 #[tauri::command]
 pub fn get_config_values_batch(keys: Vec<&str>) -> Result<ApiResponse<std::collections::HashMap<String, Option<Value>>>, AppError> {
     let pool = get_db_pool();
@@ -203,59 +204,154 @@ pub fn get_config_values_batch(keys: Vec<&str>) -> Result<ApiResponse<std::colle
     Ok(ApiResponse::success(result))
 }
 
-#[permission_macro::permission("main", "setting", "my", "label")]
+#[permission_macro::permission(cap = "config.write")]
 #[tauri::command]
 pub fn delete_config_value(key: &str) -> Result<ApiResponse<()>, AppError> {
-    let pool = get_db_pool();
-    let conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+    let path = if key.is_empty() { Vec::new() } else { vec![key.to_string()] };
+    let result = ConfigStore::delete(path, window.label());
 
-    if key.is_empty() {
-        match conn.execute("DELETE FROM config", []) {
-            Ok(_) => Ok(ApiResponse::success(())),
-            Err(e) => Ok(ApiResponse::error(
-                ApiStatusCode::ErrDatabase,
-                format!("删除所有配置失败: {}", e),
-            )),
+    match result {
+        Ok(_) => {
+            ConfigEvents::notify(ConfigTable::Config, key, None);
+            Ok(ApiResponse::success(()))
         }
-    } else {
-        match conn.execute("DELETE FROM config WHERE key = ?1", [key]) {
-            Ok(_) => Ok(ApiResponse::success(())),
-            Err(e) => Ok(ApiResponse::error(
-                ApiStatusCode::ErrDatabase,
-                format!("删除配置值失败: {}", e),
-            )),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 按路径获取配置
+///
+/// # Arguments
+///
+/// * `path` - 配置路径，第一段为配置键，其余为值内部的嵌套字段，空数组返回全部配置
+#[permission_macro::permission(cap = "config.read")]
+#[tauri::command]
+pub fn get_config_by_path(path: Vec<String>) -> Result<ApiResponse<Option<Value>>, AppError> {
+    match ConfigStore::get(&path) {
+        Ok(value) => Ok(ApiResponse::success(value)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 按路径设置配置，中间层级不存在时自动创建
+#[permission_macro::permission(cap = "config.write")]
+#[tauri::command]
+pub fn set_config_by_path(path: Vec<String>, value: Value) -> Result<ApiResponse<()>, AppError> {
+    let key = path.first().cloned().unwrap_or_default();
+    match ConfigStore::set(path, value, window.label()) {
+        Ok(Validated::Valid(current)) => {
+            ConfigEvents::notify(ConfigTable::Config, &key, Some(current));
+            Ok(ApiResponse::success(()))
+        }
+        Ok(Validated::Invalid(errors)) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            ConfigSchema::format_errors(&errors),
+        )),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 按路径删除配置，空数组表示删除所有配置
+#[permission_macro::permission(cap = "config.write")]
+#[tauri::command]
+pub fn delete_config_by_path(path: Vec<String>) -> Result<ApiResponse<()>, AppError> {
+    let key = path.first().cloned().unwrap_or_default();
+    match ConfigStore::delete(path, window.label()) {
+        Ok(Validated::Valid(current)) => {
+            ConfigEvents::notify(ConfigTable::Config, &key, current);
+            Ok(ApiResponse::success(()))
         }
+        Ok(Validated::Invalid(errors)) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            ConfigSchema::format_errors(&errors),
+        )),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
     }
 }
 
-#[permission_macro::permission("main", "pluginMarket")]
+/// 检查已存储的配置并重置无效值
+///
+/// # Arguments
+///
+/// * `dry_run` - 为 true 时只返回检查报告，不修改数据
+#[permission_macro::permission(cap = "config.manage")]
+#[tauri::command]
+pub fn repair_config_values(dry_run: Option<bool>) -> Result<ApiResponse<RepairReport>, AppError> {
+    match ConfigSchema::repair(dry_run.unwrap_or(false), window.label()) {
+        Ok(report) => {
+            if !report.dry_run {
+                for entry in &report.invalid {
+                    ConfigEvents::notify(ConfigTable::Config, &entry.key, None);
+                }
+            }
+            Ok(ApiResponse::success(report))
+        }
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+#[permission_macro::permission(cap = "plugin.config")]
 #[tauri::command]
 pub fn set_plugin_config_value(
     window_id: &str,
-    info: Value,
+    mut info: Value,
     config: Value,
     data: Value,
 ) -> Result<ApiResponse<()>, AppError> {
-    let pool = get_db_pool();
-    let conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-
-    let info_str = serde_json::to_string(&info)
-        .map_err(|e| AppError::Error(format!("序列化info失败: {}", e)))?;
     let config_str = serde_json::to_string(&config)
         .map_err(|e| AppError::Error(format!("序列化config失败: {}", e)))?;
     let data_str = serde_json::to_string(&data)
         .map_err(|e| AppError::Error(format!("序列化data失败: {}", e)))?;
 
-    conn.execute(
-        "INSERT OR REPLACE INTO plugin_config (window_id, info, config, data) VALUES (?1, ?2, ?3, ?4)",
-        [window_id, &info_str, &config_str, &data_str],
-    )
-    .map_err(|e| AppError::Error(format!("保存插件配置失败: {}", e)))?;
+    let enabled = config
+        .get("isEnabled")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+
+    let owned_window_id = window_id.to_string();
+    info = execute_write(move |conn| {
+        // 校验结果、回滚版本等字段由宿主维护，保留已有的值，忽略前端传入的值
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT info FROM plugin_config WHERE window_id = ?1",
+                [&owned_window_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Error(format!("读取插件配置失败: {}", e)))?;
+        let existing: Value = existing
+            .and_then(|info| serde_json::from_str(&info).ok())
+            .unwrap_or(Value::Null);
+        if let Some(info) = info.as_object_mut() {
+            for key in PLUGIN_HOST_INFO_KEYS {
+                info.remove(key);
+                if let Some(value) = existing.get(key) {
+                    info.insert(key.to_string(), value.clone());
+                }
+            }
+        }
 
+        let info_str = serde_json::to_string(&info)
+            .map_err(|e| AppError::Error(format!("序列化info失败: {}", e)))?;
+        // 启用状态由 mh_plugin_set_enabled 维护，已有记录保留原状态，新记录沿用 config.isEnabled
+        conn.execute(
+            "INSERT INTO plugin_config (window_id, info, config, data, enabled) VALUES (?1, ?2, ?3, ?4, ?5) \
+             ON CONFLICT(window_id) DO UPDATE SET info = excluded.info, config = excluded.config, data = excluded.data",
+            params![owned_window_id, info_str, config_str, data_str, enabled],
+        )
+        .map_err(|e| AppError::Error(format!("保存插件配置失败: {}", e)))?;
+        Ok(info)
+    })?;
+
+    ConfigEvents::notify(
+        ConfigTable::PluginConfig,
+        window_id,
+        Some(serde_json::json!({ "info": info, "config": config, "data": data })),
+    );
     Ok(ApiResponse::success(()))
 }
 
-#[permission_macro::permission("main", "pluginMarket")]
+#[permission_macro::permission(cap = "plugin.config")]
 #[tauri::command]
 pub fn get_plugin_config_value(
     window_id: Option<&str>,
@@ -264,10 +360,10 @@ pub fn get_plugin_config_value(
     let conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
 
     let mut stmt = if let Some(_id) = window_id {
-        conn.prepare("SELECT window_id, info, config, data FROM plugin_config WHERE window_id = ?1")
+        conn.prepare("SELECT window_id, info, config, data, enabled FROM plugin_config WHERE window_id = ?1")
             .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?
     } else {
-        conn.prepare("SELECT window_id, info, config, data FROM plugin_config")
+        conn.prepare("SELECT window_id, info, config, data, enabled FROM plugin_config")
             .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?
     };
 
@@ -296,6 +392,9 @@ pub fn get_plugin_config_value(
         let data: String = row
             .get(3)
             .map_err(|e| AppError::Error(format!("获取data失败: {}", e)))?;
+        let enabled: bool = row
+            .get(4)
+            .map_err(|e| AppError::Error(format!("获取enabled失败: {}", e)))?;
 
         // 使用simd-json加速解析
         let mut info_bytes = info.into_bytes();
@@ -306,11 +405,15 @@ pub fn get_plugin_config_value(
         };
 
         let mut config_bytes = config.into_bytes();
-        let config_value: Value = match simd_json::serde::from_slice(&mut config_bytes) {
+        let mut config_value: Value = match simd_json::serde::from_slice(&mut config_bytes) {
             Ok(v) => v,
             Err(_) => serde_json::from_slice(&config_bytes)
                 .map_err(|e| AppError::Error(format!("解析config失败: {}", e)))?,
         };
+        // 前端通过 config.isEnabled 读取启用状态，以 enabled 列为准
+        if let Some(config) = config_value.as_object_mut() {
+            config.insert("isEnabled".to_string(), Value::Bool(enabled));
+        }
 
         let mut data_bytes = data.into_bytes();
         let data_value: Value = match simd_json::serde::from_slice(&mut data_bytes) {
@@ -330,19 +433,20 @@ pub fn get_plugin_config_value(
     Ok(ApiResponse::success(result))
 }
 
-#[permission_macro::permission("main", "pluginMarket")]
+#[permission_macro::permission(cap = "plugin.config")]
 #[tauri::command]
 pub fn delete_plugin_config_value(window_id: Option<&str>) -> Result<ApiResponse<()>, AppError> {
-    let pool = get_db_pool();
-    let conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-
-    if let Some(id) = window_id {
-        conn.execute("DELETE FROM plugin_config WHERE window_id = ?1", [id])
-            .map_err(|e| AppError::Error(format!("删除插件配置失败: {}", e)))?;
-    } else {
-        conn.execute("DELETE FROM plugin_config", [])
-            .map_err(|e| AppError::Error(format!("删除所有插件配置失败: {}", e)))?;
-    }
+    let owned_window_id = window_id.map(str::to_string);
+    execute_write(move |conn| {
+        if let Some(id) = owned_window_id {
+            conn.execute("DELETE FROM plugin_config WHERE window_id = ?1", [id])
+                .map_err(|e| AppError::Error(format!("删除插件配置失败: {}", e)))
+        } else {
+            conn.execute("DELETE FROM plugin_config", [])
+                .map_err(|e| AppError::Error(format!("删除所有插件配置失败: {}", e)))
+        }
+    })?;
 
+    ConfigEvents::notify(ConfigTable::PluginConfig, window_id.unwrap_or_default(), None);
     Ok(ApiResponse::success(()))
 }
@@ -3,6 +3,7 @@ use crate::{
     utils::{error::AppError, response::ApiResponse},
 };
 
+#[permission_macro::permission(cap = "file.search")]
 #[tauri::command]
 pub async fn fd_search(
     options: SearchOptions,
@@ -1,8 +1,8 @@
-// 应用处理器宏，按功能模块分组
+// 应用处理器宏，按功能模块分组，每个命令都必须声明权限策略
 #[macro_export]
 macro_rules! generate_app_handlers {
     () => {
-        tauri::generate_handler![
+        permission_macro::handlers![
             // 窗口管理
             set_window_size,
             create_new_window,
@@ -22,13 +22,35 @@ macro_rules! generate_app_handlers {
             get_config_values_batch,
             set_config_value,
             delete_config_value,
+            get_config_by_path,
+            set_config_by_path,
+            delete_config_by_path,
+            repair_config_values,
             set_plugin_config_value,
             get_plugin_config_value,
             delete_plugin_config_value,
+            config_export,
+            config_import,
+            subscribe_config_changes,
+            unsubscribe_config_changes,
+            config_history_list,
+            config_history_revert,
+            config_history_restore,
             // 宠物配置管理
             set_pet_config,
             get_pet_config,
             delete_pet_config,
+            // 备份与恢复
+            backup_create,
+            backup_restore,
+            backup_list,
+            // 配置方案
+            profile_list,
+            profile_create,
+            profile_rename,
+            profile_delete,
+            profile_switch,
+            profile_set_auto_switch,
             // Live2D 模型导入管理
             import_live2d_model,
             get_all_live2d_models,
@@ -40,16 +62,45 @@ macro_rules! generate_app_handlers {
             paste,
             // 插件管理
             mh_plugin_install,
+            mh_plugin_cancel_install,
             mh_plugin_install_local,
             mh_plugin_uninstall,
             mh_plugin_analyze_package,
+            mh_plugin_rollback,
+            mh_plugin_lifecycle_done,
+            mh_plugin_set_enabled,
+            mh_plugin_dev_register,
+            mh_plugin_dev_unregister,
+            mh_plugin_check_updates,
+            mh_plugin_capabilities,
+            mh_plugin_set_grants,
+            mh_plugin_update_all,
+            mh_plugin_trusted_publishers,
+            mh_plugin_trust_publisher,
+            mh_plugin_untrust_publisher,
+            mh_plugin_storage_usage,
+            mh_plugin_set_storage_quota,
+            mh_plugin_storage_clear_orphaned,
+            mh_plugin_unresolved_dependencies,
             mh_get_self_config,
             mh_set_self_config,
             mh_delete_self_config,
+            mh_storage_get,
+            mh_storage_set,
+            mh_storage_delete,
+            mh_storage_list,
+            // 插件消息总线
+            mh_bus_subscribe,
+            mh_bus_unsubscribe,
+            mh_bus_publish,
+            mh_bus_request,
+            mh_bus_respond,
             // 状态和数据
             set_global_data,
             get_global_data,
             delete_global_data,
+            // 权限审计
+            permission_audit_list,
             // 通用功能
             file_exists,
             open_web_or_app,
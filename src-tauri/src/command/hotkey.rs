@@ -19,30 +19,24 @@ pub struct HotkeyConfig {
     pub items: HashMap<String, HotkeyItem>,
 }
 
+#[permission_macro::permission(cap = "hotkey.manage")]
 #[tauri::command]
 pub fn set_hotkey_enabled(config: HotkeyConfig) -> Result<ApiResponse<()>, AppError> {
+    apply_hotkey_config(&config);
+    Ok(ApiResponse::success(()))
+}
+
+/// 按快捷键配置注册全局快捷键
+pub fn apply_hotkey_config(config: &HotkeyConfig) {
     // 总开关关闭时直接禁用所有快捷键
     if !config.enabled {
         HotkeyManager::global().set_enabled(false);
-        return Ok(ApiResponse::success(()));
+        return;
     }
 
     // 总开关打开，根据各项配置设置快捷键
     let mut hotkeys = Vec::new();
 
-    // 辅助函数：标准化快捷键格式
-    fn normalize_hotkey(key: &str) -> String {
-        let mut result = key.to_lowercase().replace(" ", "");
-
-        // 处理常见的修饰键别名
-        result = result.replace("ctrl+", "control+");
-        result = result.replace("cmd+", "command+");
-        result = result.replace("opt+", "option+");
-        result = result.replace("win+", "meta+");
-
-        result
-    }
-
     // 统一处理所有快捷键项
     for (name, item) in config.items.iter() {
         if item.enabled && !item.key.is_empty() {
@@ -53,5 +47,17 @@ pub fn set_hotkey_enabled(config: HotkeyConfig) -> Result<ApiResponse<()>, AppEr
 
     // 更新快捷键配置
     HotkeyManager::global().set_hotkeys(hotkeys);
-    Ok(ApiResponse::success(()))
+}
+
+// 辅助函数：标准化快捷键格式
+fn normalize_hotkey(key: &str) -> String {
+    let mut result = key.to_lowercase().replace(" ", "");
+
+    // 处理常见的修饰键别名
+    result = result.replace("ctrl+", "control+");
+    result = result.replace("cmd+", "command+");
+    result = result.replace("opt+", "option+");
+    result = result.replace("win+", "meta+");
+
+    result
 }
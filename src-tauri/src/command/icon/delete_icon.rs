@@ -8,6 +8,7 @@ use std::fs;
  * @param filename 图片文件名（不含路径）
  * @param app_type 应用类型，0为网页图标，1为应用图标
  */
+#[permission_macro::permission(cap = "icon.write")]
 #[tauri::command]
 pub fn delete_icon(filename: &str, app_type: u32) -> Result<ApiResponse<()>, AppError> {
     // 获取基础路径
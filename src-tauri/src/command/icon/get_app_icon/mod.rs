@@ -8,6 +8,7 @@ mod win;
 use crate::utils::error::AppError;
 use crate::utils::response::ApiResponse;
 
+#[permission_macro::permission(cap = "icon.read")]
 #[tauri::command]
 pub fn get_app_icon(exe_path: &str) -> Result<ApiResponse<String>, AppError> {
     #[cfg(target_os = "windows")]
@@ -143,6 +143,7 @@ fn extract_icon_urls(html: &str) -> Vec<String> {
 /// # Returns
 ///
 /// * `AppResult<String>` - 成功返回保存的图标文件路径
+#[permission_macro::permission(cap = "icon.read")]
 #[tauri::command]
 pub async fn get_web_icon(url: String) -> Result<ApiResponse<String>, AppError> {
     // 检查并补全 URL
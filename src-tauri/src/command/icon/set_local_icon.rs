@@ -17,6 +17,7 @@ use crate::utils::response::ApiStatusCode;
  * @param image_path 图片路径
  * @param app_type 应用类型，0为网页图标，1为应用图标
  */
+#[permission_macro::permission(cap = "icon.write")]
 #[tauri::command]
 pub fn set_local_icon(image_path: &str, app_type: u32) -> Result<ApiResponse<String>, AppError> {
     let myhelper_path = get_myhelper_path().map_err(|e| AppError::Error(e))?;
@@ -83,6 +84,7 @@ pub fn set_local_icon(image_path: &str, app_type: u32) -> Result<ApiResponse<Str
  * 设置 logo 图标
  * @param image_base64 图片的 base64 编码
  */
+#[permission_macro::permission(cap = "icon.write")]
 #[tauri::command]
 pub fn set_logo(image_base64: &str) -> Result<ApiResponse<String>, AppError> {
     let myhelper_path = get_myhelper_path().map_err(|e| AppError::Error(e))?;
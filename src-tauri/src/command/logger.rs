@@ -3,6 +3,7 @@ use crate::{
     utils::{error::AppError, response::ApiResponse},
 };
 
+#[permission_macro::permission(cap = "log.write")]
 #[tauri::command]
 pub async fn write_log(entry: LogEntry) -> Result<ApiResponse<()>, AppError> {
     Logger::write_log(entry)
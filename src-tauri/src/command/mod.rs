@@ -1,24 +1,36 @@
+pub mod backup;
 pub mod common;
 pub mod config;
+pub mod config_events;
+pub mod config_history;
+pub mod config_transfer;
 pub mod database;
 pub mod handlers;
 pub mod hotkey;
 pub mod icon;
 pub mod logger;
 pub mod open_web_or_app;
+pub mod permission;
 pub mod pet;
+pub mod profile;
 pub mod quick_input;
 pub mod state;
 pub mod fd_search;
 
+pub use backup::*;
 pub use common::*;
 pub use config::*;
+pub use config_events::*;
+pub use config_history::*;
+pub use config_transfer::*;
 pub use database::*;
 pub use hotkey::*;
 pub use icon::*;
 pub use logger::*;
 pub use open_web_or_app::*;
+pub use permission::*;
 pub use pet::*;
+pub use profile::*;
 pub use quick_input::*;
 pub use state::*;
 pub use fd_search::*;
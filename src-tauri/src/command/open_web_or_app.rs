@@ -16,7 +16,7 @@ use std::process::Command;
 ///
 /// * `AppResult<()>` - 操作成功返回 Ok(()), 失败返回错误信息
 
-#[permission_macro::permission("main")]
+#[permission_macro::permission(cap = "url.open")]
 #[tauri::command]
 pub fn open_web_or_app(path: String) -> Result<ApiResponse<()>, AppError> {
     // 首先尝试使用 open::that
@@ -0,0 +1,30 @@
+use crate::services::permission::{PermissionAuditEntry, PermissionPolicy};
+use crate::utils::error::AppError;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+
+/// 默认返回的拒绝记录条数
+const DEFAULT_AUDIT_LIMIT: u32 = 100;
+
+/// 获取最近被拒绝的命令调用，按时间倒序
+///
+/// # Arguments
+///
+/// * `limit` - 返回的最大条数，默认 100
+/// * `window_label` - 只返回指定窗口的记录
+#[permission_macro::permission(cap = "permission.audit")]
+#[tauri::command]
+pub fn permission_audit_list(
+    limit: Option<u32>,
+    window_label: Option<String>,
+) -> Result<ApiResponse<Vec<PermissionAuditEntry>>, AppError> {
+    match PermissionPolicy::audit_log(
+        limit.unwrap_or(DEFAULT_AUDIT_LIMIT),
+        window_label.as_deref(),
+    ) {
+        Ok(entries) => Ok(ApiResponse::success(entries)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
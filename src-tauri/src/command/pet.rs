@@ -1,4 +1,5 @@
-use crate::services::database::get_db_pool;
+use crate::services::config_events::{ConfigEvents, ConfigTable};
+use crate::services::database::{execute_write, get_db_pool};
 use crate::utils::error::{AppError, AppResult};
 use crate::utils::path::get_myhelper_path;
 use crate::utils::response::{ApiResponse, ApiStatusCode};
@@ -75,11 +76,9 @@ fn is_zip_file(file_path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-#[permission_macro::permission("main", "setting", "my")]
+#[permission_macro::permission(cap = "pet.config")]
 #[tauri::command]
 pub fn set_pet_config(config_type: &str, config_data: Value) -> Result<ApiResponse<()>, AppError> {
-    let pool = get_db_pool();
-
     let json_value = match serde_json::to_string(&config_data) {
         Ok(v) => v,
         Err(e) => {
@@ -90,22 +89,23 @@ pub fn set_pet_config(config_type: &str, config_data: Value) -> Result<ApiRespon
         }
     };
 
-    let conn = pool
-        .get()
-        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-    match conn.execute(
-        "INSERT OR REPLACE INTO pet_config (config_type, config_data, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
-        [config_type, &json_value],
-    ) {
-        Ok(_) => Ok(ApiResponse::success(())),
-        Err(e) => Ok(ApiResponse::error(
-            ApiStatusCode::ErrDatabase,
-            format!("保存宠物配置失败: {}", e),
-        )),
+    let owned_config_type = config_type.to_string();
+    match execute_write(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO pet_config (config_type, config_data, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+            [&owned_config_type, &json_value],
+        )
+        .map_err(|e| AppError::Error(format!("保存宠物配置失败: {}", e)))
+    }) {
+        Ok(_) => {
+            ConfigEvents::notify(ConfigTable::PetConfig, config_type, Some(config_data));
+            Ok(ApiResponse::success(()))
+        }
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
     }
 }
 
-#[permission_macro::permission("main", "setting", "my")]
+#[permission_macro::permission(cap = "pet.config")]
 #[tauri::command]
 pub fn get_pet_config(config_type: &str) -> Result<ApiResponse<Option<Value>>, AppError> {
     let pool = get_db_pool();
@@ -165,28 +165,27 @@ pub fn get_pet_config(config_type: &str) -> Result<ApiResponse<Option<Value>>, A
     }
 }
 
-#[permission_macro::permission("main", "setting", "my")]
+#[permission_macro::permission(cap = "pet.config")]
 #[tauri::command]
 pub fn delete_pet_config(config_type: &str) -> Result<ApiResponse<()>, AppError> {
-    let pool = get_db_pool();
-    let conn = pool
-        .get()
-        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-
-    match conn.execute(
-        "DELETE FROM pet_config WHERE config_type = ?1",
-        [config_type],
-    ) {
-        Ok(_) => Ok(ApiResponse::success(())),
-        Err(e) => Ok(ApiResponse::error(
-            ApiStatusCode::ErrDatabase,
-            format!("删除宠物配置失败: {}", e),
-        )),
+    let owned_config_type = config_type.to_string();
+    match execute_write(move |conn| {
+        conn.execute(
+            "DELETE FROM pet_config WHERE config_type = ?1",
+            [&owned_config_type],
+        )
+        .map_err(|e| AppError::Error(format!("删除宠物配置失败: {}", e)))
+    }) {
+        Ok(_) => {
+            ConfigEvents::notify(ConfigTable::PetConfig, config_type, None);
+            Ok(ApiResponse::success(()))
+        }
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
     }
 }
 
 /// 导入 Live2D 模型
-#[permission_macro::permission("main", "setting")]
+#[permission_macro::permission(cap = "pet.model")]
 #[tauri::command]
 pub async fn import_live2d_model(
     app: AppHandle,
@@ -257,7 +256,7 @@ pub async fn import_live2d_model(
 }
 
 /// 删除用户导入的模型
-#[permission_macro::permission("main", "setting")]
+#[permission_macro::permission(cap = "pet.model")]
 #[tauri::command]
 pub async fn delete_user_live2d_model(
     app: AppHandle,
@@ -299,7 +298,7 @@ pub async fn delete_user_live2d_model(
 // 旧的单文件导入复制函数，已不再使用
 
 /// 获取所有 Live2D 模型（预置+用户导入）
-#[permission_macro::permission("main", "setting")]
+#[permission_macro::permission(cap = "pet.model")]
 #[tauri::command]
 pub async fn get_all_live2d_models(
     app: AppHandle,
@@ -0,0 +1,103 @@
+use crate::services::profile::{AutoSwitchRule, ProfileInfo, ProfileService};
+use crate::utils::error::AppError;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+
+/// 获取所有配置方案
+#[permission_macro::permission(cap = "profile.manage")]
+#[tauri::command]
+pub fn profile_list() -> Result<ApiResponse<Vec<ProfileInfo>>, AppError> {
+    match ProfileService::list() {
+        Ok(profiles) => Ok(ApiResponse::success(profiles)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 创建配置方案
+///
+/// # Arguments
+///
+/// * `name` - 方案名称
+/// * `clone_from` - 要复制的方案名称，为空时创建使用默认配置的空白方案
+#[permission_macro::permission(cap = "profile.manage")]
+#[tauri::command]
+pub async fn profile_create(
+    name: String,
+    clone_from: Option<String>,
+) -> Result<ApiResponse<()>, AppError> {
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        ProfileService::create(&name, clone_from)
+    })
+    .await
+    .map_err(|e| AppError::Error(format!("配置方案任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 重命名配置方案
+#[permission_macro::permission(cap = "profile.manage")]
+#[tauri::command]
+pub async fn profile_rename(name: String, new_name: String) -> Result<ApiResponse<()>, AppError> {
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        ProfileService::rename(&name, &new_name)
+    })
+    .await
+    .map_err(|e| AppError::Error(format!("配置方案任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 删除配置方案
+#[permission_macro::permission(cap = "profile.manage")]
+#[tauri::command]
+pub async fn profile_delete(name: String) -> Result<ApiResponse<()>, AppError> {
+    let result = tauri::async_runtime::spawn_blocking(move || ProfileService::delete(&name))
+        .await
+        .map_err(|e| AppError::Error(format!("配置方案任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 切换配置方案，切换后重新应用快捷键并发送 `profile:switched` 事件
+#[permission_macro::permission(cap = "profile.manage")]
+#[tauri::command]
+pub async fn profile_switch(name: String) -> Result<ApiResponse<()>, AppError> {
+    let source = window.label().to_string();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        ProfileService::switch(&name, &source)
+    })
+    .await
+    .map_err(|e| AppError::Error(format!("配置方案任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrDatabase, e.to_string())),
+    }
+}
+
+/// 设置配置方案的自动切换规则，`rule` 为空时关闭自动切换
+#[permission_macro::permission(cap = "profile.manage")]
+#[tauri::command]
+pub async fn profile_set_auto_switch(
+    name: String,
+    rule: Option<AutoSwitchRule>,
+) -> Result<ApiResponse<()>, AppError> {
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        ProfileService::set_auto_switch(&name, rule)
+    })
+    .await
+    .map_err(|e| AppError::Error(format!("配置方案任务执行失败: {}", e)))?;
+
+    match result {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrParamsInvalid, e.to_string())),
+    }
+}
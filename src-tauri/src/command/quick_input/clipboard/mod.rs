@@ -100,6 +100,7 @@ impl ClipboardHandler for Manager {
 /// 启动剪贴板监听
 ///
 /// 开始监听系统剪贴板的变化，当内容更新时触发事件
+#[permission_macro::permission(cap = "clipboard.read")]
 #[tauri::command]
 pub async fn start_clipboard_listener() -> Result<ApiResponse<()>, AppError> {
     if CLIPBOARD_LISTENER.load(Ordering::SeqCst) {
@@ -139,6 +140,7 @@ pub async fn start_clipboard_listener() -> Result<ApiResponse<()>, AppError> {
 /// 停止剪贴板监听
 ///
 /// 停止监听系统剪贴板的变化
+#[permission_macro::permission(cap = "clipboard.read")]
 #[tauri::command]
 pub async fn stop_clipboard_listener() -> Result<ApiResponse<()>, AppError> {
     if CLIPBOARD_LISTENER.load(Ordering::SeqCst) {
@@ -156,6 +158,7 @@ pub async fn stop_clipboard_listener() -> Result<ApiResponse<()>, AppError> {
 /// # Arguments
 ///
 /// * `text` - 要写入剪贴板的文本内容
+#[permission_macro::permission(cap = "clipboard.write")]
 #[tauri::command]
 pub async fn write_clipboard(text: String) -> Result<ApiResponse<()>, AppError> {
     // 设置标志位，标记这是内部操作
@@ -21,6 +21,7 @@ fn focus_previous_window() -> Result<(), String> {
     Ok(())
 }
 
+#[permission_macro::permission(cap = "input.paste")]
 #[tauri::command]
 pub async fn paste() -> Result<ApiResponse<()>, AppError> {
     if let Err(e) = focus_previous_window() {
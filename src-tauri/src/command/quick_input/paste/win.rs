@@ -24,6 +24,7 @@ fn focus_previous_window() -> Result<(), String> {
     Ok(())
 }
 
+#[permission_macro::permission(cap = "input.paste")]
 #[tauri::command]
 pub async fn paste() -> Result<ApiResponse<()>, AppError> {
     let mut enigo = match Enigo::new(&Settings::default()) {
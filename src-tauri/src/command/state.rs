@@ -11,7 +11,7 @@ pub struct GlobalData {
     data: RwLock<HashMap<String, Value>>,
 }
 
-#[permission_macro::permission("main", "setting", "my", "pluginMarket")]
+#[permission_macro::permission(cap = "state.write")]
 #[tauri::command]
 pub async fn set_global_data(
     state: State<'_, Arc<GlobalData>>,
@@ -23,7 +23,7 @@ pub async fn set_global_data(
     Ok(ApiResponse::success(()))
 }
 
-#[permission_macro::permission("main", "setting", "my", "pluginMarket")]
+#[permission_macro::permission(cap = "state.read")]
 #[tauri::command]
 pub async fn get_global_data(
     state: State<'_, Arc<GlobalData>>,
@@ -36,7 +36,7 @@ pub async fn get_global_data(
     }
 }
 
-#[permission_macro::permission("main", "setting", "my")]
+#[permission_macro::permission(cap = "state.delete")]
 #[tauri::command]
 pub async fn delete_global_data(
     state: State<'_, Arc<GlobalData>>, 
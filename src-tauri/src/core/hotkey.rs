@@ -1,4 +1,5 @@
 use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::state::{PluginStateService, PLUGIN_HOTKEY_PREFIX};
 use crate::services::logger::{LogEntry, Logger};
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
@@ -9,6 +10,12 @@ use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 // 存储全局快捷键管理器状态
 pub struct HotkeyManager {
     enabled: RwLock<bool>,
+    // 快捷键总开关是否打开
+    switched_on: RwLock<bool>,
+    // 用户配置的快捷键
+    config_hotkeys: RwLock<Vec<(String, String)>>,
+    // 已启用插件声明的快捷键
+    plugin_hotkeys: RwLock<Vec<(String, String)>>,
     // 存储快捷键配置
     hotkeys: RwLock<HashMap<String, String>>,
     // 反向映射
@@ -19,6 +26,9 @@ impl HotkeyManager {
     pub fn new() -> Self {
         HotkeyManager {
             enabled: RwLock::new(false),
+            switched_on: RwLock::new(false),
+            config_hotkeys: RwLock::new(Vec::new()),
+            plugin_hotkeys: RwLock::new(Vec::new()),
             hotkeys: RwLock::new(HashMap::new()),
             shortcut_to_action: RwLock::new(HashMap::new()),
         }
@@ -30,6 +40,70 @@ impl HotkeyManager {
 
     // 设置新的快捷键配置
     pub fn set_hotkeys(&self, hotkeys: Vec<(String, String)>) -> bool {
+        *self.switched_on.write() = true;
+        *self.config_hotkeys.write() = hotkeys;
+        self.apply_hotkeys()
+    }
+
+    // 设置插件快捷键，总开关关闭时只记录不注册
+    pub fn set_plugin_hotkeys(&self, hotkeys: Vec<(String, String)>) -> bool {
+        *self.plugin_hotkeys.write() = hotkeys;
+        if *self.switched_on.read() {
+            self.apply_hotkeys()
+        } else {
+            self.rebuild_hotkey_maps(self.merged_hotkeys());
+            true
+        }
+    }
+
+    // 合并用户和插件的快捷键，插件快捷键与用户快捷键冲突时忽略插件快捷键
+    fn merged_hotkeys(&self) -> Vec<(String, String)> {
+        let mut hotkeys = self.config_hotkeys.read().clone();
+        for (action, key) in self.plugin_hotkeys.read().iter() {
+            let normalized_key = self.normalize_shortcut(key);
+            if hotkeys
+                .iter()
+                .any(|(_, used)| self.normalize_shortcut(used) == normalized_key)
+            {
+                Logger::write_log(LogEntry {
+                    level: "warn".to_string(),
+                    message: format!("插件快捷键 {} 与已有快捷键 {} 冲突", action, key),
+                    timestamp: String::new(),
+                    details: None,
+                })
+                .unwrap_or_else(|_| {});
+                continue;
+            }
+            hotkeys.push((action.clone(), key.clone()));
+        }
+        hotkeys
+    }
+
+    // 按快捷键列表重建正反向映射
+    fn rebuild_hotkey_maps(&self, hotkeys: Vec<(String, String)>) {
+        let mut hotkeys_map = self.hotkeys.write();
+        let mut shortcut_map = self.shortcut_to_action.write();
+
+        // 清空现有配置
+        hotkeys_map.clear();
+        shortcut_map.clear();
+
+        // 预先分配容量，避免重新分配
+        hotkeys_map.reserve(hotkeys.len());
+        shortcut_map.reserve(hotkeys.len());
+
+        // 批量插入新键
+        for (action, key) in hotkeys {
+            // 标准化快捷键格式
+            let normalized_key = self.normalize_shortcut(&key);
+            hotkeys_map.insert(action.clone(), normalized_key.clone());
+            shortcut_map.insert(normalized_key, action);
+        }
+    }
+
+    // 重新注册用户和插件的快捷键
+    fn apply_hotkeys(&self) -> bool {
+        let hotkeys = self.merged_hotkeys();
         if let Some(app_handle) = AppHandleManager::get() {
             // 先取消所有已注册的快捷键
             if self.is_enabled() {
@@ -61,26 +135,7 @@ impl HotkeyManager {
             }
 
             // 更新快捷键配置
-            {
-                let mut hotkeys_map = self.hotkeys.write();
-                let mut shortcut_map = self.shortcut_to_action.write();
-
-                // 清空现有配置
-                hotkeys_map.clear();
-                shortcut_map.clear();
-
-                // 预先分配容量，避免重新分配
-                hotkeys_map.reserve(hotkeys.len());
-                shortcut_map.reserve(hotkeys.len());
-
-                // 批量插入新键
-                for (action, key) in hotkeys {
-                    // 标准化快捷键格式
-                    let normalized_key = self.normalize_shortcut(&key);
-                    hotkeys_map.insert(action.clone(), normalized_key.clone());
-                    shortcut_map.insert(normalized_key, action);
-                }
-            }
+            self.rebuild_hotkey_maps(hotkeys);
 
             // 注册新的快捷键
             if let Err(e) = self.register_configured_hotkeys(app_handle) {
@@ -103,6 +158,8 @@ impl HotkeyManager {
     }
 
     pub fn set_enabled(&self, enabled: bool) -> bool {
+        *self.switched_on.write() = enabled;
+
         // 首先检查状态是否改变，避免不必要的操作
         {
             let current = *self.enabled.read();
@@ -297,6 +354,11 @@ pub fn init() -> impl tauri::plugin::Plugin<tauri::Wry> {
                         let _ = app.emit("hotkey-triggered", &action);
                     }
 
+                    // 插件快捷键只发送到对应的插件窗口
+                    action if action.starts_with(PLUGIN_HOTKEY_PREFIX) => {
+                        PluginStateService::trigger_hotkey(action);
+                    }
+
                     // 默认情况下，所有其他动作都通知前端处理
                     _ => {
                         let _ = app.emit("hotkey-triggered", &action);
@@ -1,6 +1,13 @@
 use crate::command::quick_input::clipboard::observe_app;
+use crate::mh_plugin::dev::PluginDevService;
+use crate::mh_plugin::lifecycle::PluginLifecycleService;
+use crate::mh_plugin::state::PluginStateService;
 use crate::mh_plugin::sync::sync_plugins;
+use crate::mh_plugin::update::PluginUpdateService;
+use crate::services::backup::BackupService;
+use crate::services::config::ConfigStore;
 use crate::services::database::init_database;
+use crate::services::profile::ProfileService;
 use crate::utils::error::{AppError, AppResult};
 
 /// 初始化应用
@@ -8,14 +15,47 @@ pub fn init_app() -> AppResult<()> {
     // 初始化数据库
     init_database().map_err(|e| AppError::Error(format!("初始化数据库失败: {}", e)))?;
 
+    // 迁移旧版 config.json，失败时保留原文件，下次启动重试
+    if let Err(e) = ConfigStore::migrate_legacy_file() {
+        let app_error = AppError::from(format!("迁移 config.json 失败: {}", e));
+        eprintln!("{}", app_error);
+    }
+
     // 同步插件配置
     tauri::async_runtime::spawn(async {
         if let Err(e) = sync_plugins().await {
             let app_error = AppError::from(format!("同步插件失败: {}", e));
             eprintln!("{}", app_error);
         }
+
+        // 继续监听开发模式插件的目录
+        if let Err(e) = PluginDevService::start_watchers() {
+            let app_error = AppError::from(format!("监听开发模式插件失败: {}", e));
+            eprintln!("{}", app_error);
+        }
+
+        // 注册已启用插件的快捷键
+        if let Err(e) = PluginStateService::refresh_hotkeys() {
+            let app_error = AppError::from(format!("注册插件快捷键失败: {}", e));
+            eprintln!("{}", app_error);
+        }
+
+        // 执行插件的 onStartup 钩子
+        if let Err(e) = PluginLifecycleService::run_startup_hooks().await {
+            let app_error = AppError::from(format!("执行插件启动钩子失败: {}", e));
+            eprintln!("{}", app_error);
+        }
     });
 
+    // 启动自动备份
+    BackupService::start_scheduler();
+
+    // 启动插件更新检查
+    PluginUpdateService::start_scheduler();
+
+    // 启动配置方案自动切换
+    ProfileService::start_auto_switch();
+
     // 初始化应用观察者
     observe_app().map_err(|e| AppError::Error(format!("初始化应用观察者失败: {}", e)))?;
 
@@ -1,9 +1,7 @@
-use crate::services::config::utils_get_config;
-use crate::services::config::utils_set_config;
+use crate::services::config::ConfigStore;
 use crate::utils::error::{AppError, AppResult};
 use parking_lot::{Mutex, RwLock};
 use serde_json::json;
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{LogicalPosition, WindowEvent};
@@ -81,7 +79,7 @@ fn load_window_position(
     screen_height: f64,
     scale_factor: f64,
 ) -> AppResult<LogicalPosition<f64>> {
-    match utils_get_config("config", vec!["position".to_string()]) {
+    match ConfigStore::get(&["position".to_string()]) {
         Ok(Some(value)) => parse_position_config(value, screen_width, screen_height, scale_factor),
         Ok(None) => Ok(LogicalPosition::new(500.0, 300.0)),
         Err(e) => Err(AppError::Error(format!("读取配置失败: {}", e))),
@@ -141,13 +139,9 @@ fn handle_window_move(
 /// 保存窗口位置
 fn save_window_position(window: &Arc<RwLock<tauri::WebviewWindow>>) {
     if let Ok(position) = window.read().outer_position() {
-        let mut data = HashMap::new();
-        data.insert(
-            "position".to_string(),
-            json!({"x": position.x, "y": position.y}),
-        );
+        let value = json!({"x": position.x, "y": position.y});
 
-        if let Err(e) = utils_set_config("config", data) {
+        if let Err(e) = ConfigStore::set(vec!["position".to_string()], value, "main") {
             let app_error = AppError::from(format!("保存位置时出错: {}", e));
             eprintln!("{}", app_error);
         }
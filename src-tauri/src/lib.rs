@@ -26,6 +26,9 @@ pub fn run() {
             // 设置全局 AppHandle
             crate::core::app_handle::AppHandleManager::set(app.handle().clone());
 
+            // 初始化应用程序，窗口位置保存在数据库中，需要先于窗口设置完成
+            init_app()?;
+
             // 创建窗口管理器
             let window_manager = WindowManager::new(window);
             let window = window_manager.get_window();
@@ -37,9 +40,6 @@ pub fn run() {
             // 设置系统托盘
             setup_tray(app, window)?;
 
-            // 初始化应用程序
-            init_app()?;
-
             Ok(())
         })
         .invoke_handler(generate_app_handlers!())
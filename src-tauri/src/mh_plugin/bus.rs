@@ -0,0 +1,569 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::lifecycle::PluginLifecycleService;
+use crate::mh_plugin::manifest::{
+    is_reserved_window_id, is_valid_bus_name, is_valid_window_id, PluginManifest,
+    BUILTIN_WINDOW_LABELS, HOST_NAMESPACE,
+};
+use crate::mh_plugin::state::PluginStateService;
+use crate::services::permission::PermissionPolicy;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::oneshot;
+
+/// 总线消息事件，发送到订阅了该主题的窗口
+pub const BUS_MESSAGE_EVENT: &str = "bus:message";
+/// 方法调用事件，发送到提供该方法的插件窗口
+pub const BUS_REQUEST_EVENT: &str = "bus:request";
+
+/// 方法调用的默认超时时间
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// 方法调用的最长超时时间
+const MAX_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 总线消息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMessage {
+    pub topic: String,
+    /// 发布消息的窗口
+    pub sender: String,
+    pub payload: Value,
+}
+
+/// 方法调用请求，插件处理后调用 `mh_bus_respond` 返回结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusRequest {
+    pub id: u64,
+    /// 调用方窗口
+    pub caller: String,
+    pub method: String,
+    pub payload: Value,
+}
+
+/// 等待响应的方法调用
+struct PendingRequest {
+    /// 调用方窗口
+    caller: String,
+    /// 提供方法的插件，只有该插件窗口可以响应
+    target: String,
+    sender: oneshot::Sender<Result<Value, String>>,
+}
+
+/// 释放时移除等待响应的方法调用，调用方不再等待时也会移除
+struct PendingGuard(u64);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        PENDING.lock().remove(&self.0);
+    }
+}
+
+/// 窗口标签 -> 订阅的主题，`<命名空间>/*` 表示订阅整个命名空间
+static SUBSCRIPTIONS: Lazy<RwLock<HashMap<String, HashSet<String>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 请求ID -> 等待响应的方法调用
+static PENDING: Lazy<Mutex<HashMap<u64, PendingRequest>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 插件消息总线
+///
+/// 主题格式为 `<命名空间>/<主题名>`，插件的命名空间为其窗口ID，内置窗口的命名空间为 `host`。
+/// 窗口总是可以发布和订阅自身命名空间下的主题，发布或订阅其他插件的主题需要该插件在清单
+/// `bus.topics` 中允许；方法调用只能调用插件在清单 `bus.methods` 中声明的方法
+pub struct PluginBus;
+
+impl PluginBus {
+    /// 窗口是否可以订阅该主题
+    ///
+    /// 内置窗口可以订阅所有主题；插件只能订阅自身命名空间，或其他插件在 `bus.topics` 中
+    /// 允许订阅的单个主题，不能订阅其他命名空间的 `<命名空间>/*` 和 `host` 命名空间
+    pub fn can_subscribe(window_label: &str, topic: &str) -> AppResult<bool> {
+        let (namespace, name) = match topic.strip_suffix("/*") {
+            Some(namespace) if is_valid_namespace(namespace) => (namespace, None),
+            Some(_) => return Err(AppError::Error(format!("无效的命名空间: {}", topic))),
+            None => {
+                let (namespace, name) = parse_topic(topic)?;
+                (namespace, Some(name))
+            }
+        };
+        if is_builtin_window(window_label) || namespace_of(window_label) == Some(namespace) {
+            return Ok(true);
+        }
+        let Some(name) = name else {
+            return Ok(false);
+        };
+        if namespace == HOST_NAMESPACE {
+            return Ok(false);
+        }
+        Ok(active_manifest(namespace).is_some_and(|manifest| {
+            manifest.bus.topics.iter().any(|topic| {
+                topic.name == name
+                    && topic
+                        .subscribers
+                        .iter()
+                        .any(|subscriber| subscriber == "*" || subscriber == window_label)
+            })
+        }))
+    }
+
+    /// 订阅主题，重复订阅会合并
+    ///
+    /// 不检查订阅权限，窗口订阅的主题需要先通过 [`PluginBus::can_subscribe`]
+    pub fn subscribe(window_label: &str, topics: Vec<String>) {
+        SUBSCRIPTIONS
+            .write()
+            .entry(window_label.to_string())
+            .or_default()
+            .extend(topics);
+    }
+
+    /// 取消订阅，`topics` 为 None 时取消该窗口的全部订阅
+    pub fn unsubscribe(window_label: &str, topics: Option<Vec<String>>) {
+        let mut subscriptions = SUBSCRIPTIONS.write();
+        match topics {
+            Some(topics) => {
+                if let Some(subscribed) = subscriptions.get_mut(window_label) {
+                    for topic in &topics {
+                        subscribed.remove(topic);
+                    }
+                    if subscribed.is_empty() {
+                        subscriptions.remove(window_label);
+                    }
+                }
+            }
+            None => {
+                subscriptions.remove(window_label);
+            }
+        }
+    }
+
+    /// 窗口是否可以发布该主题
+    pub fn can_publish(window_label: &str, topic: &str) -> AppResult<bool> {
+        let (namespace, name) = parse_topic(topic)?;
+        if namespace_of(window_label) == Some(namespace) {
+            return Ok(true);
+        }
+        if is_builtin_window(window_label) || namespace == HOST_NAMESPACE {
+            return Ok(false);
+        }
+        Ok(active_manifest(namespace).is_some_and(|manifest| {
+            manifest.bus.topics.iter().any(|topic| {
+                topic.name == name
+                    && topic
+                        .publishers
+                        .iter()
+                        .any(|publisher| publisher == "*" || publisher == window_label)
+            })
+        }))
+    }
+
+    /// 向订阅了该主题的窗口发送消息，发送者自身不会收到，返回收到消息的窗口数
+    ///
+    /// 不检查发布权限，窗口发布的消息需要先通过 [`PluginBus::can_publish`]
+    pub fn publish(sender: &str, topic: &str, payload: Value) -> usize {
+        let Some(app_handle) = AppHandleManager::get() else {
+            return 0;
+        };
+        let Some((namespace, _)) = topic.split_once('/') else {
+            return 0;
+        };
+        let wildcard = format!("{}/*", namespace);
+        let message = BusMessage {
+            topic: topic.to_string(),
+            sender: sender.to_string(),
+            payload,
+        };
+
+        let mut delivered = 0;
+        let mut closed_windows = Vec::new();
+        {
+            let subscriptions = SUBSCRIPTIONS.read();
+            for (label, topics) in subscriptions.iter() {
+                if label == sender || !(topics.contains(topic) || topics.contains(&wildcard)) {
+                    continue;
+                }
+                if app_handle.get_webview_window(label).is_none() {
+                    closed_windows.push(label.clone());
+                    continue;
+                }
+                if app_handle
+                    .emit_to(label.as_str(), BUS_MESSAGE_EVENT, &message)
+                    .is_ok()
+                {
+                    delivered += 1;
+                }
+            }
+        }
+
+        // 清理已关闭窗口的订阅
+        if !closed_windows.is_empty() {
+            let mut subscriptions = SUBSCRIPTIONS.write();
+            for label in closed_windows {
+                subscriptions.remove(&label);
+            }
+        }
+        delivered
+    }
+
+    /// 窗口是否可以调用插件的方法
+    ///
+    /// 插件未安装、已禁用或没有声明该方法时返回错误；内置窗口可以调用所有声明的方法，
+    /// 其他插件需要在 `callers` 中列出或为 `*`
+    pub fn can_call(caller: &str, target: &str, method: &str) -> AppResult<bool> {
+        let manifest = active_manifest(target)
+            .ok_or_else(|| AppError::Error(format!("插件 {} 未安装或已禁用", target)))?;
+        let declared = manifest
+            .bus
+            .methods
+            .iter()
+            .find(|declared| declared.name == method)
+            .ok_or_else(|| AppError::Error(format!("插件 {} 没有提供方法 {}", target, method)))?;
+
+        Ok(is_builtin_window(caller)
+            || declared
+                .callers
+                .iter()
+                .any(|allowed| allowed == "*" || allowed == caller))
+    }
+
+    /// 调用插件的方法并等待响应
+    ///
+    /// 插件窗口需要已打开，超时或插件返回错误时返回错误。
+    /// 不检查调用权限，窗口发起的调用需要先通过 [`PluginBus::can_call`]
+    pub async fn request(
+        caller: &str,
+        target: &str,
+        method: &str,
+        payload: Value,
+        timeout: Option<Duration>,
+    ) -> AppResult<Value> {
+        let app_handle =
+            AppHandleManager::get().ok_or_else(|| AppError::Error("应用尚未初始化".to_string()))?;
+        if app_handle.get_webview_window(target).is_none() {
+            return Err(AppError::Error(format!("插件 {} 未运行", target)));
+        }
+
+        let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        PENDING.lock().insert(
+            id,
+            PendingRequest {
+                caller: caller.to_string(),
+                target: target.to_string(),
+                sender,
+            },
+        );
+        let _guard = PendingGuard(id);
+
+        let request = BusRequest {
+            id,
+            caller: caller.to_string(),
+            method: method.to_string(),
+            payload,
+        };
+        if let Err(e) = app_handle.emit_to(target, BUS_REQUEST_EVENT, &request) {
+            return Err(AppError::Error(format!("发送方法调用失败: {}", e)));
+        }
+
+        let timeout = timeout
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT)
+            .min(MAX_REQUEST_TIMEOUT);
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(result)) => result.map_err(AppError::Error),
+            Ok(Err(_)) => Err(AppError::Error(format!("插件 {} 没有响应", target))),
+            Err(_) => Err(AppError::Error(format!(
+                "调用插件 {} 的方法 {} 超时",
+                target, method
+            ))),
+        }
+    }
+
+    /// 移除插件的订阅和相关的方法调用，插件禁用或卸载时调用
+    ///
+    /// 调用该插件方法的一方立即收到没有响应的错误
+    pub fn remove_plugin(window_id: &str) {
+        SUBSCRIPTIONS.write().remove(window_id);
+        PENDING
+            .lock()
+            .retain(|_, request| request.caller != window_id && request.target != window_id);
+    }
+
+    /// 返回方法调用的结果，只有被调用的插件窗口可以响应
+    pub fn respond(window_label: &str, id: u64, result: Result<Value, String>) -> AppResult<()> {
+        let mut pending = PENDING.lock();
+        match pending.get(&id) {
+            Some(request) if request.target == window_label => {}
+            Some(_) => {
+                return Err(AppError::Error(format!(
+                    "窗口 {} 不能响应请求 {}",
+                    window_label, id
+                )));
+            }
+            None => return Err(AppError::Error(format!("请求 {} 不存在或已超时", id))),
+        }
+        if let Some(request) = pending.remove(&id) {
+            let _ = request.sender.send(result);
+        }
+        Ok(())
+    }
+}
+
+/// 是否为内置窗口，只按内置窗口标签判断，标签为 `host` 的窗口不是内置窗口
+fn is_builtin_window(window_label: &str) -> bool {
+    BUILTIN_WINDOW_LABELS.contains(&window_label)
+}
+
+/// 窗口所属的命名空间，内置窗口属于宿主命名空间
+///
+/// 使用保留标识或无效标识的其他窗口没有命名空间
+fn namespace_of(window_label: &str) -> Option<&str> {
+    if is_builtin_window(window_label) {
+        Some(HOST_NAMESPACE)
+    } else if is_valid_window_id(window_label) && !is_reserved_window_id(window_label) {
+        Some(window_label)
+    } else {
+        None
+    }
+}
+
+fn is_valid_namespace(namespace: &str) -> bool {
+    namespace == HOST_NAMESPACE || is_valid_window_id(namespace)
+}
+
+/// 解析 `<命名空间>/<主题名>` 格式的主题
+fn parse_topic(topic: &str) -> AppResult<(&str, &str)> {
+    topic
+        .split_once('/')
+        .filter(|(namespace, name)| is_valid_namespace(namespace) && is_valid_bus_name(name))
+        .ok_or_else(|| AppError::Error(format!("无效的主题 {}，格式为 <命名空间>/<主题名>", topic)))
+}
+
+/// 已安装且已启用的插件清单
+fn active_manifest(window_id: &str) -> Option<PluginManifest> {
+    if PluginStateService::is_disabled(window_id) {
+        return None;
+    }
+    PluginLifecycleService::installed_manifest(window_id)
+}
+
+/// 订阅消息总线上的主题
+///
+/// # Arguments
+///
+/// * `topics` - 主题列表，`<命名空间>/*` 表示订阅整个命名空间
+///
+/// 消息通过 `bus:message` 事件推送到当前窗口
+#[permission_macro::permission(cap = "bus.message")]
+#[tauri::command]
+pub fn mh_bus_subscribe(topics: Vec<String>) -> Result<ApiResponse<()>, AppError> {
+    for topic in &topics {
+        match PluginBus::can_subscribe(window.label(), topic) {
+            Ok(true) => {}
+            Ok(false) => {
+                let denied =
+                    PermissionPolicy::deny(window.label(), "mh_bus_subscribe", Some(topic));
+                return Ok(ApiResponse::permission_denied(denied.to_string()));
+            }
+            Err(e) => {
+                return Ok(ApiResponse::error(
+                    ApiStatusCode::ErrParamsInvalid,
+                    e.to_string(),
+                ));
+            }
+        }
+    }
+    PluginBus::subscribe(window.label(), topics);
+    Ok(ApiResponse::success(()))
+}
+
+/// 取消订阅主题，`topics` 为空时取消当前窗口的全部订阅
+#[permission_macro::permission(cap = "bus.message")]
+#[tauri::command]
+pub fn mh_bus_unsubscribe(topics: Option<Vec<String>>) -> Result<ApiResponse<()>, AppError> {
+    PluginBus::unsubscribe(window.label(), topics);
+    Ok(ApiResponse::success(()))
+}
+
+/// 发布消息，返回收到消息的窗口数
+#[permission_macro::permission(cap = "bus.message")]
+#[tauri::command]
+pub fn mh_bus_publish(topic: String, payload: Value) -> Result<ApiResponse<usize>, AppError> {
+    match PluginBus::can_publish(window.label(), &topic) {
+        Ok(true) => Ok(ApiResponse::success(PluginBus::publish(
+            window.label(),
+            &topic,
+            payload,
+        ))),
+        Ok(false) => {
+            let denied = PermissionPolicy::deny(window.label(), "mh_bus_publish", Some(&topic));
+            Ok(ApiResponse::permission_denied(denied.to_string()))
+        }
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 调用插件在清单中声明的方法并返回结果
+///
+/// # Arguments
+///
+/// * `target` - 提供方法的插件窗口ID
+/// * `method` - 方法名
+/// * `payload` - 调用参数
+/// * `timeout_ms` - 超时时间，默认 10 秒，最长 60 秒
+#[permission_macro::permission(cap = "bus.message")]
+#[tauri::command]
+pub async fn mh_bus_request(
+    target: String,
+    method: String,
+    payload: Value,
+    timeout_ms: Option<u64>,
+) -> Result<ApiResponse<Value>, AppError> {
+    match PluginBus::can_call(window.label(), &target, &method) {
+        Ok(true) => {}
+        Ok(false) => {
+            let denied = PermissionPolicy::deny(
+                window.label(),
+                "mh_bus_request",
+                Some(&format!("{}/{}", target, method)),
+            );
+            return Ok(ApiResponse::permission_denied(denied.to_string()));
+        }
+        Err(e) => {
+            return Ok(ApiResponse::error(
+                ApiStatusCode::ErrParamsInvalid,
+                e.to_string(),
+            ));
+        }
+    }
+
+    let timeout = timeout_ms.map(Duration::from_millis);
+    match PluginBus::request(window.label(), &target, &method, payload, timeout).await {
+        Ok(value) => Ok(ApiResponse::success(value)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrSystem, e.to_string())),
+    }
+}
+
+/// 返回 `bus:request` 方法调用的结果，`error` 不为空时调用方收到错误
+#[permission_macro::permission(cap = "bus.message")]
+#[tauri::command]
+pub fn mh_bus_respond(
+    id: u64,
+    result: Option<Value>,
+    error: Option<String>,
+) -> Result<ApiResponse<()>, AppError> {
+    let result = match error {
+        Some(error) => Err(error),
+        None => Ok(result.unwrap_or(Value::Null)),
+    };
+    match PluginBus::respond(window.label(), id, result) {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            e.to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_namespace_and_name() {
+        assert_eq!(parse_topic("demo/done").unwrap(), ("demo", "done"));
+        assert_eq!(
+            parse_topic("host/theme.changed").unwrap(),
+            ("host", "theme.changed")
+        );
+        for topic in ["demo", "/done", "demo/", "a b/done", "demo/a/b", "demo/*"] {
+            assert!(parse_topic(topic).is_err(), "{}", topic);
+        }
+    }
+
+    #[test]
+    fn builtin_windows_share_the_host_namespace() {
+        assert_eq!(namespace_of("main"), Some(HOST_NAMESPACE));
+        assert_eq!(namespace_of("setting"), Some(HOST_NAMESPACE));
+        assert_eq!(namespace_of("demo"), Some("demo"));
+    }
+
+    #[test]
+    fn window_labelled_host_gets_no_host_privileges() {
+        assert_eq!(namespace_of(HOST_NAMESPACE), None);
+        assert!(!PluginBus::can_publish("host", "host/theme").unwrap());
+        assert!(!PluginBus::can_subscribe("host", "host/*").unwrap());
+        assert!(!PluginBus::can_subscribe("host", "host/theme").unwrap());
+        assert!(!PluginBus::can_subscribe("host", "demo/*").unwrap());
+    }
+
+    #[test]
+    fn publishes_own_namespace_only_without_declaration() {
+        assert!(PluginBus::can_publish("demo", "demo/done").unwrap());
+        assert!(PluginBus::can_publish("main", "host/theme").unwrap());
+        assert!(!PluginBus::can_publish("demo", "host/theme").unwrap());
+        assert!(!PluginBus::can_publish("main", "demo/done").unwrap());
+        assert!(PluginBus::can_publish("demo", "demo").is_err());
+    }
+
+    #[test]
+    fn subscribes_without_declaration_only_where_allowed() {
+        assert!(PluginBus::can_subscribe("demo", "demo/*").unwrap());
+        assert!(PluginBus::can_subscribe("demo", "demo/done").unwrap());
+        assert!(PluginBus::can_subscribe("main", "demo/*").unwrap());
+        assert!(PluginBus::can_subscribe("main", "host/*").unwrap());
+        assert!(!PluginBus::can_subscribe("demo", "host/*").unwrap());
+        assert!(!PluginBus::can_subscribe("demo", "host/theme").unwrap());
+        assert!(!PluginBus::can_subscribe("demo", "other/*").unwrap());
+        assert!(PluginBus::can_subscribe("demo", "a b/*").is_err());
+        assert!(PluginBus::can_subscribe("demo", "other").is_err());
+    }
+
+    fn pending(id: u64, caller: &str, target: &str) {
+        let (sender, _) = oneshot::channel();
+        PENDING.lock().insert(
+            id,
+            PendingRequest {
+                caller: caller.to_string(),
+                target: target.to_string(),
+                sender,
+            },
+        );
+    }
+
+    #[test]
+    fn pending_request_is_removed_when_caller_stops_waiting() {
+        pending(u64::MAX, "main", "demo");
+        drop(PendingGuard(u64::MAX));
+        assert!(!PENDING.lock().contains_key(&u64::MAX));
+    }
+
+    #[test]
+    fn removing_plugin_clears_subscriptions_and_requests() {
+        PluginBus::subscribe("removed", vec!["removed/done".to_string()]);
+        pending(u64::MAX - 1, "main", "removed");
+        pending(u64::MAX - 2, "removed", "demo");
+        pending(u64::MAX - 3, "main", "demo");
+
+        PluginBus::remove_plugin("removed");
+        assert!(!SUBSCRIPTIONS.read().contains_key("removed"));
+        let pending = PENDING.lock();
+        assert!(!pending.contains_key(&(u64::MAX - 1)));
+        assert!(!pending.contains_key(&(u64::MAX - 2)));
+        assert!(pending.contains_key(&(u64::MAX - 3)));
+    }
+}
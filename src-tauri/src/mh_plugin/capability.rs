@@ -0,0 +1,124 @@
+use crate::mh_plugin::manifest::{BUILTIN_WINDOW_LABELS, PLUGIN_CAPABILITIES};
+use crate::services::database::{execute_write, get_db_pool};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 能力说明
+pub fn describe_capability(name: &str) -> Option<&'static str> {
+    PLUGIN_CAPABILITIES
+        .iter()
+        .find(|(capability, _)| *capability == name)
+        .map(|(_, description)| *description)
+}
+
+/// 插件申请的能力及授权状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityGrant {
+    pub name: String,
+    pub description: String,
+    pub granted: bool,
+}
+
+/// 插件窗口是否拥有指定能力
+///
+/// 能力必须同时在当前版本的清单中申请并由用户授予，回滚到未申请该能力的版本后自动失效
+pub fn has_capability(window_label: &str, capability: &str) -> bool {
+    if BUILTIN_WINDOW_LABELS.contains(&window_label) {
+        return false;
+    }
+    let Ok(conn) = get_db_pool().get() else {
+        return false;
+    };
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM plugin_config p, \
+         json_each(p.info, '$.grants') g, json_each(p.data, '$.permissions') d \
+         WHERE p.window_id = ?1 AND json_valid(p.info) AND json_valid(p.data) \
+         AND g.value = ?2 AND d.value = ?2)",
+        params![window_label, capability],
+        |row| row.get::<_, bool>(0),
+    )
+    .unwrap_or(false)
+}
+
+/// 获取插件申请的能力及授权状态
+pub fn plugin_capabilities(window_id: &str) -> AppResult<Vec<CapabilityGrant>> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+    let (info, data): (String, String) = conn
+        .query_row(
+            "SELECT info, data FROM plugin_config WHERE window_id = ?1",
+            [window_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| AppError::Error(format!("读取插件配置失败: {}", e)))?
+        .ok_or_else(|| AppError::Error(format!("插件 {} 不存在", window_id)))?;
+
+    let declared = string_list(&data, "permissions");
+    let granted = string_list(&info, "grants");
+    Ok(declared
+        .into_iter()
+        .map(|name| CapabilityGrant {
+            description: describe_capability(&name).unwrap_or_default().to_string(),
+            granted: granted.contains(&name),
+            name,
+        })
+        .collect())
+}
+
+/// 设置插件的能力授权，只能授予插件清单中申请的能力
+pub fn set_plugin_grants(window_id: &str, grants: Vec<String>) -> AppResult<()> {
+    let declared: Vec<String> = plugin_capabilities(window_id)?
+        .into_iter()
+        .map(|capability| capability.name)
+        .collect();
+    if let Some(undeclared) = grants.iter().find(|grant| !declared.contains(grant)) {
+        return Err(AppError::Error(format!("插件没有申请能力: {}", undeclared)));
+    }
+
+    let window_id = window_id.to_string();
+    let grants = serde_json::to_string(&grants)
+        .map_err(|e| AppError::Error(format!("序列化授权失败: {}", e)))?;
+    execute_write(move |conn| {
+        conn.execute(
+            "UPDATE plugin_config SET info = json_set(CASE WHEN json_valid(info) THEN info ELSE '{}' END, \
+             '$.grants', json(?2)) WHERE window_id = ?1",
+            params![window_id, grants],
+        )
+        .map_err(|e| AppError::Error(format!("保存授权失败: {}", e)))?;
+        Ok(())
+    })
+}
+
+fn string_list(json: &str, field: &str) -> Vec<String> {
+    serde_json::from_str::<Value>(json)
+        .ok()
+        .and_then(|value| value.get(field).cloned())
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// 获取插件申请的能力及授权状态
+#[permission_macro::permission(cap = "plugin.grant")]
+#[tauri::command]
+pub fn mh_plugin_capabilities(window_id: String) -> Result<ApiResponse<Vec<CapabilityGrant>>, AppError> {
+    match plugin_capabilities(&window_id) {
+        Ok(capabilities) => Ok(ApiResponse::success(capabilities)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrConfig, e.to_string())),
+    }
+}
+
+/// 设置插件的能力授权，`grants` 为授予的全部能力，未列出的能力会被撤销
+#[permission_macro::permission(cap = "plugin.grant")]
+#[tauri::command]
+pub fn mh_plugin_set_grants(window_id: String, grants: Vec<String>) -> Result<ApiResponse<()>, AppError> {
+    match set_plugin_grants(&window_id, grants) {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrParamsInvalid, e.to_string())),
+    }
+}
@@ -0,0 +1,324 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::install::install_market_package;
+use crate::mh_plugin::manifest::{host_version, PluginManifest};
+use crate::mh_plugin::update::{PluginIndex, PluginUpdateService};
+use crate::services::database::get_db_pool;
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::Emitter;
+
+/// 同步插件后发现无法满足的依赖时通知所有窗口
+pub const PLUGIN_DEPENDENCIES_EVENT: &str = "plugin:dependencies-unresolved";
+
+/// 无法满足的依赖
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedDependency {
+    /// 声明依赖的插件
+    pub window_id: String,
+    /// 被依赖的插件或共享运行库
+    pub dependency: String,
+    /// 要求的版本范围
+    pub required: String,
+    /// 已安装的版本，未安装时为 None
+    pub installed: Option<String>,
+}
+
+/// 插件配置中记录的插件清单字段
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct InstalledPlugin {
+    version: Option<Version>,
+    dependencies: BTreeMap<String, VersionReq>,
+}
+
+/// 插件依赖服务
+///
+/// 已安装插件的版本和依赖取自 plugin_config.data 中记录的插件清单
+pub struct PluginDependencyService;
+
+impl PluginDependencyService {
+    /// 检查所有已安装插件的依赖，返回未安装或版本不满足的依赖
+    pub fn unresolved() -> AppResult<Vec<UnresolvedDependency>> {
+        Ok(unresolved_in(&installed_plugins()?))
+    }
+
+    /// 记录无法满足的依赖并通知所有窗口，同步插件后调用
+    pub fn report_unresolved() -> AppResult<Vec<UnresolvedDependency>> {
+        let unresolved = Self::unresolved()?;
+        if unresolved.is_empty() {
+            return Ok(unresolved);
+        }
+        for item in &unresolved {
+            Logger::write_log(LogEntry {
+                level: "warn".to_string(),
+                message: format!(
+                    "插件 {} 的依赖 {} 无法满足",
+                    item.window_id, item.dependency
+                ),
+                timestamp: String::new(),
+                details: Some(format!(
+                    "要求 {}，已安装 {}",
+                    item.required,
+                    item.installed.as_deref().unwrap_or("无")
+                )),
+            })?;
+        }
+        if let Some(app_handle) = AppHandleManager::get() {
+            let _ = app_handle.emit(PLUGIN_DEPENDENCIES_EVENT, &unresolved);
+        }
+        Ok(unresolved)
+    }
+
+    /// 依赖指定插件的已安装插件，按 windowId 排序
+    pub fn dependents(window_id: &str) -> AppResult<Vec<String>> {
+        let mut dependents: Vec<String> = installed_plugins()?
+            .into_iter()
+            .filter(|(_, plugin)| plugin.dependencies.contains_key(window_id))
+            .map(|(dependent, _)| dependent)
+            .collect();
+        dependents.sort();
+        Ok(dependents)
+    }
+
+    /// 插件是否为共享运行库
+    pub fn is_library(window_id: &str) -> bool {
+        let Ok(conn) = get_db_pool().get() else {
+            return false;
+        };
+        conn.query_row(
+            "SELECT json_extract(data, '$.kind') = 'library' FROM plugin_config \
+             WHERE window_id = ?1 AND json_valid(data)",
+            [window_id],
+            |row| row.get::<_, Option<bool>>(0),
+        )
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+    }
+
+    /// 安装新版本前检查其他插件对该插件的版本要求，新版本不满足时拒绝安装
+    pub fn check_dependents(manifest: &PluginManifest) -> AppResult<()> {
+        let mut conflicts: Vec<String> = installed_plugins()?
+            .into_iter()
+            .filter_map(|(dependent, plugin)| {
+                let required = plugin.dependencies.get(&manifest.window_id)?;
+                (!required.matches(&manifest.version))
+                    .then(|| format!("{} 要求 {}", dependent, required))
+            })
+            .collect();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        conflicts.sort();
+        Err(AppError::Error(format!(
+            "插件 {} {} 不满足其他插件的版本要求: {}",
+            manifest.window_id,
+            manifest.version,
+            conflicts.join(", ")
+        )))
+    }
+
+    /// 从插件市场安装插件缺少的依赖，已安装但版本不满足的依赖会升级到市场中的版本
+    ///
+    /// `resolving` 为正在安装的插件及其版本，包括当前插件，用于处理循环依赖。
+    /// 插件市场只提供每个插件的最新版本，最新版本不满足版本范围时安装失败。
+    /// `allow_unsigned` 沿用安装当前插件时用户的确认
+    pub async fn install_dependencies(
+        manifest: &PluginManifest,
+        resolving: &[(String, Version)],
+        allow_unsigned: bool,
+    ) -> AppResult<()> {
+        let mut index: Option<PluginIndex> = None;
+        for (dependency, required) in &manifest.dependencies {
+            if let Some((_, version)) = resolving.iter().find(|(id, _)| id == dependency) {
+                if required.matches(version) {
+                    continue;
+                }
+                return Err(AppError::Error(format!(
+                    "插件 {} 依赖 {} {}，与正在安装的版本 {} 冲突",
+                    manifest.window_id, dependency, required, version
+                )));
+            }
+            let installed = installed_plugins()?
+                .remove(dependency)
+                .and_then(|plugin| plugin.version);
+            if installed.is_some_and(|version| required.matches(&version)) {
+                continue;
+            }
+
+            if index.is_none() {
+                index = Some(PluginUpdateService::market_index().await?);
+            }
+            let entry = index
+                .as_ref()
+                .and_then(|index| {
+                    index
+                        .plugins
+                        .iter()
+                        .find(|entry| entry.window_id == *dependency)
+                })
+                .ok_or_else(|| {
+                    AppError::Error(format!(
+                        "插件 {} 依赖的 {} 不在插件市场中",
+                        manifest.window_id, dependency
+                    ))
+                })?
+                .clone();
+            if !required.matches(&entry.version) {
+                return Err(AppError::Error(format!(
+                    "插件 {} 依赖 {} {}，插件市场中的版本 {} 不满足",
+                    manifest.window_id, dependency, required, entry.version
+                )));
+            }
+            if let Some(min_host_version) = &entry.min_host_version {
+                if host_version() < *min_host_version {
+                    return Err(AppError::Error(format!(
+                        "插件 {} 依赖的 {} 要求宿主版本不低于 {}",
+                        manifest.window_id, dependency, min_host_version
+                    )));
+                }
+            }
+
+            Box::pin(install_market_package(
+                &entry.url,
+                &entry.window_id,
+                Some(&entry.sha256),
+                entry.signature.as_deref(),
+                None,
+                resolving.to_vec(),
+                allow_unsigned,
+            ))
+            .await
+            .map_err(|e| AppError::Error(format!("安装依赖 {} 失败: {}", dependency, e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// 已安装插件中未安装或版本不满足的依赖，按声明依赖的插件和被依赖的插件排序
+fn unresolved_in(installed: &HashMap<String, InstalledPlugin>) -> Vec<UnresolvedDependency> {
+    let mut unresolved = Vec::new();
+    for (window_id, plugin) in installed {
+        for (dependency, required) in &plugin.dependencies {
+            let version = installed
+                .get(dependency)
+                .and_then(|dependency| dependency.version.as_ref());
+            if version.is_some_and(|version| required.matches(version)) {
+                continue;
+            }
+            unresolved.push(UnresolvedDependency {
+                window_id: window_id.clone(),
+                dependency: dependency.clone(),
+                required: required.to_string(),
+                installed: version.map(Version::to_string),
+            });
+        }
+    }
+    unresolved.sort_by(|a, b| (&a.window_id, &a.dependency).cmp(&(&b.window_id, &b.dependency)));
+    unresolved
+}
+
+/// 读取已安装插件的版本和依赖，清单无法识别的插件视为没有依赖
+fn installed_plugins() -> AppResult<HashMap<String, InstalledPlugin>> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+    let mut stmt = conn
+        .prepare_cached("SELECT window_id, data FROM plugin_config")
+        .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+
+    let mut plugins = HashMap::new();
+    for row in rows {
+        let (window_id, data) =
+            row.map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?;
+        plugins.insert(window_id, serde_json::from_str(&data).unwrap_or_default());
+    }
+    Ok(plugins)
+}
+
+/// 获取已安装插件中无法满足的依赖
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub fn mh_plugin_unresolved_dependencies(
+) -> Result<ApiResponse<Vec<UnresolvedDependency>>, AppError> {
+    match PluginDependencyService::unresolved() {
+        Ok(unresolved) => Ok(ApiResponse::success(unresolved)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(version: &str, dependencies: &[(&str, &str)]) -> InstalledPlugin {
+        InstalledPlugin {
+            version: Version::parse(version).ok(),
+            dependencies: dependencies
+                .iter()
+                .map(|(id, required)| (id.to_string(), VersionReq::parse(required).unwrap()))
+                .collect(),
+        }
+    }
+
+    fn installed(plugins: Vec<(&str, InstalledPlugin)>) -> HashMap<String, InstalledPlugin> {
+        plugins
+            .into_iter()
+            .map(|(id, plugin)| (id.to_string(), plugin))
+            .collect()
+    }
+
+    #[test]
+    fn satisfied_dependencies_are_not_reported() {
+        let plugins = installed(vec![
+            ("app", plugin("1.0.0", &[("vue-rt", "^3.4")])),
+            ("vue-rt", plugin("3.5.1", &[])),
+        ]);
+        assert!(unresolved_in(&plugins).is_empty());
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_dependencies_sorted() {
+        let plugins = installed(vec![
+            ("b", plugin("1.0.0", &[("vue-rt", "^4"), ("a", "*")])),
+            ("a", plugin("1.0.0", &[("missing", "^1")])),
+            ("vue-rt", plugin("3.5.1", &[])),
+        ]);
+        let unresolved = unresolved_in(&plugins);
+        assert_eq!(unresolved.len(), 2);
+
+        assert_eq!(unresolved[0].window_id, "a");
+        assert_eq!(unresolved[0].dependency, "missing");
+        assert_eq!(unresolved[0].installed, None);
+
+        assert_eq!(unresolved[1].window_id, "b");
+        assert_eq!(unresolved[1].dependency, "vue-rt");
+        assert_eq!(unresolved[1].required, "^4");
+        assert_eq!(unresolved[1].installed.as_deref(), Some("3.5.1"));
+    }
+
+    #[test]
+    fn unreadable_records_count_as_not_installed() {
+        let unreadable: InstalledPlugin = serde_json::from_str("not json").unwrap_or_default();
+        let plugins = installed(vec![
+            ("app", plugin("1.0.0", &[("lib", "^1")])),
+            ("lib", unreadable),
+        ]);
+        let unresolved = unresolved_in(&plugins);
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].installed, None);
+    }
+}
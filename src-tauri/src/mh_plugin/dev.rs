@@ -0,0 +1,486 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::lifecycle::{asset_url, PluginLifecycleService};
+use crate::mh_plugin::manifest::{PluginManifest, MANIFEST_FILE};
+use crate::mh_plugin::state::PluginStateService;
+use crate::mh_plugin::storage::PluginStorageService;
+use crate::services::database::{
+    batch_remove_plugin_configs, get_db_pool, query_dev_plugin_ids, query_plugin_enabled,
+    save_dev_plugin,
+};
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use crate::utils::reqwest::create_web_client;
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::ipc::CapabilityBuilder;
+use tauri::{Emitter, Manager};
+use url::Url;
+
+/// 开发模式插件的文件变化后重新加载插件窗口
+pub const PLUGIN_DEV_RELOADED_EVENT: &str = "plugin:dev-reloaded";
+/// 开发模式插件重新加载失败，通常是清单校验不通过
+pub const PLUGIN_DEV_ERROR_EVENT: &str = "plugin:dev-error";
+
+/// 检查插件目录变化的间隔
+const WATCH_INTERVAL: Duration = Duration::from_millis(800);
+
+/// 正在监听的开发模式插件目录
+static WATCHERS: Lazy<Mutex<HashMap<String, JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 已添加远程 IPC 能力的插件窗口和开发服务器地址，格式为 `<windowId> <url>`
+static REMOTE_CAPABILITIES: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 开发服务器页面可以使用的 Tauri 内置权限，应用自身的命令仍由能力授权控制
+const REMOTE_PERMISSIONS: [&str; 1] = ["core:event:default"];
+
+/// 开发模式插件的来源，记录在 plugin_config.info.dev 中
+///
+/// 本地目录和开发服务器地址只能设置一个
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevSource {
+    /// 包含 mhPlugin.json 的插件目录，文件变化后自动重新加载插件窗口
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 开发服务器地址，从 `<url>/mhPlugin.json` 读取清单，热更新由开发服务器负责
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl DevSource {
+    /// 插件目录内页面的访问地址
+    pub fn page_url(&self, page: &str) -> AppResult<Url> {
+        match (&self.path, &self.url) {
+            (Some(path), None) => asset_url(&Path::new(path).join(page)),
+            (None, Some(url)) => dev_server_url(url)?
+                .join(page)
+                .map_err(|e| AppError::Error(format!("无效的页面地址: {}", e))),
+            _ => Err(AppError::Error(
+                "开发模式插件必须指定本地目录或开发服务器地址中的一个".to_string(),
+            )),
+        }
+    }
+}
+
+/// 开发模式插件的事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginDevEvent {
+    pub window_id: String,
+    /// 重新加载失败的原因
+    pub message: Option<String>,
+}
+
+pub struct PluginDevService;
+
+impl PluginDevService {
+    /// 以开发模式加载插件，插件文件不会被复制到插件目录
+    ///
+    /// 再次加载同一插件时更新来源和窗口数据，`grants` 为 None 时保留已有的授权
+    pub async fn register(
+        source: DevSource,
+        grants: Option<Vec<String>>,
+    ) -> AppResult<PluginManifest> {
+        let manifest = Self::load_manifest(&source).await?;
+        let window_id = manifest.window_id.clone();
+
+        let installed = get_myhelper_path()
+            .map(|path| path.join("Plugin").join(&window_id).join(MANIFEST_FILE))
+            .map_err(AppError::from)?
+            .exists();
+        let previous = Self::manifest(&window_id);
+        if installed || (previous.is_none() && query_plugin_enabled(&window_id)?.is_some()) {
+            return Err(AppError::Error(format!(
+                "插件 {} 已安装，请先卸载后再以开发模式加载",
+                window_id
+            )));
+        }
+
+        Self::save(&source, &manifest, grants).await?;
+        Self::unwatch(&window_id);
+        if let Some(path) = &source.path {
+            Self::watch(window_id.clone(), source.clone(), PathBuf::from(path));
+        }
+        PluginLifecycleService::installed(&manifest, previous.as_ref());
+
+        Logger::write_log(LogEntry {
+            level: "info".to_string(),
+            message: format!("以开发模式加载插件: {}", window_id),
+            timestamp: String::new(),
+            details: Some(format!("{:?}", source)),
+        })?;
+        Ok(manifest)
+    }
+
+    /// 移除开发模式插件，本地目录和插件的自身配置保持不变
+    pub async fn unregister(window_id: &str) -> AppResult<()> {
+        let previous = Self::manifest(window_id)
+            .ok_or_else(|| AppError::Error(format!("插件 {} 不是开发模式插件", window_id)))?;
+
+        Self::unwatch(window_id);
+        PluginLifecycleService::uninstalling(window_id).await;
+        if let Some(window) =
+            AppHandleManager::get().and_then(|app_handle| app_handle.get_webview_window(window_id))
+        {
+            let _ = window.close();
+        }
+        batch_remove_plugin_configs(&[window_id.to_string()]).await?;
+        PluginStorageService::clear(window_id).await?;
+        PluginLifecycleService::uninstalled(window_id, Some(&previous));
+
+        Logger::write_log(LogEntry {
+            level: "info".to_string(),
+            message: format!("移除开发模式插件: {}", window_id),
+            timestamp: String::new(),
+            details: None,
+        })?;
+        Ok(())
+    }
+
+    /// 是否为开发模式插件
+    pub fn is_dev(window_id: &str) -> bool {
+        Self::source(window_id).is_some()
+    }
+
+    /// 开发模式插件的来源，不是开发模式插件时返回 None
+    pub fn source(window_id: &str) -> Option<DevSource> {
+        Self::read_column(window_id, "json_extract(info, '$.dev')")
+    }
+
+    /// 开发模式插件最近一次加载的清单，不是开发模式插件时返回 None
+    pub fn manifest(window_id: &str) -> Option<PluginManifest> {
+        Self::read_column(window_id, "data")
+    }
+
+    /// 校验插件窗口要加载的外部地址，并允许该地址的页面调用 IPC
+    ///
+    /// 只有开发模式插件可以加载外部地址，且地址必须位于其注册的开发服务器下
+    pub fn authorize_external_url(window_id: &str, url: &Url) -> AppResult<()> {
+        let base = Self::source(window_id)
+            .and_then(|source| source.url)
+            .ok_or_else(|| {
+                AppError::Error(format!(
+                    "插件 {} 不是开发服务器模式的插件，不能加载外部地址",
+                    window_id
+                ))
+            })
+            .and_then(|url| dev_server_url(&url))?;
+        if !url.as_str().starts_with(base.as_str()) {
+            return Err(AppError::Error(format!(
+                "地址 {} 不在插件 {} 的开发服务器 {} 下",
+                url, window_id, base
+            )));
+        }
+        Self::allow_remote_ipc(window_id, &base)
+    }
+
+    /// 为插件窗口添加限定于开发服务器地址的远程能力，每个窗口和地址只添加一次
+    ///
+    /// 运行期间添加的能力无法撤销，但只对该插件窗口生效，且只有开发模式插件的窗口能加载外部地址
+    fn allow_remote_ipc(window_id: &str, base: &Url) -> AppResult<()> {
+        let key = format!("{} {}", window_id, base);
+        if REMOTE_CAPABILITIES.lock().contains(&key) {
+            return Ok(());
+        }
+        let app_handle = AppHandleManager::get()
+            .ok_or_else(|| AppError::Error("获取AppHandle失败".to_string()))?;
+
+        let mut capability =
+            CapabilityBuilder::new(format!("dev-plugin-{}-{}", window_id, hash(&key)))
+                .remote(format!("{}*", base))
+                .window(window_id);
+        for permission in REMOTE_PERMISSIONS {
+            capability = capability.permission(permission);
+        }
+        app_handle
+            .add_capability(capability)
+            .map_err(|e| AppError::Error(format!("添加开发服务器的远程能力失败: {}", e)))?;
+
+        REMOTE_CAPABILITIES.lock().insert(key);
+        Ok(())
+    }
+
+    /// 应用启动后继续监听开发模式插件的目录
+    pub fn start_watchers() -> AppResult<()> {
+        for window_id in query_dev_plugin_ids()? {
+            if let Some(source) = Self::source(&window_id) {
+                if let Some(path) = source.path.clone() {
+                    Self::watch(window_id, source, PathBuf::from(path));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_column<T: serde::de::DeserializeOwned>(window_id: &str, column: &str) -> Option<T> {
+        let conn = get_db_pool().get().ok()?;
+        let json: Option<String> = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM plugin_config WHERE window_id = ?1 \
+                     AND json_valid(info) AND json_extract(info, '$.dev') IS NOT NULL",
+                    column
+                ),
+                [window_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()??;
+        serde_json::from_str(&json?).ok()
+    }
+
+    /// 读取并校验插件清单，本地目录还要求入口文件和生命周期页面存在
+    async fn load_manifest(source: &DevSource) -> AppResult<PluginManifest> {
+        match (&source.path, &source.url) {
+            (Some(path), None) => {
+                let dir = Path::new(path);
+                if !dir.is_dir() {
+                    return Err(AppError::Error(format!("插件目录不存在: {}", path)));
+                }
+                let bytes = tokio::fs::read(dir.join(MANIFEST_FILE))
+                    .await
+                    .map_err(|e| AppError::Error(format!("读取 {} 失败: {}", MANIFEST_FILE, e)))?;
+                let manifest = PluginManifest::parse(&bytes, None)?;
+                if !dir.join(&manifest.entry).is_file() {
+                    return Err(AppError::Error(format!(
+                        "插件目录中缺少入口文件: {}",
+                        manifest.entry
+                    )));
+                }
+                for (name, page) in manifest.lifecycle.pages() {
+                    if !dir.join(page).is_file() {
+                        return Err(AppError::Error(format!(
+                            "插件目录中缺少生命周期页面 {}: {}",
+                            name, page
+                        )));
+                    }
+                }
+                Ok(manifest)
+            }
+            (None, Some(url)) => {
+                let manifest_url = dev_server_url(url)?
+                    .join(MANIFEST_FILE)
+                    .map_err(|e| AppError::Error(format!("无效的开发服务器地址: {}", e)))?;
+                let response = create_web_client()?
+                    .get(manifest_url)
+                    .send()
+                    .await
+                    .map_err(|e| AppError::Error(format!("连接开发服务器失败: {}", e)))?;
+                if !response.status().is_success() {
+                    return Err(AppError::Error(format!(
+                        "从开发服务器读取 {} 失败: {}",
+                        MANIFEST_FILE,
+                        response.status()
+                    )));
+                }
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::Error(format!("读取 {} 失败: {}", MANIFEST_FILE, e)))?;
+                PluginManifest::parse(&bytes, None).map_err(AppError::from)
+            }
+            _ => Err(AppError::Error(
+                "开发模式插件必须指定本地目录或开发服务器地址中的一个".to_string(),
+            )),
+        }
+    }
+
+    /// 保存来源和按清单生成的窗口数据
+    async fn save(
+        source: &DevSource,
+        manifest: &PluginManifest,
+        grants: Option<Vec<String>>,
+    ) -> AppResult<()> {
+        let mut data = serde_json::to_value(manifest)
+            .map_err(|e| AppError::Error(format!("序列化插件清单失败: {}", e)))?;
+        let url = match &source.path {
+            Some(path) => Path::new(path)
+                .join(&manifest.entry)
+                .to_string_lossy()
+                .replace('\\', "/"),
+            None => source.page_url(&manifest.entry)?.to_string(),
+        };
+        if let Some(data) = data.as_object_mut() {
+            data.insert("url".to_string(), json!(url));
+        }
+
+        let dev = serde_json::to_value(source)
+            .map_err(|e| AppError::Error(format!("序列化插件来源失败: {}", e)))?;
+        let grants = grants.map(|grants| {
+            grants
+                .into_iter()
+                .filter(|grant| manifest.permissions.contains(grant))
+                .collect()
+        });
+        save_dev_plugin(&manifest.window_id, dev, grants, data).await
+    }
+
+    /// 轮询插件目录，文件停止变化后重新加载一次
+    fn watch(window_id: String, source: DevSource, dir: PathBuf) {
+        let key = window_id.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            let mut applied = fingerprint(dir.clone()).await;
+            let mut last = applied;
+            loop {
+                tokio::time::sleep(WATCH_INTERVAL).await;
+                let current = fingerprint(dir.clone()).await;
+                // 文件仍在变化时等待下一轮，避免构建过程中多次重新加载
+                if current != last {
+                    last = current;
+                    continue;
+                }
+                if current != applied {
+                    applied = current;
+                    Self::reload(&window_id, &source).await;
+                }
+            }
+        });
+        if let Some(previous) = WATCHERS.lock().insert(key, handle) {
+            previous.abort();
+        }
+    }
+
+    fn unwatch(window_id: &str) {
+        if let Some(handle) = WATCHERS.lock().remove(window_id) {
+            handle.abort();
+        }
+    }
+
+    /// 重新校验清单并更新窗口数据，然后刷新已打开的插件窗口
+    async fn reload(window_id: &str, source: &DevSource) {
+        let result = match Self::load_manifest(source).await {
+            Ok(manifest) if manifest.window_id != window_id => Err(AppError::Error(format!(
+                "windowId: 开发模式下不能修改，当前为 {}",
+                window_id
+            ))),
+            Ok(manifest) => Self::save(source, &manifest, None).await,
+            Err(e) => Err(e),
+        };
+
+        let app_handle = AppHandleManager::get();
+        match result {
+            Ok(()) => {
+                if let Err(e) = PluginStateService::refresh_hotkeys() {
+                    let _ = Logger::write_log(LogEntry {
+                        level: "warn".to_string(),
+                        message: "刷新插件快捷键失败".to_string(),
+                        timestamp: String::new(),
+                        details: Some(e.to_string()),
+                    });
+                }
+                if let Some(app_handle) = app_handle {
+                    if let Some(window) = app_handle.get_webview_window(window_id) {
+                        let _ = window.eval("window.location.reload()");
+                    }
+                    let _ = app_handle.emit(
+                        PLUGIN_DEV_RELOADED_EVENT,
+                        PluginDevEvent {
+                            window_id: window_id.to_string(),
+                            message: None,
+                        },
+                    );
+                }
+            }
+            Err(e) => {
+                let _ = Logger::write_log(LogEntry {
+                    level: "warn".to_string(),
+                    message: format!("重新加载开发模式插件 {} 失败", window_id),
+                    timestamp: String::new(),
+                    details: Some(e.to_string()),
+                });
+                if let Some(app_handle) = app_handle {
+                    let _ = app_handle.emit(
+                        PLUGIN_DEV_ERROR_EVENT,
+                        PluginDevEvent {
+                            window_id: window_id.to_string(),
+                            message: Some(e.to_string()),
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// 开发服务器地址，只支持 http 和 https，路径补全末尾的 `/` 以便拼接页面
+fn dev_server_url(url: &str) -> AppResult<Url> {
+    let mut url =
+        Url::parse(url).map_err(|e| AppError::Error(format!("无效的开发服务器地址: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::Error(
+            "开发服务器地址只支持 http 或 https".to_string(),
+        ));
+    }
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    Ok(url)
+}
+
+fn hash(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 插件目录中所有文件的路径、大小和修改时间的摘要，忽略隐藏文件、.gitignore 中的文件和 node_modules
+async fn fingerprint(dir: PathBuf) -> Option<u64> {
+    tokio::task::spawn_blocking(move || {
+        if !dir.is_dir() {
+            return None;
+        }
+        let mut files: Vec<(PathBuf, u64, Option<std::time::SystemTime>)> = WalkBuilder::new(&dir)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != "node_modules")
+            .build()
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                metadata
+                    .is_file()
+                    .then(|| (entry.into_path(), metadata.len(), metadata.modified().ok()))
+            })
+            .collect();
+        files.sort();
+
+        let mut hasher = DefaultHasher::new();
+        files.hash(&mut hasher);
+        Some(hasher.finish())
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// 以开发模式加载本地插件目录或开发服务器，返回插件的窗口ID
+///
+/// `path` 和 `url` 只能设置一个；`grants` 为用户授予的能力，为空时保留已有的授权
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub async fn mh_plugin_dev_register(
+    path: Option<String>,
+    url: Option<String>,
+    grants: Option<Vec<String>>,
+) -> AppResult<String> {
+    let manifest = PluginDevService::register(DevSource { path, url }, grants).await?;
+    Ok(manifest.window_id)
+}
+
+/// 移除开发模式插件，本地目录保持不变
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub async fn mh_plugin_dev_unregister(window_id: String) -> AppResult<()> {
+    PluginDevService::unregister(&window_id).await
+}
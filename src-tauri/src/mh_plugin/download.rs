@@ -0,0 +1,306 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::manifest::MAX_ZIP_SIZE;
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use reqwest::header::{ACCEPT, RANGE};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+use tokio::fs::{self as tokio_fs, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch;
+use url::Url;
+
+/// 插件安装进度事件
+pub const INSTALL_PROGRESS_EVENT: &str = "plugin:install-progress";
+
+/// 下载中的插件包所在目录，文件按校验和命名以便下次安装时续传
+const DOWNLOAD_DIR: &str = "PluginDownloads";
+/// 单次请求超时，超时后从已下载的位置续传
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+/// 连接中断后的最大重试次数
+const MAX_RETRIES: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+/// 下载进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 正在安装的插件，值为取消信号
+static INSTALLS: Lazy<Mutex<HashMap<String, watch::Sender<bool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 安装阶段
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallPhase {
+    Downloading,
+    Verifying,
+    Extracting,
+}
+
+/// 插件安装进度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallProgress {
+    pub window_id: String,
+    pub phase: InstallPhase,
+    /// 已下载的字节数
+    pub bytes: u64,
+    /// 插件包总大小，服务器未返回长度时为 None
+    pub total: Option<u64>,
+}
+
+/// 通知所有窗口插件安装进度
+pub fn emit_progress(window_id: &str, phase: InstallPhase, bytes: u64, total: Option<u64>) {
+    if let Some(app_handle) = AppHandleManager::get() {
+        let _ = app_handle.emit(
+            INSTALL_PROGRESS_EVENT,
+            InstallProgress {
+                window_id: window_id.to_string(),
+                phase,
+                bytes,
+                total,
+            },
+        );
+    }
+}
+
+/// 正在进行的安装，释放时移除安装记录
+///
+/// 同一插件同时只能有一个安装任务
+pub struct InstallTask {
+    window_id: String,
+    cancelled: watch::Receiver<bool>,
+}
+
+impl InstallTask {
+    pub fn begin(window_id: &str) -> AppResult<Self> {
+        let mut installs = INSTALLS.lock();
+        if installs.contains_key(window_id) {
+            return Err(AppError::from(format!("插件 {} 正在安装中", window_id)));
+        }
+        let (sender, cancelled) = watch::channel(false);
+        installs.insert(window_id.to_string(), sender);
+        Ok(Self {
+            window_id: window_id.to_string(),
+            cancelled,
+        })
+    }
+
+    /// 已取消时返回错误
+    pub fn check(&self) -> AppResult<()> {
+        if *self.cancelled.borrow() {
+            return Err(AppError::from("插件安装已取消"));
+        }
+        Ok(())
+    }
+
+    /// 等待取消信号
+    async fn cancelled(&mut self) {
+        if self.cancelled.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+impl Drop for InstallTask {
+    fn drop(&mut self) {
+        INSTALLS.lock().remove(&self.window_id);
+    }
+}
+
+/// 取消正在进行的安装，已开始解压的安装无法取消
+pub fn cancel_install(window_id: &str) -> AppResult<()> {
+    match INSTALLS.lock().get(window_id) {
+        Some(sender) => {
+            sender.send_replace(true);
+            Ok(())
+        }
+        None => Err(AppError::from(format!("插件 {} 没有正在进行的安装", window_id))),
+    }
+}
+
+/// 单次下载请求的错误
+enum AttemptError {
+    /// 连接中断等可以续传的错误
+    Retry(AppError),
+    Fatal(AppError),
+}
+
+/// 下载插件包到临时文件，返回文件路径
+///
+/// 连接中断时通过 HTTP Range 从已下载的位置续传，下载过程中超过 [`MAX_ZIP_SIZE`] 立即中止。
+/// 网络错误导致失败时保留临时文件，下次安装同一插件包时继续下载；取消安装时删除临时文件
+pub async fn download_package(
+    client: &Client,
+    url: &Url,
+    sha256: &str,
+    task: &mut InstallTask,
+) -> AppResult<PathBuf> {
+    let path = download_path(sha256)?;
+    if let Some(parent) = path.parent() {
+        tokio_fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::from(format!("创建下载目录失败: {}", e)))?;
+    }
+
+    let mut retries = 0;
+    loop {
+        // 包括在等待重试时取消
+        if let Err(e) = task.check() {
+            discard_download(&path);
+            return Err(e);
+        }
+        match download_attempt(client, url, &path, task).await {
+            Ok(()) => return Ok(path),
+            Err(AttemptError::Retry(e)) if retries < MAX_RETRIES => {
+                retries += 1;
+                Logger::write_log(LogEntry {
+                    level: "warn".to_string(),
+                    message: format!("插件下载中断，正在续传 ({}/{})", retries, MAX_RETRIES),
+                    timestamp: String::new(),
+                    details: Some(e.to_string()),
+                })
+                .map_err(AppError::from)?;
+                tokio::select! {
+                    _ = tokio::time::sleep(RETRY_DELAY) => {}
+                    _ = task.cancelled() => {}
+                }
+            }
+            Err(AttemptError::Retry(e)) => return Err(e),
+            Err(AttemptError::Fatal(e)) => {
+                discard_download(&path);
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// 删除下载的临时文件
+pub fn discard_download(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+async fn download_attempt(
+    client: &Client,
+    url: &Url,
+    path: &Path,
+    task: &mut InstallTask,
+) -> Result<(), AttemptError> {
+    let mut downloaded = tokio_fs::metadata(path).await.map(|meta| meta.len()).unwrap_or(0);
+
+    let mut request = client
+        .get(url.clone())
+        .timeout(DOWNLOAD_TIMEOUT)
+        .header(ACCEPT, "*/*");
+    if downloaded > 0 {
+        request = request.header(RANGE, format!("bytes={}-", downloaded));
+    }
+    let mut response = tokio::select! {
+        response = request.send() => response
+            .map_err(|e| AttemptError::Retry(AppError::from(format!("下载文件失败: {}", e))))?,
+        _ = task.cancelled() => return Err(AttemptError::Fatal(AppError::from("插件安装已取消"))),
+    };
+
+    let status = response.status();
+    let append = match status {
+        StatusCode::PARTIAL_CONTENT if downloaded > 0 => true,
+        // 临时文件与服务器上的插件包不一致，重新下载
+        StatusCode::RANGE_NOT_SATISFIABLE if downloaded > 0 => {
+            discard_download(path);
+            return Err(AttemptError::Retry(AppError::from("续传位置无效，重新下载")));
+        }
+        status if status.is_success() => false,
+        status => {
+            let error_text = response.text().await.unwrap_or_default();
+            Logger::write_log(LogEntry {
+                level: "error".to_string(),
+                message: "下载请求失败".to_string(),
+                timestamp: String::new(),
+                details: Some(format!("状态码: {}, 错误内容: {}", status, error_text)),
+            })
+            .map_err(|e| AttemptError::Fatal(AppError::from(e)))?;
+            return Err(AttemptError::Fatal(AppError::from(format!(
+                "下载失败，状态码: {} - 错误内容: {}",
+                status, error_text
+            ))));
+        }
+    };
+    if !append {
+        downloaded = 0;
+    }
+
+    let total = response.content_length().map(|len| len + downloaded);
+    if let Some(total) = total.filter(|total| *total > MAX_ZIP_SIZE) {
+        return Err(AttemptError::Fatal(AppError::from(format!(
+            "插件包太大: {} bytes",
+            total
+        ))));
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .await
+        .map_err(|e| AttemptError::Fatal(AppError::from(format!("创建临时文件失败: {}", e))))?;
+
+    emit_progress(&task.window_id, InstallPhase::Downloading, downloaded, total);
+    let mut last_emit = Instant::now();
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk.map_err(|e| {
+                AttemptError::Retry(AppError::from(format!("读取响应失败: {}", e)))
+            }),
+            _ = task.cancelled() => Err(AttemptError::Fatal(AppError::from("插件安装已取消"))),
+        };
+        // 中断前写入的数据保留在临时文件中用于续传
+        let chunk = match chunk {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = file.flush().await;
+                return Err(e);
+            }
+        };
+
+        downloaded += chunk.len() as u64;
+        if downloaded > MAX_ZIP_SIZE {
+            return Err(AttemptError::Fatal(AppError::from(format!(
+                "插件包太大: 超过 {} bytes",
+                MAX_ZIP_SIZE
+            ))));
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| AttemptError::Fatal(AppError::from(format!("写入临时文件失败: {}", e))))?;
+
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            emit_progress(&task.window_id, InstallPhase::Downloading, downloaded, total);
+            last_emit = Instant::now();
+        }
+    }
+    file.flush()
+        .await
+        .map_err(|e| AttemptError::Fatal(AppError::from(format!("写入临时文件失败: {}", e))))?;
+
+    emit_progress(&task.window_id, InstallPhase::Downloading, downloaded, total);
+    Ok(())
+}
+
+fn download_path(sha256: &str) -> AppResult<PathBuf> {
+    let sha256 = sha256.trim().to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::from("插件包校验和格式无效，应为 64 位十六进制字符串"));
+    }
+    get_myhelper_path()
+        .map(|path| path.join(DOWNLOAD_DIR).join(format!("{}.part", sha256)))
+        .map_err(AppError::from)
+}
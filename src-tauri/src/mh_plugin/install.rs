@@ -1,24 +1,34 @@
+use crate::mh_plugin::capability::describe_capability;
+use crate::mh_plugin::dependency::PluginDependencyService;
+use crate::mh_plugin::download::{
+    cancel_install, discard_download, download_package, emit_progress, InstallPhase, InstallTask,
+};
+use crate::mh_plugin::dev::PluginDevService;
+use crate::mh_plugin::lifecycle::PluginLifecycleService;
+use crate::mh_plugin::manifest::{is_valid_window_id, PluginManifest, MAX_ZIP_SIZE};
+use crate::mh_plugin::storage::PluginStorageService;
+use crate::mh_plugin::sync::{process_plugin, PluginScan};
+use crate::mh_plugin::verify::{verify_package, PackageVerification};
+use crate::mh_plugin::upgrade::{install_staged, previous_manifest, remove_versions, rollback};
+use crate::services::database::{save_installed_plugin, save_rolled_back_plugin};
 use crate::utils::error::{AppError, AppResult};
 use crate::services::logger::{LogEntry, Logger};
 use crate::utils::path::get_myhelper_path;
-use reqwest::Client;
 use serde_json;
+use serde_json::Value;
 use std::fs::{self, File};
-use std::io::{self, Cursor, Read, Seek};
+use std::io::{Cursor, Read, Seek};
+use std::collections::HashSet;
 use std::path::Path;
+use crate::utils::reqwest::create_web_client;
+use semver::Version;
 use url::Url;
 use zip::read::ZipArchive;
 
-const MAX_ZIP_SIZE: usize = 15 * 1024 * 1024; // 15MB
-const REQUIRED_FILES: [&str; 2] = ["index.html", "mhPlugin.json"];
 
 // 验证窗口ID格式
 fn validate_window_id(window_id: &str) -> AppResult<()> {
-    if window_id.is_empty()
-        || !window_id
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    {
+    if !is_valid_window_id(window_id) {
         Logger::write_log(LogEntry {
             level: "error".to_string(),
             message: format!("无效的窗口ID: {}", window_id),
@@ -27,114 +37,167 @@ fn validate_window_id(window_id: &str) -> AppResult<()> {
         })
         .map_err(|e| AppError::from(e))?;
         return Err(AppError::from(
-            "无效的窗口ID，只能包含字母、数字、连字符或下划线",
+            "无效的窗口ID，只能包含 ASCII 字母、数字、连字符或下划线",
         ));
     }
     Ok(())
 }
 
-// 准备插件目标目录
-fn prepare_plugin_directory(window_id: &str) -> AppResult<std::path::PathBuf> {
-    // 获取用户目录
-    let target_dir = get_myhelper_path()
-        .map(|path| path.join("Plugin").join(window_id))
-        .map_err(|e| AppError::from(e))?;
-
-    // 如果目录已存在，先删除
-    if target_dir.exists() {
-        fs::remove_dir_all(&target_dir)
-            .map_err(|e| AppError::from(format!("删除目录失败: {}", e)))?;
+// 开发模式插件直接使用本地文件，不能通过安装或回滚替换
+fn ensure_not_dev(window_id: &str) -> AppResult<()> {
+    if PluginDevService::is_dev(window_id) {
+        return Err(AppError::from(format!(
+            "插件 {} 正在以开发模式加载，请先移除开发模式插件",
+            window_id
+        )));
     }
-    fs::create_dir_all(&target_dir).map_err(|e| AppError::from(format!("创建目录失败: {}", e)))?;
+    Ok(())
+}
 
-    Ok(target_dir)
+// 读取插件包中的清单，检查其他插件的版本要求并安装缺少的依赖
+//
+// `resolving` 为依赖链上正在安装的插件，不包括当前插件
+async fn prepare_dependencies(
+    bytes: &[u8],
+    window_id: &str,
+    mut resolving: Vec<(String, Version)>,
+    allow_unsigned: bool,
+) -> AppResult<()> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| AppError::from(format!("无效的ZIP文件: {}", e)))?;
+    let manifest = PluginManifest::from_zip(&mut archive, Some(window_id))?;
+    PluginDependencyService::check_dependents(&manifest)?;
+    resolving.push((manifest.window_id.clone(), manifest.version.clone()));
+    PluginDependencyService::install_dependencies(&manifest, &resolving, allow_unsigned).await
 }
 
-// 安装ZIP格式的插件
+// 安装ZIP格式的插件，清单校验通过后才会分阶段替换插件目录
 fn install_plugin_from_zip<R: Read + Seek>(
     archive_reader: R,
     window_id: &str,
-    target_dir: &Path,
-) -> AppResult<()> {
+) -> AppResult<PluginManifest> {
     // 解压ZIP文件
     let mut archive = ZipArchive::new(archive_reader)
         .map_err(|e| AppError::from(format!("无效的ZIP文件: {}", e)))?;
 
-    // 检查必要文件是否存在
-    let mut found_files = vec![false; REQUIRED_FILES.len()];
-    for i in 0..archive.len() {
-        let file = archive
-            .by_index(i)
-            .map_err(|e| AppError::from(format!("读取ZIP文件失败: {}", e)))?;
-        let name = file.name();
-        for (idx, required_file) in REQUIRED_FILES.iter().enumerate() {
-            if name.ends_with(required_file) {
-                found_files[idx] = true;
-            }
-        }
-    }
-
-    if found_files.iter().any(|&found| !found) {
-        let missing_files: Vec<_> = REQUIRED_FILES
-            .iter()
-            .zip(found_files.iter())
-            .filter(|(_, &found)| !found)
-            .map(|(&name, _)| name)
-            .collect();
-        return Err(AppError::from(format!("缺少必要文件: {:?}", missing_files)));
-    }
+    // 校验插件清单和入口文件
+    PluginManifest::from_zip(&mut archive, Some(window_id))?;
 
-    // 解压文件
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| AppError::from(format!("提取文件失败: {}", e)))?;
-        let out_path = target_dir.join(file.mangled_name());
-
-        if out_path.starts_with(&target_dir) {
-            if let Some(parent) = out_path.parent() {
-                // 如果父目录路径存在且是一个文件，先删除它
-                if parent.exists() && parent.is_file() {
-                    fs::remove_file(parent)
-                        .map_err(|e| AppError::from(format!("删除已存在的文件失败: {}", e)))?;
-                }
-                // 创建父目录
-                fs::create_dir_all(parent)
-                    .map_err(|e| AppError::from(format!("创建目录失败: {}", e)))?;
-            }
-            // 如果文件已存在，先删除
-            if out_path.exists() {
-                if out_path.is_file() {
-                    fs::remove_file(&out_path)
-                        .map_err(|e| AppError::from(format!("删除已存在的文件失败: {}", e)))?;
-                } else {
-                    fs::remove_dir_all(&out_path)
-                        .map_err(|e| AppError::from(format!("删除已存在的目录失败: {}", e)))?;
-                }
-            }
-            let mut output_file = File::create(&out_path)
-                .map_err(|e| AppError::from(format!("创建文件失败: {}", e)))?;
-            io::copy(&mut file, &mut output_file)
-                .map_err(|e| AppError::from(format!("写入文件失败: {}", e)))?;
-        } else {
-            return Err(AppError::from("ZIP文件包含非法路径"));
-        }
-    }
+    // 解压到临时目录并替换插件目录，保留插件数据
+    let manifest = install_staged(&mut archive, window_id)?;
 
     // 记录安装成功的日志
     Logger::write_log(LogEntry {
         level: "info".to_string(),
         message: format!("插件安装成功，窗口ID: {}", window_id),
         timestamp: String::new(),
-        details: None,
+        details: Some(format!("版本: {}", manifest.version)),
     })
     .map_err(|e| AppError::from(e))?;
 
-    Ok(())
+    Ok(manifest)
 }
 
+/// 按插件目录生成插件配置记录 (info, config, data)
+async fn load_plugin_record(window_id: &str) -> AppResult<(Value, Value, Value)> {
+    let plugin_dir = get_myhelper_path()
+        .map(|path| path.join("Plugin").join(window_id))
+        .map_err(AppError::from)?;
+    match process_plugin(&plugin_dir, window_id, &HashSet::new()).await? {
+        PluginScan::Valid(info, config, data) => Ok((info, config, data)),
+        PluginScan::Invalid(reason) => Err(AppError::from(format!(
+            "读取已安装的插件失败: {}",
+            reason
+        ))),
+        PluginScan::Missing => Err(AppError::from("读取已安装的插件失败")),
+    }
+}
+
+/// 把新版本、校验结果和能力授权写入插件配置
+///
+/// 只保留清单中申请了的能力，`grants` 为 None 时保留已有的授权
+async fn save_installed(
+    window_id: &str,
+    verification: &PackageVerification,
+    manifest: &PluginManifest,
+    grants: Option<Vec<String>>,
+) -> AppResult<()> {
+    let record = load_plugin_record(window_id).await?;
+    let verification = serde_json::to_value(verification)
+        .map_err(|e| AppError::from(format!("序列化校验结果失败: {}", e)))?;
+    let grants = grants.map(|grants| {
+        grants
+            .into_iter()
+            .filter(|grant| manifest.permissions.contains(grant))
+            .collect()
+    });
+    save_installed_plugin(window_id, verification, grants, record).await
+}
+
+/// 从插件市场安装插件
+///
+/// # Arguments
+///
+/// * `url` - 插件包下载地址
+/// * `window_id` - 插件的窗口ID
+/// * `sha256` - 插件市场提供的插件包 SHA-256
+/// * `signature` - 插件包的 Ed25519 签名（Base64），提供时必须由信任的发布者签名
+/// * `grants` - 用户在安装时授予的能力，为空时保留已有的授权
+/// * `allow_unsigned` - 用户已确认安装未签名的插件包，同时适用于需要安装的依赖
+#[permission_macro::permission(cap = "plugin.manage")]
 #[tauri::command]
-pub async fn mh_plugin_install(url: &str, window_id: &str) -> AppResult<()> {
+pub async fn mh_plugin_install(
+    url: &str,
+    window_id: &str,
+    sha256: Option<String>,
+    signature: Option<String>,
+    grants: Option<Vec<String>>,
+    allow_unsigned: Option<bool>,
+) -> AppResult<()> {
+    install_from_market(
+        url,
+        window_id,
+        sha256.as_deref(),
+        signature.as_deref(),
+        grants,
+        allow_unsigned.unwrap_or(false),
+    )
+    .await
+}
+
+/// 下载、校验并安装插件市场中的插件包，安装和更新插件共用
+pub async fn install_from_market(
+    url: &str,
+    window_id: &str,
+    sha256: Option<&str>,
+    signature: Option<&str>,
+    grants: Option<Vec<String>>,
+    allow_unsigned: bool,
+) -> AppResult<()> {
+    install_market_package(
+        url,
+        window_id,
+        sha256,
+        signature,
+        grants,
+        Vec::new(),
+        allow_unsigned,
+    )
+    .await
+}
+
+/// 安装插件市场中的插件包，先安装插件缺少的依赖
+///
+/// `resolving` 为依赖链上正在安装的插件及其版本，安装依赖时传入
+pub(crate) async fn install_market_package(
+    url: &str,
+    window_id: &str,
+    sha256: Option<&str>,
+    signature: Option<&str>,
+    grants: Option<Vec<String>>,
+    resolving: Vec<(String, Version)>,
+    allow_unsigned: bool,
+) -> AppResult<()> {
     Logger::write_log(LogEntry {
         level: "info".to_string(),
         message: "开始安装插件".to_string(),
@@ -145,20 +208,19 @@ pub async fn mh_plugin_install(url: &str, window_id: &str) -> AppResult<()> {
 
     // 验证窗口ID格式
     validate_window_id(window_id)?;
+    ensure_not_dev(window_id)?;
 
     // 解析URL
     let parsed_url = Url::parse(url).map_err(|e| AppError::from(format!("无效的URL: {}", e)))?;
+    if !matches!(parsed_url.scheme(), "http" | "https") {
+        return Err(AppError::from("插件下载地址只支持 HTTP 或 HTTPS"));
+    }
 
-    let host = parsed_url
-        .host_str()
-        .ok_or_else(|| AppError::from("URL中缺少host"))?;
-    let port = parsed_url
-        .port()
-        .map_or(String::new(), |p| format!(":{}", p));
-    let host_with_port = format!("{}{}", host, port);
-
-    // 准备插件目标目录
-    let target_dir = prepare_plugin_directory(window_id)?;
+    // 市场插件必须提供校验和
+    let sha256 = sha256
+        .map(str::trim)
+        .filter(|sha256| !sha256.is_empty())
+        .ok_or_else(|| AppError::from("缺少插件包校验和，无法验证插件完整性"))?;
 
     // 记录开始安装的日志
     Logger::write_log(LogEntry {
@@ -169,97 +231,82 @@ pub async fn mh_plugin_install(url: &str, window_id: &str) -> AppResult<()> {
     })
     .map_err(|e| AppError::from(e))?;
 
-    // 创建异步HTTP客户端
-    let client = Client::builder()
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .no_proxy()  // 禁用系统代理
-        .danger_accept_invalid_certs(true)  // 接受无效证书
-        .build()
-        .map_err(|e| AppError::from(format!("创建HTTP客户端失败: {}", e)))?;
-
-    let response = client
-        .get(url)
-        .header("Accept", "*/*")
-        .header("Accept-Encoding", "gzip, deflate")
-        .header("Connection", "keep-alive")
-        .header("Host", host_with_port)
-        .send()
-        .await
-        .map_err(|e| AppError::from(format!("下载文件失败: {}", e)))?;
+    // 同一插件同时只能有一个安装任务，可通过 mh_plugin_cancel_install 取消
+    let mut task = InstallTask::begin(window_id)?;
 
-    Logger::write_log(LogEntry {
-        level: "debug".to_string(),
-        message: "下载响应信息".to_string(),
-        timestamp: String::new(),
-        details: Some(format!("状态: {:?}, 响应头: {:#?}", response.status(), response.headers())),
-    })
-    .map_err(|e| AppError::from(e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        Logger::write_log(LogEntry {
-            level: "error".to_string(),
-            message: "下载请求失败".to_string(),
-            timestamp: String::new(),
-            details: Some(format!("状态码: {}, 错误内容: {}", status, error_text)),
-        })
-        .map_err(|e| AppError::from(e))?;
-        return Err(AppError::from(format!(
-            "下载失败，状态码: {} - 错误内容: {}",
-            status, error_text
-        )));
-    }
-
-    let content_type = response
-        .headers()
-        .get("content-type")
-        .and_then(|v| v.to_str().ok());
-    Logger::write_log(LogEntry {
-        level: "debug".to_string(),
-        message: "响应Content-Type".to_string(),
-        timestamp: String::new(),
-        details: Some(format!("{:?}", content_type)),
-    })
-    .map_err(|e| AppError::from(e))?;
+    // 使用共享的HTTP客户端，保持正常的证书校验和代理设置
+    let client = create_web_client()?;
 
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| AppError::from(format!("读取响应失败: {}", e)))?;
+    // 流式下载到临时文件，连接中断时自动续传
+    let package_path = download_package(&client, &parsed_url, sha256, &mut task).await?;
+    let bytes = fs::read(&package_path)
+        .map_err(|e| AppError::from(format!("读取插件包失败: {}", e)))?;
 
     Logger::write_log(LogEntry {
         level: "debug".to_string(),
         message: "下载完成".to_string(),
         timestamp: String::new(),
-        details: Some(format!("内容大小: {} bytes{}", bytes.len(),
-            if bytes.len() < 100 {
-                format!(", 内容: {:?}", String::from_utf8_lossy(&bytes))
-            } else {
-                String::new()
-            }
-        )),
+        details: Some(format!("内容大小: {} bytes", bytes.len())),
     })
     .map_err(|e| AppError::from(e))?;
 
-    if bytes.len() > MAX_ZIP_SIZE {
-        return Err(AppError::from(format!("插件包太大: {} bytes", bytes.len())));
-    }
-
     // 检查文件头是否为ZIP格式（ZIP文件的魔数是 PK\x03\x04）
     if bytes.len() < 4 || &bytes[0..4] != b"PK\x03\x04" {
+        discard_download(&package_path);
         return Err(AppError::from("下载的文件不是有效的ZIP格式"));
     }
 
-    // 创建内存读取器并安装插件
-    let cursor = Cursor::new(bytes);
-    install_plugin_from_zip(cursor, window_id, &target_dir)?;
+    // 校验插件包完整性和签名，校验失败时不会改动已安装的插件
+    emit_progress(window_id, InstallPhase::Verifying, bytes.len() as u64, Some(bytes.len() as u64));
+    let verification = match verify_package(&bytes, Some(sha256), signature, allow_unsigned) {
+        Ok(verification) => verification,
+        Err(e) => {
+            discard_download(&package_path);
+            return Err(e);
+        }
+    };
+
+    // 先安装依赖，依赖安装失败时不会改动已安装的插件；开始解压后不再响应取消
+    if let Err(e) = prepare_dependencies(&bytes, window_id, resolving, allow_unsigned)
+        .await
+        .and_then(|_| task.check())
+    {
+        discard_download(&package_path);
+        return Err(e);
+    }
+
+    emit_progress(window_id, InstallPhase::Extracting, bytes.len() as u64, Some(bytes.len() as u64));
+    discard_download(&package_path);
+    let previous = PluginLifecycleService::installed_manifest(window_id);
+    let manifest = install_plugin_from_zip(Cursor::new(bytes), window_id)?;
+    save_installed(window_id, &verification, &manifest, grants).await?;
+    PluginLifecycleService::installed(&manifest, previous.as_ref());
 
     Ok(())
 }
 
+/// 取消正在进行的插件安装
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub fn mh_plugin_cancel_install(window_id: &str) -> AppResult<()> {
+    cancel_install(window_id)
+}
+
+/// 安装本地插件包
+///
+/// `signature` 为插件包的 Ed25519 签名（Base64），提供时必须由信任的发布者签名；
+/// `grants` 为用户在安装时授予的能力，为空时保留已有的授权；
+/// 未签名的插件包需要用户确认后传入 `allow_unsigned`
+#[permission_macro::permission(cap = "plugin.manage")]
 #[tauri::command]
-pub async fn mh_plugin_install_local(file_path: &str, window_id: &str) -> AppResult<()> {
+pub async fn mh_plugin_install_local(
+    file_path: &str,
+    window_id: &str,
+    signature: Option<String>,
+    grants: Option<Vec<String>>,
+    allow_unsigned: Option<bool>,
+) -> AppResult<()> {
+    let allow_unsigned = allow_unsigned.unwrap_or(false);
     Logger::write_log(LogEntry {
         level: "info".to_string(),
         message: "开始安装本地插件".to_string(),
@@ -270,6 +317,7 @@ pub async fn mh_plugin_install_local(file_path: &str, window_id: &str) -> AppRes
 
     // 验证窗口ID格式
     validate_window_id(window_id)?;
+    ensure_not_dev(window_id)?;
 
     // 检查文件是否存在
     let file_path = Path::new(file_path);
@@ -286,9 +334,6 @@ pub async fn mh_plugin_install_local(file_path: &str, window_id: &str) -> AppRes
         return Err(AppError::from("文件没有扩展名"));
     }
 
-    // 准备插件目标目录
-    let target_dir = prepare_plugin_directory(window_id)?;
-
     // 记录开始安装的日志
     Logger::write_log(LogEntry {
         level: "info".to_string(),
@@ -298,34 +343,42 @@ pub async fn mh_plugin_install_local(file_path: &str, window_id: &str) -> AppRes
     })
     .map_err(|e| AppError::from(e))?;
 
+    // 与插件市场的安装、更新共用安装任务，同一插件同时只能有一个安装任务
+    let task = InstallTask::begin(window_id)?;
+
     // 获取文件大小
     let file_size = fs::metadata(file_path)
         .map_err(|e| AppError::from(format!("读取文件元数据失败: {}", e)))?
-        .len() as usize;
+        .len();
 
     if file_size > MAX_ZIP_SIZE {
         return Err(AppError::from(format!("插件包太大: {} bytes", file_size)));
     }
 
-    // 打开文件
-    let file = File::open(file_path).map_err(|e| AppError::from(format!("打开文件失败: {}", e)))?;
+    // 读取插件包并校验签名
+    let bytes = fs::read(file_path).map_err(|e| AppError::from(format!("读取文件失败: {}", e)))?;
+    let verification = verify_package(&bytes, None, signature.as_deref(), allow_unsigned)?;
+    prepare_dependencies(&bytes, window_id, Vec::new(), allow_unsigned).await?;
+    // 开始解压后不再响应取消
+    task.check()?;
 
     // 安装插件
-    install_plugin_from_zip(file, window_id, &target_dir)?;
+    let previous = PluginLifecycleService::installed_manifest(window_id);
+    let manifest = install_plugin_from_zip(Cursor::new(bytes), window_id)?;
+    save_installed(window_id, &verification, &manifest, grants).await?;
+    PluginLifecycleService::installed(&manifest, previous.as_ref());
 
     Ok(())
 }
 
+/// 卸载插件，删除插件目录前执行插件的 onUninstall 钩子
+#[permission_macro::permission(cap = "plugin.manage")]
 #[tauri::command]
-pub fn mh_plugin_uninstall(window_id: &str) -> AppResult<()> {
+pub async fn mh_plugin_uninstall(window_id: &str) -> AppResult<()> {
     // 验证窗口ID格式
-    if window_id.is_empty()
-        || !window_id
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    {
+    if !is_valid_window_id(window_id) {
         return Err(AppError::from(
-            "无效的窗口ID，只能包含字母、数字、连字符或下划线",
+            "无效的窗口ID，只能包含 ASCII 字母、数字、连字符或下划线",
         ));
     }
 
@@ -338,18 +391,38 @@ pub fn mh_plugin_uninstall(window_id: &str) -> AppResult<()> {
     })
     .map_err(|e| AppError::from(e))?;
 
+    // 其他插件依赖该插件时拒绝卸载
+    let dependents = PluginDependencyService::dependents(window_id)?;
+    if !dependents.is_empty() {
+        return Err(AppError::from(format!(
+            "插件 {} 被 {} 依赖，请先卸载这些插件",
+            window_id,
+            dependents.join(", ")
+        )));
+    }
+
+    // 开发模式插件只移除注册，保留本地目录
+    if PluginDevService::is_dev(window_id) {
+        return PluginDevService::unregister(window_id).await;
+    }
+
     // 获取用户目录
     let target_dir = get_myhelper_path()
         .map(|path| path.join("Plugin").join(window_id))
         .map_err(|e| AppError::from(e))?;
 
     // 删除文件夹及其内容
+    let previous = PluginLifecycleService::installed_manifest(window_id);
     if target_dir.exists() {
+        PluginLifecycleService::uninstalling(window_id).await;
         fs::remove_dir_all(&target_dir)
             .map_err(|e| AppError::from(format!("删除插件目录失败: {}", e)))?;
     } else {
         return Err(AppError::from("插件目录不存在"));
     }
+    remove_versions(window_id)?;
+    PluginStorageService::clear(window_id).await?;
+    PluginLifecycleService::uninstalled(window_id, previous.as_ref());
 
     // 记录卸载成功的日志
     Logger::write_log(LogEntry {
@@ -363,7 +436,26 @@ pub fn mh_plugin_uninstall(window_id: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// 回滚插件到上一个版本
+///
+/// 恢复的版本沿用当前的插件数据，回滚后当前版本成为新的回滚版本
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub async fn mh_plugin_rollback(window_id: String) -> AppResult<String> {
+    validate_window_id(&window_id)?;
+    ensure_not_dev(&window_id)?;
+    // 回滚版本不满足其他插件的版本要求时不替换插件目录
+    PluginDependencyService::check_dependents(&previous_manifest(&window_id)?)?;
+    let previous = PluginLifecycleService::installed_manifest(&window_id);
+    let manifest = rollback(&window_id)?;
+    let (_, _, data) = load_plugin_record(&window_id).await?;
+    save_rolled_back_plugin(&window_id, data).await?;
+    PluginLifecycleService::rolled_back(&manifest, previous.as_ref());
+    Ok(manifest.version.to_string())
+}
+
 /// 解析插件包
+#[permission_macro::permission(cap = "plugin.manage")]
 #[tauri::command]
 pub async fn mh_plugin_analyze_package(file_path: &str) -> Result<serde_json::Value, String> {
     Logger::write_log(LogEntry {
@@ -391,7 +483,7 @@ pub async fn mh_plugin_analyze_package(file_path: &str) -> Result<serde_json::Va
 
     // 获取文件大小
     let file_size = match fs::metadata(file_path) {
-        Ok(meta) => meta.len() as usize,
+        Ok(meta) => meta.len(),
         Err(e) => return Err(format!("读取文件元数据失败: {}", e)),
     };
 
@@ -411,72 +503,38 @@ pub async fn mh_plugin_analyze_package(file_path: &str) -> Result<serde_json::Va
         Err(e) => return Err(format!("无效的ZIP文件: {}", e)),
     };
 
-    // 查找并读取mhPlugin.json文件
-    let mut plugin_info = None;
-    for i in 0..archive.len() {
-        let mut file = match archive.by_index(i) {
-            Ok(f) => f,
-            Err(e) => return Err(format!("读取ZIP文件内容失败: {}", e)),
-        };
-
-        let name = file.name();
-        if name.ends_with("mhPlugin.json") {
-            let mut contents = String::new();
-            if let Err(e) = file.read_to_string(&mut contents) {
-                return Err(format!("读取mhPlugin.json文件失败: {}", e));
-            }
-
-            plugin_info = match serde_json::from_str::<serde_json::Value>(&contents) {
-                Ok(info) => Some(info),
-                Err(e) => return Err(format!("解析mhPlugin.json文件失败: {}", e)),
-            };
-
-            break;
-        }
-    }
-
-    // 如果没有找到mhPlugin.json文件或解析失败
-    let plugin_info = plugin_info.ok_or("未找到有效的mhPlugin.json文件")?;
+    // 读取并校验mhPlugin.json文件
+    let manifest = PluginManifest::from_zip(&mut archive, None).map_err(|e| e.to_string())?;
 
     // 构建返回结果
     let result = serde_json::json!({
         "success": true,
         "size": file_size,
         "pluginInfo": {
-            "windowId": plugin_info.get("windowId").and_then(|v| v.as_str()).unwrap_or(""),
-            "name": plugin_info.get("title").and_then(|v| v.as_str()).unwrap_or(""),
-            "description": plugin_info.get("description").and_then(|v| v.as_str()).unwrap_or(""),
-            "version": plugin_info.get("version").and_then(|v| v.as_str()),
-            "author": plugin_info.get("author").and_then(|v| v.as_str()),
-            "email": plugin_info.get("email").and_then(|v| v.as_str()),
-            "tags": plugin_info.get("tags").and_then(|v| v.as_array()).map(|arr| {
-                arr.iter()
-                   .filter_map(|item| item.as_str())
-                   .map(|s| s.to_string())
-                   .collect::<Vec<String>>()
-            }),
-            "category": plugin_info.get("category").and_then(|v| v.as_str()),
-            "size": plugin_info.get("size").and_then(|v| v.as_array()).map(|arr| {
-                if arr.len() >= 2 {
-                    let width = arr[0].as_u64().unwrap_or(800);
-                    let height = arr[1].as_u64().unwrap_or(600);
-                    vec![width, height]
-                } else {
-                    vec![800, 600]
-                }
-            }),
-            "position": plugin_info.get("position").and_then(|v| v.as_array()).map(|arr| {
-                if arr.len() >= 2 {
-                    let x = arr[0].as_i64().unwrap_or(-1);
-                    let y = arr[1].as_i64().unwrap_or(-1);
-                    vec![x, y]
-                } else {
-                    vec![-1, -1]
-                }
-            }),
-            "alwaysOnTop": plugin_info.get("alwaysOnTop").and_then(|v| v.as_bool()),
-            "resizable": plugin_info.get("resizable").and_then(|v| v.as_bool()),
-            "icon": plugin_info.get("icon").and_then(|v| v.as_str()),
+            "windowId": manifest.window_id,
+            "name": manifest.title,
+            "description": manifest.description.unwrap_or_default(),
+            "version": manifest.version.to_string(),
+            "minHostVersion": manifest.min_host_version.map(|v| v.to_string()),
+            "entry": manifest.entry,
+            "author": manifest.author,
+            "email": manifest.email,
+            "tags": manifest.tags,
+            "category": manifest.category,
+            "size": manifest.size,
+            "position": manifest.position,
+            "alwaysOnTop": manifest.always_on_top,
+            "resizable": manifest.resizable,
+            "icon": manifest.icon,
+            "capabilities": manifest.permissions.iter().map(|name| serde_json::json!({
+                "name": name,
+                "description": describe_capability(name).unwrap_or_default(),
+            })).collect::<Vec<_>>(),
+            "permissions": manifest.permissions,
+            "hotkeys": manifest.hotkeys,
+            "lifecycle": manifest.lifecycle,
+            "kind": manifest.kind,
+            "dependencies": manifest.dependencies,
         }
     });
 
@@ -0,0 +1,343 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::bus::PluginBus;
+use crate::mh_plugin::dev::PluginDevService;
+use crate::mh_plugin::manifest::{PluginKind, PluginManifest, MANIFEST_FILE};
+use crate::mh_plugin::state::PluginStateService;
+use crate::services::database::query_enabled_plugin_ids;
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use crate::utils::response::ApiResponse;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+use url::form_urlencoded::byte_serialize;
+use url::Url;
+
+/// 插件安装完成后通知所有窗口
+pub const PLUGIN_INSTALLED_EVENT: &str = "plugin:installed";
+/// 插件升级或回滚后通知所有窗口
+pub const PLUGIN_UPDATED_EVENT: &str = "plugin:updated";
+/// 插件卸载后通知所有窗口
+pub const PLUGIN_UNINSTALLED_EVENT: &str = "plugin:uninstalled";
+/// 插件窗口已打开时，生命周期钩子通过该事件发送到插件窗口
+pub const PLUGIN_LIFECYCLE_EVENT: &str = "plugin:lifecycle";
+
+/// 隐藏窗口中的钩子最长执行时间，超时后关闭窗口
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 正在隐藏窗口中执行钩子的插件，值为完成信号
+static RUNNING_HOOKS: Lazy<Mutex<HashMap<String, oneshot::Sender<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 生命周期钩子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LifecycleHook {
+    OnInstall,
+    OnUpgrade,
+    OnUninstall,
+    OnStartup,
+}
+
+impl LifecycleHook {
+    fn name(self) -> &'static str {
+        match self {
+            LifecycleHook::OnInstall => "onInstall",
+            LifecycleHook::OnUpgrade => "onUpgrade",
+            LifecycleHook::OnUninstall => "onUninstall",
+            LifecycleHook::OnStartup => "onStartup",
+        }
+    }
+
+    fn page(self, manifest: &PluginManifest) -> Option<&str> {
+        let hooks = &manifest.lifecycle;
+        match self {
+            LifecycleHook::OnInstall => hooks.on_install.as_deref(),
+            LifecycleHook::OnUpgrade => hooks.on_upgrade.as_deref(),
+            LifecycleHook::OnUninstall => hooks.on_uninstall.as_deref(),
+            LifecycleHook::OnStartup => hooks.on_startup.as_deref(),
+        }
+    }
+}
+
+/// 发送到已打开的插件窗口的钩子
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleEvent {
+    pub hook: LifecycleHook,
+    /// 升级前的版本，只有 onUpgrade 时有值
+    pub previous_version: Option<String>,
+}
+
+/// 插件安装、升级或卸载事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginChangedEvent {
+    pub window_id: String,
+    /// 当前版本，卸载时为 None
+    pub version: Option<String>,
+    /// 变更前的版本，首次安装时为 None
+    pub previous_version: Option<String>,
+}
+
+pub struct PluginLifecycleService;
+
+impl PluginLifecycleService {
+    /// 读取已安装的插件清单，插件不存在或清单无效时返回 None
+    pub fn installed_manifest(window_id: &str) -> Option<PluginManifest> {
+        if let Some(manifest) = PluginDevService::manifest(window_id) {
+            return Some(manifest);
+        }
+        let path = plugin_dir(window_id).ok()?.join(MANIFEST_FILE);
+        let bytes = fs::read(path).ok()?;
+        PluginManifest::parse(&bytes, Some(window_id)).ok()
+    }
+
+    /// 插件安装或升级完成，通知所有窗口并在后台执行 onInstall 或 onUpgrade
+    ///
+    /// `previous` 为安装前的插件清单，为 None 表示首次安装，已禁用的插件和共享运行库不执行钩子
+    pub fn installed(manifest: &PluginManifest, previous: Option<&PluginManifest>) {
+        let previous_version = previous.map(|previous| previous.version.to_string());
+        let event = PluginChangedEvent {
+            window_id: manifest.window_id.clone(),
+            version: Some(manifest.version.to_string()),
+            previous_version: previous_version.clone(),
+        };
+        let (event_name, hook) = match previous {
+            Some(_) => (PLUGIN_UPDATED_EVENT, LifecycleHook::OnUpgrade),
+            None => (PLUGIN_INSTALLED_EVENT, LifecycleHook::OnInstall),
+        };
+        emit(event_name, &event);
+        refresh_hotkeys();
+
+        let manifest = manifest.clone();
+        tauri::async_runtime::spawn(async move {
+            Self::run_logged(&manifest, hook, previous_version).await;
+        });
+    }
+
+    /// 插件回滚完成，通知所有窗口，回滚不执行钩子
+    pub fn rolled_back(manifest: &PluginManifest, previous: Option<&PluginManifest>) {
+        emit(
+            PLUGIN_UPDATED_EVENT,
+            &PluginChangedEvent {
+                window_id: manifest.window_id.clone(),
+                version: Some(manifest.version.to_string()),
+                previous_version: previous.map(|previous| previous.version.to_string()),
+            },
+        );
+        refresh_hotkeys();
+    }
+
+    /// 卸载前执行 onUninstall，等待钩子完成或超时，已禁用的插件和共享运行库不执行钩子
+    pub async fn uninstalling(window_id: &str) {
+        if let Some(manifest) = Self::installed_manifest(window_id) {
+            Self::run_logged(&manifest, LifecycleHook::OnUninstall, None).await;
+        }
+    }
+
+    /// 插件卸载完成，移除消息总线上的订阅和调用并通知所有窗口
+    pub fn uninstalled(window_id: &str, previous: Option<&PluginManifest>) {
+        PluginBus::remove_plugin(window_id);
+        emit(
+            PLUGIN_UNINSTALLED_EVENT,
+            &PluginChangedEvent {
+                window_id: window_id.to_string(),
+                version: None,
+                previous_version: previous.map(|previous| previous.version.to_string()),
+            },
+        );
+        refresh_hotkeys();
+    }
+
+    /// 应用启动后对所有已启用的插件执行 onStartup
+    pub async fn run_startup_hooks() -> AppResult<()> {
+        for window_id in query_enabled_plugin_ids()? {
+            let Some(manifest) = Self::installed_manifest(&window_id) else {
+                continue;
+            };
+            if LifecycleHook::OnStartup.page(&manifest).is_some() {
+                tauri::async_runtime::spawn(async move {
+                    Self::run_logged(&manifest, LifecycleHook::OnStartup, None).await;
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// 插件通知钩子执行完成，关闭隐藏窗口
+    pub fn finish(window_id: &str) {
+        if let Some(done) = RUNNING_HOOKS.lock().remove(window_id) {
+            let _ = done.send(());
+        }
+    }
+
+    /// 结束正在隐藏窗口中执行的钩子，打开插件窗口前调用以释放窗口标识
+    pub fn interrupt(window_id: &str) {
+        if RUNNING_HOOKS.lock().remove(window_id).is_some() {
+            if let Some(window) = AppHandleManager::get()
+                .and_then(|app_handle| app_handle.get_webview_window(window_id))
+            {
+                let _ = window.destroy();
+            }
+        }
+    }
+
+    /// 执行钩子并记录失败，已禁用的插件和共享运行库不执行钩子
+    async fn run_logged(
+        manifest: &PluginManifest,
+        hook: LifecycleHook,
+        previous_version: Option<String>,
+    ) {
+        if manifest.kind == PluginKind::Library
+            || PluginStateService::is_disabled(&manifest.window_id)
+        {
+            return;
+        }
+        if let Err(e) = Self::run(manifest, hook, previous_version).await {
+            let _ = Logger::write_log(LogEntry {
+                level: "warn".to_string(),
+                message: format!("插件 {} 执行 {} 失败", manifest.window_id, hook.name()),
+                timestamp: String::new(),
+                details: Some(e.to_string()),
+            });
+        }
+    }
+
+    /// 执行钩子
+    ///
+    /// 插件窗口已打开时发送 [`PLUGIN_LIFECYCLE_EVENT`] 事件，否则以插件的窗口标识创建隐藏窗口加载钩子页面，
+    /// 使钩子拥有与插件窗口相同的能力授权和自身配置
+    async fn run(
+        manifest: &PluginManifest,
+        hook: LifecycleHook,
+        previous_version: Option<String>,
+    ) -> AppResult<()> {
+        let Some(page) = hook.page(manifest) else {
+            return Ok(());
+        };
+        let app_handle = AppHandleManager::clone()
+            .ok_or_else(|| AppError::Error("获取AppHandle失败".to_string()))?;
+        let window_id = manifest.window_id.as_str();
+
+        if app_handle.get_webview_window(window_id).is_some() {
+            if RUNNING_HOOKS.lock().contains_key(window_id) {
+                return Err(AppError::Error("上一个生命周期钩子尚未完成".to_string()));
+            }
+            return app_handle
+                .emit_to(
+                    window_id,
+                    PLUGIN_LIFECYCLE_EVENT,
+                    LifecycleEvent {
+                        hook,
+                        previous_version,
+                    },
+                )
+                .map_err(|e| AppError::Error(format!("发送生命周期事件失败: {}", e)));
+        }
+
+        // 开发模式插件从本地目录或开发服务器加载钩子页面
+        let url = match PluginDevService::source(window_id) {
+            Some(source) => source.page_url(page)?,
+            None => asset_url(&plugin_dir(window_id)?.join(page))?,
+        };
+        let url = hook_url(url, hook, previous_version.as_deref());
+        let (done, finished) = oneshot::channel();
+        RUNNING_HOOKS.lock().insert(window_id.to_string(), done);
+
+        let window =
+            match WebviewWindowBuilder::new(&app_handle, window_id, WebviewUrl::External(url))
+                .title(&manifest.title)
+                .visible(false)
+                .skip_taskbar(true)
+                .build()
+            {
+                Ok(window) => window,
+                Err(e) => {
+                    RUNNING_HOOKS.lock().remove(window_id);
+                    return Err(AppError::Error(format!("创建生命周期窗口失败: {}", e)));
+                }
+            };
+
+        match tokio::time::timeout(HOOK_TIMEOUT, finished).await {
+            Ok(Ok(())) => {
+                let _ = window.destroy();
+            }
+            // 钩子被打开的插件窗口中断，隐藏窗口已经销毁
+            Ok(Err(_)) => {}
+            Err(_) => {
+                RUNNING_HOOKS.lock().remove(window_id);
+                let _ = window.destroy();
+                return Err(AppError::Error(format!(
+                    "{} 在 {} 秒内没有完成",
+                    hook.name(),
+                    HOOK_TIMEOUT.as_secs()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn plugin_dir(window_id: &str) -> AppResult<PathBuf> {
+    get_myhelper_path()
+        .map(|path| path.join("Plugin").join(window_id))
+        .map_err(AppError::from)
+}
+
+/// 插件清单变化后重新注册插件快捷键
+fn refresh_hotkeys() {
+    if let Err(e) = PluginStateService::refresh_hotkeys() {
+        let _ = Logger::write_log(LogEntry {
+            level: "warn".to_string(),
+            message: "刷新插件快捷键失败".to_string(),
+            timestamp: String::new(),
+            details: Some(e.to_string()),
+        });
+    }
+}
+
+fn emit(event: &str, payload: &PluginChangedEvent) {
+    if let Some(app_handle) = AppHandleManager::get() {
+        let _ = app_handle.emit(event, payload);
+    }
+}
+
+/// 本地文件的资源协议地址
+pub(crate) fn asset_url(path: &Path) -> AppResult<Url> {
+    let path: String = byte_serialize(path.to_string_lossy().replace('\\', "/").as_bytes())
+        .collect::<String>()
+        .replace('+', "%20");
+    #[cfg(windows)]
+    let base = "http://asset.localhost/";
+    #[cfg(not(windows))]
+    let base = "asset://localhost/";
+
+    Url::parse(&format!("{}{}", base, path))
+        .map_err(|e| AppError::Error(format!("无效的钩子页面地址: {}", e)))
+}
+
+/// 在钩子页面地址上附加钩子信息
+fn hook_url(mut url: Url, hook: LifecycleHook, previous_version: Option<&str>) -> Url {
+    url.query_pairs_mut().append_pair("hook", hook.name());
+    if let Some(previous_version) = previous_version {
+        url.query_pairs_mut()
+            .append_pair("previousVersion", previous_version);
+    }
+    url
+}
+
+/// 通知宿主生命周期钩子已执行完成
+#[permission_macro::permission(cap = "plugin.self")]
+#[tauri::command]
+pub fn mh_plugin_lifecycle_done() -> Result<ApiResponse<()>, AppError> {
+    PluginLifecycleService::finish(window.label());
+    Ok(ApiResponse::success(()))
+}
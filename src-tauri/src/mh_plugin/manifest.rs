@@ -0,0 +1,3 @@
+//! 插件清单定义在 plugin-manifest 中，与插件打包工具共用同一套校验规则
+
+pub use plugin_manifest::manifest::*;
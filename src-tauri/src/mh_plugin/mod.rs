@@ -1,6 +1,28 @@
+pub mod bus;
+pub mod capability;
+pub mod dependency;
+pub mod dev;
+pub mod download;
 pub mod install;
+pub mod lifecycle;
+pub mod manifest;
 pub mod self_config;
+pub mod state;
+pub mod storage;
 pub mod sync;
+pub mod update;
+pub mod upgrade;
+pub mod verify;
 
+pub use bus::*;
+pub use capability::*;
+pub use dependency::*;
+pub use dev::*;
 pub use install::*;
+pub use lifecycle::*;
+pub use manifest::*;
 pub use self_config::*;
+pub use state::*;
+pub use storage::*;
+pub use update::*;
+pub use verify::*;
@@ -1,21 +1,29 @@
+use crate::utils::atomic_file::{backup_path, read_json_or_recover, write_json_atomic};
 use crate::utils::path::get_myhelper_path;
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use serde_json::{json, Value};
-use simd_json;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::Window;
 use tokio::fs as tokio_fs;
-use tokio::io::AsyncReadExt;
-use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex as AsyncMutex;
+
+/// 插件自身配置文件名
+pub const SELF_CONFIG_FILE: &str = "selfConfig.json";
 
 // 使用Lazy和RwLock缓存配置路径，避免重复计算
 static CONFIG_PATHS: Lazy<RwLock<HashMap<String, PathBuf>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
+// 每个插件配置文件的读改写锁，避免同一插件的多个窗口互相覆盖
+static CONFIG_LOCKS: Lazy<RwLock<HashMap<PathBuf, Arc<AsyncMutex<()>>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
 /// 获取插件自身配置
+#[permission_macro::permission(cap = "plugin.self")]
 #[tauri::command]
 pub async fn mh_get_self_config(window: Window, keys: Vec<String>) -> Result<Value, String> {
     let window_id = window.label();
@@ -38,6 +46,7 @@ pub async fn mh_get_self_config(window: Window, keys: Vec<String>) -> Result<Val
 }
 
 /// 设置插件自身配置
+#[permission_macro::permission(cap = "plugin.self")]
 #[tauri::command]
 pub async fn mh_set_self_config(
     window: Window,
@@ -46,6 +55,8 @@ pub async fn mh_set_self_config(
 ) -> Result<(), String> {
     let window_id = window.label();
     let config_path = get_self_config_path(window_id)?;
+    let lock = get_config_lock(&config_path);
+    let _guard = lock.lock().await;
     let mut config = read_config_async(&config_path).await?;
 
     // 根据 keys 设置配置项
@@ -65,17 +76,22 @@ pub async fn mh_set_self_config(
 }
 
 /// 删除插件自身配置
+#[permission_macro::permission(cap = "plugin.self")]
 #[tauri::command]
 pub async fn mh_delete_self_config(window: Window, keys: Vec<String>) -> Result<(), String> {
     let window_id = window.label();
     let config_path = get_self_config_path(window_id)?;
+    let lock = get_config_lock(&config_path);
+    let _guard = lock.lock().await;
 
     if keys.is_empty() {
-        // 删除整个配置文件
-        if config_path.exists() {
-            tokio_fs::remove_file(&config_path)
-                .await
-                .map_err(|e| e.to_string())?;
+        // 删除整个配置文件及其备份
+        for path in [config_path.clone(), backup_path(&config_path)] {
+            if path.exists() {
+                tokio_fs::remove_file(&path)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
         }
         return Ok(());
     }
@@ -119,7 +135,7 @@ fn get_self_config_path(window_id: &str) -> Result<PathBuf, String> {
         .map_err(|e| e.to_string())?;
 
     fs::create_dir_all(&target_dir).map_err(|e| e.to_string())?;
-    let config_path = target_dir.join("selfConfig.json");
+    let config_path = target_dir.join(SELF_CONFIG_FILE);
 
     // 更新缓存
     {
@@ -130,47 +146,33 @@ fn get_self_config_path(window_id: &str) -> Result<PathBuf, String> {
     Ok(config_path)
 }
 
-// 异步读取配置文件
-async fn read_config_async(path: &PathBuf) -> Result<Value, String> {
-    if !path.exists() {
-        return Ok(json!({}));
+// 获取配置文件对应的锁
+fn get_config_lock(config_path: &PathBuf) -> Arc<AsyncMutex<()>> {
+    if let Some(lock) = CONFIG_LOCKS.read().get(config_path) {
+        return lock.clone();
     }
 
-    // 使用tokio异步读取文件
-    let mut file = tokio_fs::File::open(path)
-        .await
-        .map_err(|e| e.to_string())?;
+    CONFIG_LOCKS
+        .write()
+        .entry(config_path.clone())
+        .or_default()
+        .clone()
+}
 
-    let mut buffer = Vec::with_capacity(4096);
-    file.read_to_end(&mut buffer)
+// 异步读取配置文件，文件损坏时自动从备份恢复
+async fn read_config_async(path: &PathBuf) -> Result<Value, String> {
+    let path = path.clone();
+    tauri::async_runtime::spawn_blocking(move || read_json_or_recover::<Value>(&path))
         .await
-        .map_err(|e| e.to_string())?;
-
-    // 使用simd-json加速解析
-    match simd_json::serde::from_slice::<Value>(&mut buffer) {
-        Ok(data) => Ok(data),
-        Err(_) => {
-            // 回退到标准serde_json
-            serde_json::from_slice(&buffer).map_err(|e| e.to_string())
-        }
-    }
+        .map_err(|e| e.to_string())?
+        .map(|config| config.unwrap_or_else(|| json!({})))
 }
 
-// 异步写入配置文件
+// 异步原子写入配置文件
 async fn write_config_async(path: &PathBuf, config: &Value) -> Result<(), String> {
-    // 使用serde_json序列化，因为simd-json主要优化解析而非序列化
-    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-
-    // 使用tokio异步写入文件
-    let mut file = tokio_fs::File::create(path)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    file.write_all(content.as_bytes())
+    let path = path.clone();
+    let config = config.clone();
+    tauri::async_runtime::spawn_blocking(move || write_json_atomic(&path, &config, true))
         .await
-        .map_err(|e| e.to_string())?;
-
-    file.flush().await.map_err(|e| e.to_string())?;
-
-    Ok(())
+        .map_err(|e| e.to_string())?
 }
@@ -0,0 +1,122 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::core::hotkey::HotkeyManager;
+use crate::mh_plugin::bus::PluginBus;
+use crate::mh_plugin::lifecycle::PluginLifecycleService;
+use crate::services::database::{
+    query_enabled_plugin_ids, query_plugin_enabled, query_plugin_invalid, update_plugin_enabled,
+};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::response::ApiResponse;
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+/// 插件启用或禁用后通知所有窗口
+pub const PLUGIN_ENABLED_EVENT: &str = "plugin:enabled";
+/// 插件快捷键触发后发送到插件窗口，载荷为清单中的快捷键标识
+pub const PLUGIN_HOTKEY_EVENT: &str = "plugin:hotkey";
+/// 插件快捷键的动作前缀，完整格式为 `plugin:<windowId>:<hotkeyId>`
+pub const PLUGIN_HOTKEY_PREFIX: &str = "plugin:";
+
+/// 插件启用状态变更事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginEnabledEvent {
+    pub window_id: String,
+    pub enabled: bool,
+}
+
+pub struct PluginStateService;
+
+impl PluginStateService {
+    /// 插件是否已被禁用，内置窗口和未安装的插件返回 false
+    pub fn is_disabled(window_id: &str) -> bool {
+        matches!(query_plugin_enabled(window_id), Ok(Some(false)))
+    }
+
+    /// 插件清单无效的原因，同步插件时发现清单无效的插件保留记录但不能打开窗口
+    pub fn invalid_reason(window_id: &str) -> Option<String> {
+        query_plugin_invalid(window_id).ok().flatten()
+    }
+
+    /// 启用或禁用插件，插件文件和配置保持不变
+    ///
+    /// 禁用时结束正在执行的生命周期钩子、移除消息总线上的订阅和调用并关闭已打开的插件窗口
+    pub fn set_enabled(window_id: &str, enabled: bool) -> AppResult<()> {
+        if !update_plugin_enabled(window_id, enabled)? {
+            return Err(AppError::Error(format!("插件 {} 不存在", window_id)));
+        }
+
+        if !enabled {
+            PluginLifecycleService::interrupt(window_id);
+            PluginBus::remove_plugin(window_id);
+            if let Some(window) = AppHandleManager::get()
+                .and_then(|app_handle| app_handle.get_webview_window(window_id))
+            {
+                let _ = window.close();
+            }
+        }
+        Self::refresh_hotkeys()?;
+
+        if let Some(app_handle) = AppHandleManager::get() {
+            let _ = app_handle.emit(
+                PLUGIN_ENABLED_EVENT,
+                PluginEnabledEvent {
+                    window_id: window_id.to_string(),
+                    enabled,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// 按已启用插件的清单重新注册插件快捷键
+    pub fn refresh_hotkeys() -> AppResult<()> {
+        let mut window_ids: Vec<String> = query_enabled_plugin_ids()?.into_iter().collect();
+        window_ids.sort();
+
+        let hotkeys = window_ids
+            .iter()
+            .filter_map(|window_id| PluginLifecycleService::installed_manifest(window_id))
+            .flat_map(|manifest| {
+                manifest.hotkeys.into_iter().map(move |hotkey| {
+                    (
+                        format!(
+                            "{}{}:{}",
+                            PLUGIN_HOTKEY_PREFIX, manifest.window_id, hotkey.id
+                        ),
+                        hotkey.key,
+                    )
+                })
+            })
+            .collect();
+
+        HotkeyManager::global().set_plugin_hotkeys(hotkeys);
+        Ok(())
+    }
+
+    /// 将触发的插件快捷键发送到插件窗口，插件已禁用或窗口未打开时忽略
+    pub fn trigger_hotkey(action: &str) {
+        let Some((window_id, hotkey_id)) = action
+            .strip_prefix(PLUGIN_HOTKEY_PREFIX)
+            .and_then(|action| action.split_once(':'))
+        else {
+            return;
+        };
+        if Self::is_disabled(window_id) {
+            return;
+        }
+        if let Some(app_handle) = AppHandleManager::get() {
+            if app_handle.get_webview_window(window_id).is_some() {
+                let _ = app_handle.emit_to(window_id, PLUGIN_HOTKEY_EVENT, hotkey_id);
+            }
+        }
+    }
+}
+
+/// 启用或禁用插件
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub fn mh_plugin_set_enabled(window_id: &str, enabled: bool) -> Result<ApiResponse<()>, AppError> {
+    PluginStateService::set_enabled(window_id, enabled)?;
+    Ok(ApiResponse::success(()))
+}
@@ -0,0 +1,452 @@
+use crate::services::database::{execute_write, execute_write_async, get_db_pool};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 插件键值存储的默认配额，单位为字节
+pub const DEFAULT_STORAGE_QUOTA: u64 = 10 * 1024 * 1024;
+/// 键的最大长度，单位为字节
+const MAX_KEY_LENGTH: usize = 512;
+
+/// 存储的值，JSON 值或 Base64 编码的二进制数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum StorageValue {
+    Json(Value),
+    Binary(String),
+}
+
+/// 存储项的元数据
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageEntry {
+    pub key: String,
+    /// 键和值的字节数之和
+    pub size: u64,
+    pub binary: bool,
+    pub updated_at: String,
+}
+
+/// 插件存储用量
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    pub window_id: String,
+    pub keys: u64,
+    pub used: u64,
+    pub quota: u64,
+    /// 插件记录已不存在，存储项是残留数据，插件重新同步后恢复关联
+    pub orphaned: bool,
+}
+
+/// 插件键值存储服务
+///
+/// 数据按插件 windowId 隔离，每个插件的存储用量不能超过配额，
+/// 配额记录在 plugin_config.info.storageQuota 中，未设置时使用默认配额
+pub struct PluginStorageService;
+
+impl PluginStorageService {
+    /// 读取多个键的值，不存在的键不会出现在结果中
+    pub fn get(window_id: &str, keys: &[String]) -> AppResult<HashMap<String, StorageValue>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        ensure_plugin(&conn, window_id)?;
+
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT value, binary FROM plugin_storage WHERE window_id = ?1 AND key = ?2",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let mut values = HashMap::new();
+        for key in keys {
+            let row: Option<(Vec<u8>, bool)> = stmt
+                .query_row(params![window_id, key], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .optional()
+                .map_err(|e| AppError::Error(format!("读取存储项失败: {}", e)))?;
+            let Some((value, binary)) = row else {
+                continue;
+            };
+            let value = if binary {
+                StorageValue::Binary(STANDARD.encode(value))
+            } else {
+                StorageValue::Json(
+                    serde_json::from_slice(&value)
+                        .map_err(|e| AppError::Error(format!("解析存储项 {} 失败: {}", key, e)))?,
+                )
+            };
+            values.insert(key.clone(), value);
+        }
+        Ok(values)
+    }
+
+    /// 批量写入，所有键值在同一事务中写入，写入后的用量超过配额时全部不生效
+    ///
+    /// 用量超过配额（例如配额被调低）时，仍允许不增加用量的写入
+    pub async fn set(window_id: &str, entries: HashMap<String, StorageValue>) -> AppResult<()> {
+        let mut rows = Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            validate_key(&key)?;
+            let (bytes, binary) = match value {
+                StorageValue::Json(value) => (
+                    serde_json::to_vec(&value).map_err(|e| {
+                        AppError::Error(format!("序列化存储项 {} 失败: {}", key, e))
+                    })?,
+                    false,
+                ),
+                StorageValue::Binary(value) => (
+                    STANDARD.decode(value).map_err(|e| {
+                        AppError::Error(format!("存储项 {} 不是有效的 Base64: {}", key, e))
+                    })?,
+                    true,
+                ),
+            };
+            rows.push((key, bytes, binary));
+        }
+
+        let window_id = window_id.to_string();
+        execute_write_async(move |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+            let quota = ensure_plugin(&tx, &window_id)?;
+            let before = used_size(&tx, &window_id)?;
+            let updated_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+            for (key, value, binary) in &rows {
+                let size = (key.len() + value.len()) as u64;
+                tx.execute(
+                    "INSERT INTO plugin_storage (window_id, key, value, binary, size, updated_at) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+                     ON CONFLICT(window_id, key) DO UPDATE SET value = excluded.value, \
+                     binary = excluded.binary, size = excluded.size, updated_at = excluded.updated_at",
+                    params![window_id, key, value, binary, size, updated_at],
+                )
+                .map_err(|e| AppError::Error(format!("写入存储项失败: {}", e)))?;
+            }
+
+            let after = used_size(&tx, &window_id)?;
+            if after > quota && after > before {
+                return Err(AppError::Error(format!(
+                    "存储空间不足: 写入后将使用 {} 字节，配额为 {} 字节",
+                    after, quota
+                )));
+            }
+            tx.commit()
+                .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+        })
+        .await
+    }
+
+    /// 批量删除，返回实际删除的键数量
+    pub async fn delete(window_id: &str, keys: Vec<String>) -> AppResult<usize> {
+        let window_id = window_id.to_string();
+        execute_write_async(move |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+            ensure_plugin(&tx, &window_id)?;
+
+            let mut deleted = 0;
+            for key in &keys {
+                deleted += tx
+                    .execute(
+                        "DELETE FROM plugin_storage WHERE window_id = ?1 AND key = ?2",
+                        params![window_id, key],
+                    )
+                    .map_err(|e| AppError::Error(format!("删除存储项失败: {}", e)))?;
+            }
+            tx.commit()
+                .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
+            Ok(deleted)
+        })
+        .await
+    }
+
+    /// 删除插件的全部存储项，只在卸载插件时调用，返回删除的键数量
+    ///
+    /// 插件记录被删除（例如同步时插件目录暂时缺失）不会清理存储
+    pub async fn clear(window_id: &str) -> AppResult<usize> {
+        let window_id = window_id.to_string();
+        execute_write_async(move |conn| {
+            conn.execute(
+                "DELETE FROM plugin_storage WHERE window_id = ?1",
+                [&window_id],
+            )
+            .map_err(|e| AppError::Error(format!("清理插件存储失败: {}", e)))
+        })
+        .await
+    }
+
+    /// 删除插件记录已不存在的存储项，`window_id` 为 None 时清理所有残留数据，返回删除的键数量
+    ///
+    /// 插件记录仍存在时不会删除，已安装插件的存储只能通过卸载清理
+    pub async fn clear_orphaned(window_id: Option<&str>) -> AppResult<usize> {
+        let window_id = window_id.map(str::to_string);
+        execute_write_async(move |conn| {
+            conn.execute(
+                "DELETE FROM plugin_storage WHERE (?1 IS NULL OR window_id = ?1) \
+                 AND window_id NOT IN (SELECT window_id FROM plugin_config)",
+                params![window_id],
+            )
+            .map_err(|e| AppError::Error(format!("清理残留存储失败: {}", e)))
+        })
+        .await
+    }
+
+    /// 列出以 `prefix` 开头的键，按键排序
+    pub fn list(window_id: &str, prefix: &str) -> AppResult<Vec<StorageEntry>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        ensure_plugin(&conn, window_id)?;
+
+        // 不使用 LIKE，避免前缀中的 % 和 _ 被当作通配符
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT key, size, binary, updated_at FROM plugin_storage \
+                 WHERE window_id = ?1 AND substr(key, 1, length(?2)) = ?2 ORDER BY key",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let entries = stmt
+            .query_map(params![window_id, prefix], |row| {
+                Ok(StorageEntry {
+                    key: row.get(0)?,
+                    size: row.get(1)?,
+                    binary: row.get(2)?,
+                    updated_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Error(format!("读取存储项失败: {}", e)))?;
+        Ok(entries)
+    }
+
+    /// 查询插件的存储用量，`window_id` 为 None 时返回所有插件
+    ///
+    /// 插件记录不存在但仍有存储项的窗口同样列出，标记为残留数据
+    pub fn usage(window_id: Option<&str>) -> AppResult<Vec<StorageUsage>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT w.window_id, COUNT(s.key), COALESCE(SUM(s.size), 0), \
+                 CASE WHEN json_valid(p.info) THEN json_extract(p.info, '$.storageQuota') END, \
+                 p.window_id IS NULL \
+                 FROM (SELECT window_id FROM plugin_config UNION SELECT window_id FROM plugin_storage) w \
+                 LEFT JOIN plugin_config p ON p.window_id = w.window_id \
+                 LEFT JOIN plugin_storage s ON s.window_id = w.window_id \
+                 WHERE ?1 IS NULL OR w.window_id = ?1 \
+                 GROUP BY w.window_id ORDER BY w.window_id",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let usage = stmt
+            .query_map(params![window_id], |row| {
+                Ok(StorageUsage {
+                    window_id: row.get(0)?,
+                    keys: row.get(1)?,
+                    used: row.get(2)?,
+                    quota: row
+                        .get::<_, Option<u64>>(3)
+                        .unwrap_or(None)
+                        .unwrap_or(DEFAULT_STORAGE_QUOTA),
+                    orphaned: row.get(4)?,
+                })
+            })
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Error(format!("读取存储用量失败: {}", e)))?;
+
+        if let Some(window_id) = window_id {
+            if usage.is_empty() {
+                return Err(AppError::Error(format!("插件 {} 不存在", window_id)));
+            }
+        }
+        Ok(usage)
+    }
+
+    /// 设置插件的存储配额，`quota` 为 None 时恢复默认配额
+    ///
+    /// 调低配额不会删除已有数据，用量超过配额后插件只能写入不增加用量的数据
+    pub fn set_quota(window_id: &str, quota: Option<u64>) -> AppResult<()> {
+        if quota == Some(0) || quota.is_some_and(|quota| quota > i64::MAX as u64) {
+            return Err(AppError::Error("存储配额无效".into()));
+        }
+
+        let window_id = window_id.to_string();
+        execute_write(move |conn| {
+            let updated = match quota {
+                Some(quota) => conn.execute(
+                    "UPDATE plugin_config SET info = json_set(CASE WHEN json_valid(info) THEN info ELSE '{}' END, \
+                     '$.storageQuota', ?2) WHERE window_id = ?1",
+                    params![window_id, quota],
+                ),
+                None => conn.execute(
+                    "UPDATE plugin_config SET info = json_remove(CASE WHEN json_valid(info) THEN info ELSE '{}' END, \
+                     '$.storageQuota') WHERE window_id = ?1",
+                    params![window_id],
+                ),
+            }
+            .map_err(|e| AppError::Error(format!("保存存储配额失败: {}", e)))?;
+            if updated == 0 {
+                return Err(AppError::Error(format!("插件 {} 不存在", window_id)));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// 确认窗口是已安装的插件，返回插件的存储配额
+fn ensure_plugin(conn: &Connection, window_id: &str) -> AppResult<u64> {
+    let quota: Option<Option<u64>> = conn
+        .query_row(
+            "SELECT CASE WHEN json_valid(info) THEN json_extract(info, '$.storageQuota') END \
+             FROM plugin_config WHERE window_id = ?1",
+            [window_id],
+            |row| Ok(row.get::<_, Option<u64>>(0).unwrap_or(None)),
+        )
+        .optional()
+        .map_err(|e| AppError::Error(format!("读取插件配置失败: {}", e)))?;
+    quota
+        .map(|quota| quota.unwrap_or(DEFAULT_STORAGE_QUOTA))
+        .ok_or_else(|| AppError::Error(format!("窗口 {} 不是已安装的插件", window_id)))
+}
+
+fn used_size(conn: &Connection, window_id: &str) -> AppResult<u64> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(size), 0) FROM plugin_storage WHERE window_id = ?1",
+        [window_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| AppError::Error(format!("读取存储用量失败: {}", e)))
+}
+
+fn validate_key(key: &str) -> AppResult<()> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(AppError::Error(format!(
+            "存储键长度必须在 1 到 {} 字节之间",
+            MAX_KEY_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// 读取当前插件存储的值
+#[permission_macro::permission(cap = "plugin.self")]
+#[tauri::command]
+pub fn mh_storage_get(
+    window: tauri::Window,
+    keys: Vec<String>,
+) -> Result<ApiResponse<HashMap<String, StorageValue>>, AppError> {
+    match PluginStorageService::get(window.label(), &keys) {
+        Ok(values) => Ok(ApiResponse::success(values)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 批量写入当前插件存储，任一项失败或超出配额时全部不生效
+#[permission_macro::permission(cap = "plugin.self")]
+#[tauri::command]
+pub async fn mh_storage_set(
+    window: tauri::Window,
+    entries: HashMap<String, StorageValue>,
+) -> Result<ApiResponse<()>, AppError> {
+    match PluginStorageService::set(window.label(), entries).await {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 批量删除当前插件存储的键，返回实际删除的数量
+#[permission_macro::permission(cap = "plugin.self")]
+#[tauri::command]
+pub async fn mh_storage_delete(
+    window: tauri::Window,
+    keys: Vec<String>,
+) -> Result<ApiResponse<usize>, AppError> {
+    match PluginStorageService::delete(window.label(), keys).await {
+        Ok(deleted) => Ok(ApiResponse::success(deleted)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 列出当前插件存储中以 `prefix` 开头的键
+#[permission_macro::permission(cap = "plugin.self")]
+#[tauri::command]
+pub fn mh_storage_list(
+    window: tauri::Window,
+    prefix: Option<String>,
+) -> Result<ApiResponse<Vec<StorageEntry>>, AppError> {
+    match PluginStorageService::list(window.label(), prefix.as_deref().unwrap_or_default()) {
+        Ok(entries) => Ok(ApiResponse::success(entries)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 获取插件的存储用量，`window_id` 为空时返回所有插件
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub fn mh_plugin_storage_usage(
+    window_id: Option<String>,
+) -> Result<ApiResponse<Vec<StorageUsage>>, AppError> {
+    match PluginStorageService::usage(window_id.as_deref()) {
+        Ok(usage) => Ok(ApiResponse::success(usage)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 设置插件的存储配额（字节），`quota` 为空时恢复默认配额
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub fn mh_plugin_set_storage_quota(
+    window_id: String,
+    quota: Option<u64>,
+) -> Result<ApiResponse<()>, AppError> {
+    match PluginStorageService::set_quota(&window_id, quota) {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrParamsInvalid,
+            e.to_string(),
+        )),
+    }
+}
+
+/// 清理插件记录已不存在的存储数据，`window_id` 为空时清理所有残留数据，返回删除的键数量
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub async fn mh_plugin_storage_clear_orphaned(
+    window_id: Option<String>,
+) -> Result<ApiResponse<usize>, AppError> {
+    match PluginStorageService::clear_orphaned(window_id.as_deref()).await {
+        Ok(deleted) => Ok(ApiResponse::success(deleted)),
+        Err(e) => Ok(ApiResponse::error(
+            ApiStatusCode::ErrDatabase,
+            e.to_string(),
+        )),
+    }
+}
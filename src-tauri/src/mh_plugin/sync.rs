@@ -1,6 +1,9 @@
 use crate::services::database::{
-    batch_insert_plugin_configs, batch_remove_plugin_configs, query_plugin_ids,
+    batch_insert_plugin_configs, batch_remove_plugin_configs, batch_update_plugin_invalid,
+    query_dev_plugin_ids, query_plugin_ids,
 };
+use crate::mh_plugin::dependency::{PluginDependencyService, UnresolvedDependency};
+use crate::mh_plugin::manifest::{PluginManifest, MANIFEST_FILE};
 use crate::utils::error::{AppError, AppResult};
 use crate::services::logger::{LogEntry, Logger};
 use crate::utils::path::get_myhelper_path;
@@ -13,22 +16,34 @@ use tokio::fs as tokio_fs;
 use tokio::io::AsyncReadExt;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
-use tokio::try_join;
 
 // 批处理大小
 const BATCH_SIZE: usize = 100;
 // 并发限制 - 增加到32以优化I/O密集型任务
 const MAX_CONCURRENT: usize = 32;
 
+/// 单个插件目录的检查结果
+pub(crate) enum PluginScan {
+    /// 不是插件目录
+    Missing,
+    /// 插件清单或入口文件无效，附带原因
+    Invalid(String),
+    /// 有效的插件，包含 (info, config, data)
+    Valid(Value, Value, Value),
+}
+
 /// 高性能同步插件目录和配置
 ///
 /// 同步逻辑：
 /// 1. 检查记录与本地文件是否对应
-/// 2. 如果对应，保持不变
+/// 2. 如果对应，保持不变，包括插件的启用状态
 /// 3. 如果不对应：
 ///    - 如果文件不存在，删除数据库记录
 ///    - 如果记录不存在，且文件符合校验规则，插入新记录
-pub async fn sync_plugins() -> AppResult<()> {
+/// 4. 已有记录的插件清单无效时保留记录，在 info.invalid 中记录原因，清单恢复有效后清除
+/// 5. 开发模式插件不在插件目录中，记录保持不变
+/// 6. 同步完成后检查插件依赖，返回未安装或版本不满足的依赖
+pub async fn sync_plugins() -> AppResult<Vec<UnresolvedDependency>> {
     // 获取插件根目录
     let plugin_root = get_myhelper_path()
         .map(|path| path.join("Plugin"))
@@ -39,15 +54,19 @@ pub async fn sync_plugins() -> AppResult<()> {
         tokio_fs::create_dir_all(&plugin_root)
             .await
             .map_err(|e| AppError::from(format!("创建插件目录失败: {}", e)))?;
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     // 一次性读取所有插件记录到内存
     let existing_plugins: HashSet<String> = query_plugin_ids()?;
-    let mut processed_plugins = HashSet::new();
+    // 开发模式插件的文件不在插件目录中，保留其记录
+    let dev_plugins: HashSet<String> = query_dev_plugin_ids()?;
+    let mut processed_plugins = dev_plugins.clone();
 
     // 收集需要插入的插件
     let mut to_insert = Vec::with_capacity(BATCH_SIZE);
+    // 收集清单无效的已有插件及原因
+    let mut invalid_plugins = Vec::new();
 
     // 读取所有目录条目
     let mut entries = Vec::new();
@@ -95,6 +114,16 @@ pub async fn sync_plugins() -> AppResult<()> {
             }
         };
 
+        // 开发模式插件的目录只保存插件的自身配置
+        if dev_plugins.contains(&window_id) {
+            continue;
+        }
+
+        // 目录存在的插件先视为已处理，检查失败时保留已有记录
+        if existing_plugins.contains(&window_id) {
+            processed_plugins.insert(window_id.clone());
+        }
+
         let plugin_dir = entry.path();
 
         // 添加到任务集合
@@ -103,45 +132,64 @@ pub async fn sync_plugins() -> AppResult<()> {
             let _permit = semaphore.acquire().await.unwrap();
 
             // 处理插件
-            process_plugin(&plugin_dir, &window_id, &existing).await
+            process_plugin(&plugin_dir, &window_id, &existing)
+                .await
+                .map(|scan| (window_id, scan))
         });
     }
 
     // 处理任务结果
     while let Some(result) = tasks.join_next().await {
-        if let Ok(Ok(Some((window_id, info, config, data)))) = result {
-            if !existing_plugins.contains(&window_id) {
-                // 批量序列化以减少冗余操作
-                let (info_str, config_str, data_str) = tokio::task::spawn_blocking(move || {
-                    (
-                        serde_json::to_string(&info).unwrap_or_default(),
-                        serde_json::to_string(&config).unwrap_or_default(),
-                        serde_json::to_string(&data).unwrap_or_default(),
-                    )
-                })
-                .await
-                .map_err(|e| AppError::Error(format!("序列化任务失败: {}", e)))?;
-
-                // 检查序列化结果
-                if !info_str.is_empty() && !config_str.is_empty() {
-                    to_insert.push((window_id.clone(), info_str, config_str, data_str));
-
-                    // 批量处理插入操作
-                    if to_insert.len() >= BATCH_SIZE {
-                        batch_insert_plugin_configs(&to_insert)?;
-                        to_insert.clear();
+        let Ok(Ok((window_id, scan))) = result else {
+            continue;
+        };
+        match scan {
+            PluginScan::Missing => {
+                processed_plugins.remove(&window_id);
+            }
+            PluginScan::Invalid(reason) => {
+                // 清单无效不代表插件已被删除，保留记录和插件数据
+                if existing_plugins.contains(&window_id) {
+                    invalid_plugins.push((window_id, reason));
+                }
+            }
+            PluginScan::Valid(info, config, data) => {
+                if !existing_plugins.contains(&window_id) {
+                    // 批量序列化以减少冗余操作
+                    let (info_str, config_str, data_str) = tokio::task::spawn_blocking(move || {
+                        (
+                            serde_json::to_string(&info).unwrap_or_default(),
+                            serde_json::to_string(&config).unwrap_or_default(),
+                            serde_json::to_string(&data).unwrap_or_default(),
+                        )
+                    })
+                    .await
+                    .map_err(|e| AppError::Error(format!("序列化任务失败: {}", e)))?;
+
+                    // 检查序列化结果
+                    if !info_str.is_empty() && !config_str.is_empty() {
+                        to_insert.push((window_id.clone(), info_str, config_str, data_str));
+
+                        // 批量处理插入操作
+                        if to_insert.len() >= BATCH_SIZE {
+                            batch_insert_plugin_configs(&to_insert).await?;
+                            to_insert.clear();
+                        }
                     }
                 }
+                processed_plugins.insert(window_id);
             }
-            processed_plugins.insert(window_id);
         }
     }
 
     // 处理剩余的插入操作
     if !to_insert.is_empty() {
-        batch_insert_plugin_configs(&to_insert)?;
+        batch_insert_plugin_configs(&to_insert).await?;
     }
 
+    // 标记清单无效的插件，并清除已恢复插件的标记
+    batch_update_plugin_invalid(&invalid_plugins).await?;
+
     // 批量删除不存在的插件记录
     let to_delete: Vec<String> = existing_plugins
         .difference(&processed_plugins)
@@ -158,105 +206,88 @@ pub async fn sync_plugins() -> AppResult<()> {
             })?;
         }
 
-        batch_remove_plugin_configs(&to_delete)?;
+        batch_remove_plugin_configs(&to_delete).await?;
     }
 
     Logger::write_log(LogEntry {
         level: "info".to_string(),
         message: format!(
-            "插件配置同步完成，共发现 {} 个有效插件",
-            processed_plugins.len()
+            "插件配置同步完成，共发现 {} 个插件，其中 {} 个清单无效",
+            processed_plugins.len(),
+            invalid_plugins.len()
         ),
         timestamp: String::new(),
         details: None,
     })?;
 
-    Ok(())
+    PluginDependencyService::report_unresolved()
 }
 
 /// 处理单个插件目录
-async fn process_plugin(
+pub(crate) async fn process_plugin(
     plugin_dir: &PathBuf,
     window_id: &str,
     existing_plugins: &HashSet<String>,
-) -> AppResult<Option<(String, Value, Value, Value)>> {
-    // 检查是否为目录和必要文件
-    let (dir_meta, index_exists) = try_join!(
-        tokio_fs::metadata(plugin_dir),
-        tokio_fs::metadata(plugin_dir.join("index.html"))
-    )
-    .map_err(|_e| {
-        let _ = Logger::write_log(LogEntry {
-            level: "warn".to_string(),
-            message: format!("插件目录 {} 缺少必要文件或不是目录", window_id),
-            timestamp: String::new(),
-            details: None,
-        });
-        AppError::Error("插件目录无效".into())
-    })?;
-
-    if !dir_meta.is_dir() || !index_exists.is_file() {
-        return Ok(None);
+) -> AppResult<PluginScan> {
+    // 检查是否为目录
+    match tokio_fs::metadata(plugin_dir).await {
+        Ok(meta) if meta.is_dir() => {}
+        _ => return Ok(PluginScan::Missing),
     }
 
     // 读取主配置文件 - 使用异步I/O优化
-    let config_path = plugin_dir.join("mhPlugin.json");
-    let mut config_buffer = async {
-        // 直接使用异步文件I/O代替spawn_blocking包装的同步I/O
-        let mut file = match tokio_fs::File::open(&config_path).await {
-            Ok(file) => file,
-            Err(_) => return Vec::new(),
-        };
-
-        let mut buffer = Vec::with_capacity(4096); // 预分配4KB
-        match file.read_to_end(&mut buffer).await {
-            Ok(_) => buffer,
-            Err(_) => Vec::new(),
+    let config_path = plugin_dir.join(MANIFEST_FILE);
+    let config_buffer = match tokio_fs::read(&config_path).await {
+        Ok(buffer) => buffer,
+        Err(_) => {
+            Logger::write_log(LogEntry {
+                level: "warn".to_string(),
+                message: format!("插件目录 {} 缺少 {}", window_id, MANIFEST_FILE),
+                timestamp: String::new(),
+                details: None,
+            })?;
+            return Ok(PluginScan::Invalid(format!("缺少 {}", MANIFEST_FILE)));
         }
-    }
-    .await;
-
-    // 使用simd-json解析主配置文件
-    let mut data: Value = if !config_buffer.is_empty() {
-        match simd_json::serde::from_slice::<serde_json::Value>(&mut config_buffer) {
-            Ok(config) => config,
-            Err(_) => {
-                // 如果SIMD解析失败，回退到标准serde_json
-                match serde_json::from_slice::<Value>(&config_buffer) {
-                    Ok(config) => config,
-                    Err(e) => {
-                        Logger::write_log(LogEntry {
-                            level: "warn".to_string(),
-                            message: format!("解析配置失败: {}", window_id),
-                            timestamp: String::new(),
-                            details: Some(e.to_string()),
-                        })?;
-                        return Ok(None);
-                    }
-                }
-            }
+    };
+
+    // 解析并校验插件清单，windowId 必须与目录名一致
+    let manifest = match PluginManifest::parse(&config_buffer, Some(window_id)) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            Logger::write_log(LogEntry {
+                level: "warn".to_string(),
+                message: format!("插件清单无效: {}", window_id),
+                timestamp: String::new(),
+                details: Some(e.to_string()),
+            })?;
+            return Ok(PluginScan::Invalid(e.to_string()));
         }
-    } else {
-        return Ok(None);
     };
 
-    // 验证 windowId
-    if data.get("windowId").and_then(|v| v.as_str()) != Some(window_id) {
+    // 检查入口文件
+    let entry_path = plugin_dir.join(&manifest.entry);
+    if !tokio_fs::metadata(&entry_path)
+        .await
+        .map(|meta| meta.is_file())
+        .unwrap_or(false)
+    {
         Logger::write_log(LogEntry {
             level: "warn".to_string(),
-            message: format!("插件配置的 windowId 无效或不匹配: {}", window_id),
+            message: format!("插件目录 {} 缺少入口文件 {}", window_id, manifest.entry),
             timestamp: String::new(),
             details: None,
         })?;
-        return Ok(None);
+        return Ok(PluginScan::Invalid(format!(
+            "缺少入口文件 {}",
+            manifest.entry
+        )));
     }
 
+    let mut data = serde_json::to_value(&manifest)
+        .map_err(|e| AppError::Error(format!("序列化插件清单失败: {}", e)))?;
+
     // 添加 url 字段
-    let plugin_path = get_myhelper_path()?
-        .join("Plugin")
-        .join(window_id)
-        .join("index.html");
-    if let Some(url) = plugin_path.to_str() {
+    if let Some(url) = entry_path.to_str() {
         if let Some(data_obj) = data.as_object_mut() {
             data_obj.insert("url".to_string(), json!(url.replace('\\', "/")));
         }
@@ -317,5 +348,5 @@ async fn process_plugin(
         (json!({}), json!({}))
     };
 
-    Ok(Some((window_id.to_string(), info, config_value, data)))
+    Ok(PluginScan::Valid(info, config_value, data))
 }
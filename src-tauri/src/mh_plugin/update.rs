@@ -0,0 +1,304 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::mh_plugin::install::install_from_market;
+use crate::mh_plugin::manifest::host_version;
+use crate::services::database::get_db_pool;
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::reqwest::create_web_client;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use rusqlite::OptionalExtension;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// 发现插件新版本时通知所有窗口
+pub const PLUGIN_UPDATES_EVENT: &str = "plugin:updates-available";
+
+/// 插件更新配置在 config 表中的键
+const PLUGIN_UPDATE_CONFIG_KEY: &str = "pluginUpdateConfig";
+/// 插件市场索引地址，可在插件更新配置中覆盖
+#[cfg(debug_assertions)]
+const DEFAULT_INDEX_URL: &str = "http://localhost:8081/api/plugin/index";
+#[cfg(not(debug_assertions))]
+const DEFAULT_INDEX_URL: &str = "https://myhelper.ialtone.xyz/api/plugin/index";
+/// 启动后首次检查前的等待时间，避免与插件同步争用
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+/// 定时任务的检查间隔
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const INDEX_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 插件更新配置，存储在 config 表的 `pluginUpdateConfig` 键中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PluginUpdateConfig {
+    /// 是否定期检查更新
+    pub auto_check: bool,
+    /// 检查间隔（小时）
+    pub interval_hours: u64,
+    /// 插件索引地址，为空时使用插件市场的地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_url: Option<String>,
+}
+
+impl Default for PluginUpdateConfig {
+    fn default() -> Self {
+        Self {
+            auto_check: true,
+            interval_hours: 6,
+            index_url: None,
+        }
+    }
+}
+
+/// 插件市场索引
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginIndex {
+    pub plugins: Vec<PluginIndexEntry>,
+}
+
+/// 插件索引中的单个插件的最新版本
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginIndexEntry {
+    pub window_id: String,
+    pub version: Version,
+    /// 插件包下载地址
+    pub url: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub min_host_version: Option<Version>,
+}
+
+/// 可更新的插件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginUpdate {
+    pub window_id: String,
+    pub current_version: String,
+    pub latest_version: String,
+    pub url: String,
+    pub sha256: String,
+    pub signature: Option<String>,
+}
+
+/// 单个插件的更新结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginUpdateResult {
+    pub window_id: String,
+    pub version: String,
+    /// 更新失败的原因，成功时为 None
+    pub error: Option<String>,
+}
+
+pub struct PluginUpdateService;
+
+impl PluginUpdateService {
+    /// 对比插件索引和已安装的插件版本，返回可更新的插件
+    ///
+    /// 已安装版本取自 `sync_plugins` 记录的插件清单，要求更高宿主版本的插件不会列出
+    pub async fn check() -> AppResult<Vec<PluginUpdate>> {
+        let index = Self::market_index().await?;
+        let installed = Self::installed_versions()?;
+        let host_version = host_version();
+
+        let mut updates: Vec<PluginUpdate> = index
+            .plugins
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .min_host_version
+                    .as_ref()
+                    .map_or(true, |min_host_version| host_version >= *min_host_version)
+            })
+            .filter_map(|entry| {
+                let current = installed.get(&entry.window_id)?;
+                (entry.version > *current).then(|| PluginUpdate {
+                    current_version: current.to_string(),
+                    latest_version: entry.version.to_string(),
+                    window_id: entry.window_id,
+                    url: entry.url,
+                    sha256: entry.sha256,
+                    signature: entry.signature,
+                })
+            })
+            .collect();
+        updates.sort_by(|a, b| a.window_id.cmp(&b.window_id));
+        Ok(updates)
+    }
+
+    /// 通过正常的安装流程依次更新所有可更新的插件，单个插件失败不影响其余插件
+    ///
+    /// `allow_unsigned` 为 false 时未签名的更新会失败，用户确认后才能更新
+    pub async fn update_all(allow_unsigned: bool) -> AppResult<Vec<PluginUpdateResult>> {
+        let mut results = Vec::new();
+        for update in Self::check().await? {
+            let result = install_from_market(
+                &update.url,
+                &update.window_id,
+                Some(&update.sha256),
+                update.signature.as_deref(),
+                None,
+                allow_unsigned,
+            )
+            .await;
+            results.push(PluginUpdateResult {
+                window_id: update.window_id,
+                version: update.latest_version,
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+        Ok(results)
+    }
+
+    /// 启动定时检查任务，发现新版本时发送 [`PLUGIN_UPDATES_EVENT`] 事件
+    pub fn start_scheduler() {
+        tauri::async_runtime::spawn(async {
+            tokio::time::sleep(STARTUP_DELAY).await;
+            let mut last_check: Option<Instant> = None;
+            loop {
+                if let Err(e) = Self::run_scheduled(&mut last_check).await {
+                    let _ = Logger::write_log(LogEntry {
+                        level: "error".to_string(),
+                        message: "检查插件更新失败".to_string(),
+                        timestamp: String::new(),
+                        details: Some(e.to_string()),
+                    });
+                }
+                tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+            }
+        });
+    }
+
+    async fn run_scheduled(last_check: &mut Option<Instant>) -> AppResult<()> {
+        let config = Self::load_config()?;
+        if !config.auto_check || config.interval_hours == 0 {
+            return Ok(());
+        }
+        let interval = Duration::from_secs(config.interval_hours * 3600);
+        if last_check.is_some_and(|checked| checked.elapsed() < interval) {
+            return Ok(());
+        }
+        *last_check = Some(Instant::now());
+
+        let updates = Self::check().await?;
+        if !updates.is_empty() {
+            if let Some(app_handle) = AppHandleManager::get() {
+                let _ = app_handle.emit(PLUGIN_UPDATES_EVENT, &updates);
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取插件更新配置中指定的插件索引，未指定时使用插件市场的索引
+    pub async fn market_index() -> AppResult<PluginIndex> {
+        let config = Self::load_config()?;
+        let index_url = config
+            .index_url
+            .as_deref()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or(DEFAULT_INDEX_URL);
+        Self::fetch_index(index_url).await
+    }
+
+    async fn fetch_index(url: &str) -> AppResult<PluginIndex> {
+        let client = create_web_client()?;
+        let response = client
+            .get(url)
+            .timeout(INDEX_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| AppError::Error(format!("获取插件索引失败: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Error(format!(
+                "获取插件索引失败，状态码: {}",
+                response.status()
+            )));
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Error(format!("读取插件索引失败: {}", e)))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| AppError::Error(format!("解析插件索引失败: {}", e)))
+    }
+
+    /// 读取已安装插件的版本，无法识别版本的插件和开发模式插件不参与更新
+    fn installed_versions() -> AppResult<HashMap<String, Version>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT window_id, json_extract(data, '$.version') FROM plugin_config \
+                 WHERE json_valid(data) \
+                 AND NOT (json_valid(info) AND json_extract(info, '$.dev') IS NOT NULL)",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+
+        let mut versions = HashMap::new();
+        for row in rows {
+            let (window_id, version) =
+                row.map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?;
+            if let Some(version) = version.and_then(|version| Version::parse(&version).ok()) {
+                versions.insert(window_id, version);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// 读取插件更新配置，未配置时使用默认值
+    pub fn load_config() -> AppResult<PluginUpdateConfig> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM config WHERE key = ?1",
+                [PLUGIN_UPDATE_CONFIG_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Error(format!("读取插件更新配置失败: {}", e)))?;
+
+        match value {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Error(format!("解析插件更新配置失败: {}", e))),
+            None => Ok(PluginUpdateConfig::default()),
+        }
+    }
+}
+
+/// 检查插件更新
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub async fn mh_plugin_check_updates() -> Result<ApiResponse<Vec<PluginUpdate>>, AppError> {
+    match PluginUpdateService::check().await {
+        Ok(updates) => Ok(ApiResponse::success(updates)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrNetwork, e.to_string())),
+    }
+}
+
+/// 更新所有可更新的插件，返回每个插件的更新结果
+///
+/// 未签名的更新需要用户确认后传入 `allow_unsigned`
+#[permission_macro::permission(cap = "plugin.manage")]
+#[tauri::command]
+pub async fn mh_plugin_update_all(
+    allow_unsigned: Option<bool>,
+) -> Result<ApiResponse<Vec<PluginUpdateResult>>, AppError> {
+    match PluginUpdateService::update_all(allow_unsigned.unwrap_or(false)).await {
+        Ok(results) => Ok(ApiResponse::success(results)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrNetwork, e.to_string())),
+    }
+}
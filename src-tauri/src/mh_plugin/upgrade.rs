@@ -0,0 +1,272 @@
+use crate::mh_plugin::manifest::{
+    PluginManifest, MANIFEST_FILE, MAX_UNPACKED_SIZE, MAX_ZIP_ENTRIES,
+};
+use crate::mh_plugin::self_config::SELF_CONFIG_FILE;
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::atomic_file::backup_path;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+use zip::read::ZipArchive;
+
+/// 解压中的插件版本所在目录，与插件目录位于同一磁盘以保证重命名是原子操作
+const STAGING_DIR: &str = "PluginStaging";
+/// 上一个版本所在目录，用于回滚
+const PREVIOUS_DIR: &str = "PluginPrevious";
+
+/// 插件目录的布局
+struct PluginDirs {
+    live: PathBuf,
+    staging: PathBuf,
+    previous: PathBuf,
+    /// 回滚时暂存被替换的当前版本，替换成功后移入回滚目录
+    retired: PathBuf,
+}
+
+impl PluginDirs {
+    fn new(window_id: &str) -> AppResult<Self> {
+        let root = get_myhelper_path().map_err(AppError::from)?;
+        Ok(Self {
+            live: root.join("Plugin").join(window_id),
+            staging: root.join(STAGING_DIR).join(window_id),
+            previous: root.join(PREVIOUS_DIR).join(window_id),
+            retired: root
+                .join(STAGING_DIR)
+                .join(format!("{}.current", window_id)),
+        })
+    }
+}
+
+/// 分阶段安装或升级插件
+///
+/// 先解压到临时目录并校验，再把用户数据文件复制到新版本，最后替换插件目录。
+/// 已安装的版本保留到回滚目录，任何一步失败时插件目录保持原样
+pub fn install_staged<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    window_id: &str,
+) -> AppResult<PluginManifest> {
+    let dirs = PluginDirs::new(window_id)?;
+
+    remove_dir_if_exists(&dirs.staging)?;
+    fs::create_dir_all(&dirs.staging)
+        .map_err(|e| AppError::from(format!("创建临时目录失败: {}", e)))?;
+
+    let result = extract_archive(archive, &dirs.staging)
+        .and_then(|_| validate_dir(&dirs.staging, window_id))
+        .and_then(|manifest| {
+            if dirs.live.is_dir() {
+                copy_user_data(&dirs.live, &dirs.staging)?;
+            }
+            Ok(manifest)
+        });
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&dirs.staging);
+            return Err(e);
+        }
+    };
+
+    // 替换插件目录，旧版本移入回滚目录
+    ensure_parent(&dirs.live)?;
+    if dirs.live.exists() {
+        remove_dir_if_exists(&dirs.previous)?;
+        ensure_parent(&dirs.previous)?;
+        fs::rename(&dirs.live, &dirs.previous)
+            .map_err(|e| AppError::from(format!("备份旧版本失败: {}", e)))?;
+    }
+    if let Err(e) = fs::rename(&dirs.staging, &dirs.live) {
+        if dirs.previous.exists() && !dirs.live.exists() {
+            let _ = fs::rename(&dirs.previous, &dirs.live);
+        }
+        let _ = fs::remove_dir_all(&dirs.staging);
+        return Err(AppError::from(format!("替换插件目录失败: {}", e)));
+    }
+
+    Ok(manifest)
+}
+
+/// 读取并校验回滚版本的插件清单，回滚前用于检查其他插件的版本要求
+pub fn previous_manifest(window_id: &str) -> AppResult<PluginManifest> {
+    let dirs = PluginDirs::new(window_id)?;
+    if !dirs.previous.is_dir() {
+        return Err(AppError::from("没有可回滚的版本"));
+    }
+    validate_dir(&dirs.previous, window_id)
+}
+
+/// 回滚到上一个版本，当前版本移入回滚目录，可以再次回滚
+///
+/// 上一个版本先复制到临时目录，并带上当前版本的用户数据文件，替换插件目录成功后才更新回滚目录，
+/// 任何一步失败时插件目录和回滚目录保持原样
+pub fn rollback(window_id: &str) -> AppResult<PluginManifest> {
+    let dirs = PluginDirs::new(window_id)?;
+    let manifest = previous_manifest(window_id)?;
+
+    remove_dir_if_exists(&dirs.staging)?;
+    let prepared = copy_dir(&dirs.previous, &dirs.staging)
+        .and_then(|_| {
+            if dirs.live.is_dir() {
+                copy_user_data(&dirs.live, &dirs.staging)?;
+            }
+            Ok(())
+        })
+        .and_then(|_| validate_dir(&dirs.staging, window_id));
+    if let Err(e) = prepared {
+        let _ = fs::remove_dir_all(&dirs.staging);
+        return Err(e);
+    }
+
+    // 当前版本 -> 暂存目录，临时目录 -> 插件目录
+    remove_dir_if_exists(&dirs.retired)?;
+    let has_live = dirs.live.exists();
+    if has_live {
+        if let Err(e) = fs::rename(&dirs.live, &dirs.retired) {
+            let _ = fs::remove_dir_all(&dirs.staging);
+            return Err(AppError::from(format!("移动当前版本失败: {}", e)));
+        }
+    }
+    if let Err(e) = fs::rename(&dirs.staging, &dirs.live) {
+        if has_live {
+            let _ = fs::rename(&dirs.retired, &dirs.live);
+        }
+        let _ = fs::remove_dir_all(&dirs.staging);
+        return Err(AppError::from(format!("恢复上一个版本失败: {}", e)));
+    }
+
+    // 替换成功后回滚目录改为保存回滚前的版本
+    remove_dir_if_exists(&dirs.previous)?;
+    if has_live {
+        fs::rename(&dirs.retired, &dirs.previous)
+            .map_err(|e| AppError::from(format!("保存当前版本失败: {}", e)))?;
+    }
+
+    Logger::write_log(LogEntry {
+        level: "info".to_string(),
+        message: format!("插件已回滚，窗口ID: {}", window_id),
+        timestamp: String::new(),
+        details: Some(format!("版本: {}", manifest.version)),
+    })
+    .map_err(AppError::from)?;
+
+    Ok(manifest)
+}
+
+/// 删除插件的回滚版本和未完成的临时目录
+pub fn remove_versions(window_id: &str) -> AppResult<()> {
+    let dirs = PluginDirs::new(window_id)?;
+    remove_dir_if_exists(&dirs.previous)?;
+    remove_dir_if_exists(&dirs.retired)?;
+    remove_dir_if_exists(&dirs.staging)
+}
+
+/// 读取并校验目录中的插件清单和入口文件
+fn validate_dir(dir: &Path, window_id: &str) -> AppResult<PluginManifest> {
+    let bytes = fs::read(dir.join(MANIFEST_FILE))
+        .map_err(|e| AppError::from(format!("读取 {} 失败: {}", MANIFEST_FILE, e)))?;
+    let manifest = PluginManifest::parse(&bytes, Some(window_id))?;
+    if !dir.join(&manifest.entry).is_file() {
+        return Err(AppError::from(format!("缺少入口文件: {}", manifest.entry)));
+    }
+    Ok(manifest)
+}
+
+/// 把插件自身配置等用户数据复制到新版本目录，覆盖插件包中的同名文件
+fn copy_user_data(from: &Path, to: &Path) -> AppResult<()> {
+    let self_config = from.join(SELF_CONFIG_FILE);
+    for source in [backup_path(&self_config), self_config] {
+        if !source.is_file() {
+            continue;
+        }
+        let Some(file_name) = source.file_name() else {
+            continue;
+        };
+        fs::copy(&source, to.join(file_name))
+            .map_err(|e| AppError::from(format!("保留插件数据失败: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// 解压插件包到指定目录
+///
+/// 文件数和解压后的总大小超过上限时停止解压，按实际解压出的字节计数，不信任 ZIP 中记录的大小
+fn extract_archive<R: Read + Seek>(archive: &mut ZipArchive<R>, target_dir: &Path) -> AppResult<()> {
+    if archive.len() > MAX_ZIP_ENTRIES {
+        return Err(AppError::from(format!(
+            "插件包包含 {} 个文件，超过上限 {}",
+            archive.len(),
+            MAX_ZIP_ENTRIES
+        )));
+    }
+
+    let mut unpacked: u64 = 0;
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| AppError::from(format!("提取文件失败: {}", e)))?;
+        let out_path = target_dir.join(file.mangled_name());
+        if !out_path.starts_with(target_dir) {
+            return Err(AppError::from("ZIP文件包含非法路径"));
+        }
+
+        if file.is_dir() {
+            fs::create_dir_all(&out_path)
+                .map_err(|e| AppError::from(format!("创建目录失败: {}", e)))?;
+            continue;
+        }
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::from(format!("创建目录失败: {}", e)))?;
+        }
+        let mut output_file = File::create(&out_path)
+            .map_err(|e| AppError::from(format!("创建文件失败: {}", e)))?;
+        let remaining = MAX_UNPACKED_SIZE - unpacked;
+        let written = io::copy(&mut (&mut file).take(remaining + 1), &mut output_file)
+            .map_err(|e| AppError::from(format!("写入文件失败: {}", e)))?;
+        if written > remaining {
+            return Err(AppError::from(format!(
+                "插件包解压后超过大小上限 {} 字节",
+                MAX_UNPACKED_SIZE
+            )));
+        }
+        unpacked += written;
+    }
+    Ok(())
+}
+
+/// 递归复制目录，只复制普通文件和目录
+fn copy_dir(from: &Path, to: &Path) -> AppResult<()> {
+    fs::create_dir_all(to).map_err(|e| AppError::from(format!("创建目录失败: {}", e)))?;
+    let entries = fs::read_dir(from).map_err(|e| AppError::from(format!("读取目录失败: {}", e)))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| AppError::from(format!("读取目录失败: {}", e)))?;
+        let file_type = entry
+            .file_type()
+            .map_err(|e| AppError::from(format!("读取文件信息失败: {}", e)))?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), &target)
+                .map_err(|e| AppError::from(format!("复制文件失败: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+fn ensure_parent(path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::from(format!("创建目录失败: {}", e)))?;
+    }
+    Ok(())
+}
+
+fn remove_dir_if_exists(path: &Path) -> AppResult<()> {
+    if path.exists() {
+        fs::remove_dir_all(path)
+            .map_err(|e| AppError::from(format!("删除目录 {} 失败: {}", path.display(), e)))?;
+    }
+    Ok(())
+}
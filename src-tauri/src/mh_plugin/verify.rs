@@ -0,0 +1,200 @@
+use crate::utils::atomic_file::{read_json_or_recover, write_json_atomic};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use crate::utils::response::{ApiResponse, ApiStatusCode};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::Local;
+use ed25519_dalek::{Signature, VerifyingKey};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// 本地信任的发布者公钥文件
+const TRUSTED_PUBLISHERS_FILE: &str = "trusted_publishers.json";
+
+/// 信任列表的读改写锁
+static TRUSTED_PUBLISHERS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// 受信任的插件发布者
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedPublisher {
+    /// 发布者标识
+    pub id: String,
+    pub name: String,
+    /// Base64 编码的 Ed25519 公钥
+    pub public_key: String,
+}
+
+/// 插件包校验结果，记录在 plugin_config.info.verification 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVerification {
+    /// 插件包的 SHA-256
+    pub sha256: String,
+    /// 是否与插件市场提供的校验和一致，未提供校验和时为 false
+    pub checksum_verified: bool,
+    /// 签名是否通过信任的发布者公钥校验，未签名时为 false
+    pub signature_verified: bool,
+    /// 签名对应的发布者标识
+    pub publisher: Option<String>,
+    pub verified_at: String,
+}
+
+/// 校验插件包
+///
+/// 提供了 `expected_sha256` 或 `signature` 时必须校验通过，否则拒绝安装；
+/// 未签名的插件包只有在用户确认后（`allow_unsigned` 为 true）才能安装
+pub fn verify_package(
+    bytes: &[u8],
+    expected_sha256: Option<&str>,
+    signature: Option<&str>,
+    allow_unsigned: bool,
+) -> AppResult<PackageVerification> {
+    let sha256 = format!("{:x}", Sha256::digest(bytes));
+
+    let checksum_verified = match expected_sha256.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(expected) if expected.eq_ignore_ascii_case(&sha256) => true,
+        Some(expected) => {
+            return Err(AppError::Error(format!(
+                "插件包校验和不一致，期望 {}，实际 {}",
+                expected, sha256
+            )))
+        }
+        None => false,
+    };
+
+    let publisher = match signature.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(signature) => Some(verify_signature(bytes, signature)?),
+        None if allow_unsigned => None,
+        None => {
+            return Err(AppError::Error(
+                "插件包未签名，需要确认安装未签名的插件".into(),
+            ))
+        }
+    };
+
+    Ok(PackageVerification {
+        sha256,
+        checksum_verified,
+        signature_verified: publisher.is_some(),
+        publisher,
+        verified_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
+
+/// 使用信任的发布者公钥校验签名，返回签名对应的发布者标识
+fn verify_signature(bytes: &[u8], signature: &str) -> AppResult<String> {
+    let signature_bytes = STANDARD
+        .decode(signature)
+        .map_err(|e| AppError::Error(format!("插件签名格式无效: {}", e)))?;
+    let signature = Signature::from_slice(&signature_bytes)
+        .map_err(|e| AppError::Error(format!("插件签名格式无效: {}", e)))?;
+
+    let publishers = TrustedPublishers::list()?;
+    if publishers.is_empty() {
+        return Err(AppError::Error("没有信任的发布者公钥，无法校验插件签名".into()));
+    }
+
+    publishers
+        .into_iter()
+        .find(|publisher| {
+            parse_public_key(&publisher.public_key)
+                .is_ok_and(|key| key.verify_strict(bytes, &signature).is_ok())
+        })
+        .map(|publisher| publisher.id)
+        .ok_or_else(|| AppError::Error("插件签名校验失败，签名不属于任何信任的发布者".into()))
+}
+
+fn parse_public_key(public_key: &str) -> AppResult<VerifyingKey> {
+    let bytes = STANDARD
+        .decode(public_key.trim())
+        .map_err(|e| AppError::Error(format!("公钥格式无效: {}", e)))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| AppError::Error("公钥长度应为 32 字节".into()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| AppError::Error(format!("公钥无效: {}", e)))
+}
+
+/// 本地信任的发布者公钥列表
+pub struct TrustedPublishers;
+
+impl TrustedPublishers {
+    pub fn list() -> AppResult<Vec<TrustedPublisher>> {
+        let path = trusted_publishers_path()?;
+        read_json_or_recover(&path)
+            .map(Option::unwrap_or_default)
+            .map_err(|e| AppError::Error(format!("读取信任的发布者失败: {}", e)))
+    }
+
+    /// 添加或更新信任的发布者
+    pub fn add(publisher: TrustedPublisher) -> AppResult<()> {
+        if publisher.id.trim().is_empty() {
+            return Err(AppError::Error("发布者标识不能为空".into()));
+        }
+        parse_public_key(&publisher.public_key)?;
+
+        let _guard = TRUSTED_PUBLISHERS_LOCK.lock();
+        let mut publishers = Self::list()?;
+        publishers.retain(|p| p.id != publisher.id);
+        publishers.push(publisher);
+        Self::save(&publishers)
+    }
+
+    pub fn remove(id: &str) -> AppResult<()> {
+        let _guard = TRUSTED_PUBLISHERS_LOCK.lock();
+        let mut publishers = Self::list()?;
+        let count = publishers.len();
+        publishers.retain(|p| p.id != id);
+        if publishers.len() == count {
+            return Err(AppError::Error(format!("发布者 {} 不存在", id)));
+        }
+        Self::save(&publishers)
+    }
+
+    fn save(publishers: &[TrustedPublisher]) -> AppResult<()> {
+        let path = trusted_publishers_path()?;
+        write_json_atomic(&path, &publishers, true)
+            .map_err(|e| AppError::Error(format!("保存信任的发布者失败: {}", e)))
+    }
+}
+
+fn trusted_publishers_path() -> AppResult<PathBuf> {
+    get_myhelper_path()
+        .map(|path| path.join(TRUSTED_PUBLISHERS_FILE))
+        .map_err(AppError::from)
+}
+
+/// 获取信任的插件发布者
+#[permission_macro::permission(cap = "plugin.publishers.read")]
+#[tauri::command]
+pub fn mh_plugin_trusted_publishers() -> Result<ApiResponse<Vec<TrustedPublisher>>, AppError> {
+    match TrustedPublishers::list() {
+        Ok(publishers) => Ok(ApiResponse::success(publishers)),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrConfig, e.to_string())),
+    }
+}
+
+/// 添加信任的插件发布者，标识已存在时更新公钥
+#[permission_macro::permission(cap = "plugin.publishers.write")]
+#[tauri::command]
+pub fn mh_plugin_trust_publisher(
+    publisher: TrustedPublisher,
+) -> Result<ApiResponse<()>, AppError> {
+    match TrustedPublishers::add(publisher) {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrParamsInvalid, e.to_string())),
+    }
+}
+
+/// 移除信任的插件发布者
+#[permission_macro::permission(cap = "plugin.publishers.write")]
+#[tauri::command]
+pub fn mh_plugin_untrust_publisher(id: String) -> Result<ApiResponse<()>, AppError> {
+    match TrustedPublishers::remove(&id) {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(ApiStatusCode::ErrConfig, e.to_string())),
+    }
+}
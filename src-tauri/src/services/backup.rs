@@ -0,0 +1,692 @@
+use crate::services::database::{
+    current_schema_version, execute_write, get_db_pool, migrate_to_latest,
+};
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use chrono::Local;
+use rusqlite::{OptionalExtension, MAIN_DB};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// 备份格式版本，备份包结构不兼容变更时递增
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const DATA_PREFIX: &str = "data/";
+const DATABASE_FILE: &str = "myhelper.db";
+const BACKUP_DIR: &str = "Backup";
+const AUTO_BACKUP_PREFIX: &str = "auto-";
+const MANUAL_BACKUP_PREFIX: &str = "manual-";
+const PRE_RESTORE_BACKUP_PREFIX: &str = "pre-restore-";
+/// 保留的恢复前备份数量
+const PRE_RESTORE_RETENTION: usize = 3;
+const BACKUP_CONFIG_KEY: &str = "backupConfig";
+/// 自动备份检查间隔
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// 需要备份的目录（相对于 MyHelper 数据目录）
+const BACKUP_DIRS: [&str; 4] = ["Plugin", "Models/Live2D", "Image", "log"];
+/// 恢复时保留本地多出文件的目录，日志只追加且当前日志文件正被写入
+const KEEP_LOCAL_DIRS: [&str; 1] = ["log"];
+
+/// 自动备份配置，存储在 config 表的 `backupConfig` 键中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupConfig {
+    /// 是否启用自动备份
+    pub auto_backup: bool,
+    /// 自动备份间隔（小时）
+    pub interval_hours: u64,
+    /// 保留的自动备份数量
+    pub retention: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            auto_backup: true,
+            interval_hours: 24,
+            retention: 5,
+        }
+    }
+}
+
+/// 备份清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub schema_version: i32,
+    pub created_at: String,
+    pub files: Vec<BackupFileEntry>,
+}
+
+/// 备份清单中的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFileEntry {
+    /// 相对于数据目录的路径，统一使用 `/` 分隔
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// 备份文件信息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub size: u64,
+    pub is_auto: bool,
+    pub modified_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestoreAction {
+    /// 本地不存在，将新增
+    Add,
+    /// 本地存在且内容不同，将覆盖
+    Update,
+    /// 内容一致，无需处理
+    Unchanged,
+    /// 本地存在但备份中没有，将删除
+    Remove,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreChange {
+    pub path: String,
+    pub action: RestoreAction,
+    pub size: u64,
+}
+
+/// 恢复结果（预演时只描述将要发生的变更）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    pub manifest: BackupManifest,
+    pub changes: Vec<RestoreChange>,
+    /// 实际恢复前自动创建的当前数据备份
+    pub safety_backup: Option<String>,
+}
+
+pub struct BackupService;
+
+impl BackupService {
+    /// 获取备份目录
+    pub fn backup_dir() -> AppResult<PathBuf> {
+        let dir = get_myhelper_path()
+            .map_err(AppError::from)?
+            .join(BACKUP_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| AppError::Error(format!("创建备份目录失败: {}", e)))?;
+        Ok(dir)
+    }
+
+    /// 创建备份
+    ///
+    /// `target` 为空时写入备份目录，文件名带时间戳
+    pub fn create(target: Option<PathBuf>) -> AppResult<PathBuf> {
+        let target = match target {
+            Some(path) => path,
+            None => Self::backup_dir()?.join(Self::backup_file_name(MANUAL_BACKUP_PREFIX)),
+        };
+        Self::create_archive(&target)?;
+        Ok(target)
+    }
+
+    /// 列出备份目录中的所有备份，按时间倒序
+    pub fn list() -> AppResult<Vec<BackupInfo>> {
+        let mut backups = Vec::new();
+        for (path, modified) in Self::backup_files(None)? {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            backups.push(BackupInfo {
+                path: path.to_string_lossy().replace('\\', "/"),
+                is_auto: file_name.starts_with(AUTO_BACKUP_PREFIX),
+                file_name,
+                size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                modified_at: chrono::DateTime::<Local>::from(modified)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+            });
+        }
+        Ok(backups)
+    }
+
+    /// 从备份恢复
+    ///
+    /// `dry_run` 为 true 时只校验备份并返回将要发生的变更，不修改任何文件。
+    /// 备份目录中本地多出的文件会被删除，不属于备份范围的文件（如旧版本备份中的 config.json）会被忽略
+    pub fn restore(archive_path: &Path, dry_run: bool) -> AppResult<RestoreReport> {
+        let base_dir = get_myhelper_path().map_err(AppError::from)?;
+        let file = File::open(archive_path)
+            .map_err(|e| AppError::Error(format!("打开备份文件失败: {}", e)))?;
+        let mut archive =
+            ZipArchive::new(file).map_err(|e| AppError::Error(format!("无效的备份文件: {}", e)))?;
+
+        let manifest = Self::read_manifest(&mut archive)?;
+        Self::check_compatibility(&manifest)?;
+
+        // 先完整校验所有文件，确保备份包没有损坏
+        for entry in &manifest.files {
+            Self::validate_entry_path(&entry.path)?;
+            let mut zip_file = archive
+                .by_name(&format!("{}{}", DATA_PREFIX, entry.path))
+                .map_err(|e| AppError::Error(format!("备份中缺少文件 {}: {}", entry.path, e)))?;
+            let (size, checksum) = hash_reader(&mut zip_file)?;
+            if size != entry.size || checksum != entry.sha256 {
+                return Err(AppError::Error(format!("备份文件校验失败: {}", entry.path)));
+            }
+        }
+
+        // 数据库处于 WAL 模式，文件内容与已提交的数据不一致，用快照与备份比较
+        let compare_path = unique_temp_path(&Self::backup_dir()?.join("compare.db"));
+        Self::snapshot_database(&compare_path)?;
+        let changes = Self::plan_changes(&manifest, &base_dir, &compare_path);
+        let _ = fs::remove_file(&compare_path);
+        let changes = changes?;
+
+        if dry_run {
+            return Ok(RestoreReport {
+                dry_run,
+                manifest,
+                changes,
+                safety_backup: None,
+            });
+        }
+
+        // 恢复前先备份当前数据，恢复出错时可以手动还原
+        let safety_backup =
+            Self::backup_dir()?.join(Self::backup_file_name(PRE_RESTORE_BACKUP_PREFIX));
+        Self::create_archive(&safety_backup)?;
+        Self::prune(PRE_RESTORE_BACKUP_PREFIX, PRE_RESTORE_RETENTION)?;
+
+        for change in changes
+            .iter()
+            .filter(|c| c.action != RestoreAction::Unchanged)
+        {
+            if change.action == RestoreAction::Remove {
+                let path = base_dir.join(&change.path);
+                fs::remove_file(&path).map_err(|e| {
+                    AppError::Error(format!("删除文件 {} 失败: {}", change.path, e))
+                })?;
+                remove_empty_parents(&base_dir, &path);
+                continue;
+            }
+
+            let mut zip_file = archive
+                .by_name(&format!("{}{}", DATA_PREFIX, change.path))
+                .map_err(|e| AppError::Error(format!("读取备份文件失败: {}", e)))?;
+
+            if change.path == DATABASE_FILE {
+                Self::restore_database(&mut zip_file, &base_dir)?;
+            } else {
+                write_file_replacing(&base_dir.join(&change.path), &mut zip_file)?;
+            }
+        }
+
+        // 旧版本的备份需要迁移到当前 schema
+        migrate_to_latest()?;
+
+        Logger::write_log(LogEntry {
+            level: "info".to_string(),
+            message: "数据恢复完成".to_string(),
+            timestamp: String::new(),
+            details: Some(format!(
+                "备份: {}, 安全备份: {}",
+                archive_path.display(),
+                safety_backup.display()
+            )),
+        })
+        .map_err(AppError::from)?;
+
+        Ok(RestoreReport {
+            dry_run,
+            manifest,
+            changes,
+            safety_backup: Some(safety_backup.to_string_lossy().replace('\\', "/")),
+        })
+    }
+
+    /// 对比备份和本地文件，得到恢复时每个文件的变更
+    ///
+    /// `database_path` 为当前数据库的快照，用于和备份中的数据库比较
+    fn plan_changes(
+        manifest: &BackupManifest,
+        base_dir: &Path,
+        database_path: &Path,
+    ) -> AppResult<Vec<RestoreChange>> {
+        let mut changes = Vec::with_capacity(manifest.files.len());
+        for entry in manifest.files.iter().filter(|e| is_backup_path(&e.path)) {
+            let local_path = if entry.path == DATABASE_FILE {
+                database_path.to_path_buf()
+            } else {
+                base_dir.join(&entry.path)
+            };
+            let action = if !local_path.is_file() {
+                RestoreAction::Add
+            } else {
+                let (size, checksum) = hash_file(&local_path)?;
+                if size == entry.size && checksum == entry.sha256 {
+                    RestoreAction::Unchanged
+                } else {
+                    RestoreAction::Update
+                }
+            };
+            changes.push(RestoreChange {
+                path: entry.path.clone(),
+                action,
+                size: entry.size,
+            });
+        }
+
+        let mut local_files = Vec::new();
+        for dir in BACKUP_DIRS
+            .iter()
+            .filter(|dir| !KEEP_LOCAL_DIRS.contains(dir))
+        {
+            collect_files(base_dir, &base_dir.join(dir), &mut local_files)?;
+        }
+        local_files.sort();
+        for (relative, path) in local_files {
+            if manifest.files.iter().any(|entry| entry.path == relative) {
+                continue;
+            }
+            changes.push(RestoreChange {
+                size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                path: relative,
+                action: RestoreAction::Remove,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// 按配置执行一次自动备份检查
+    ///
+    /// 距离最近一次自动备份超过配置的间隔时创建新备份，并清理超出保留数量的旧备份
+    pub fn run_scheduled(config: &BackupConfig) -> AppResult<Option<PathBuf>> {
+        if !config.auto_backup || config.interval_hours == 0 {
+            return Ok(None);
+        }
+
+        let auto_backups = Self::backup_files(Some(AUTO_BACKUP_PREFIX))?;
+        let interval = Duration::from_secs(config.interval_hours * 3600);
+        let is_due = auto_backups.first().map_or(true, |(_, modified)| {
+            SystemTime::now()
+                .duration_since(*modified)
+                .map_or(true, |elapsed| elapsed >= interval)
+        });
+
+        let created = if is_due {
+            let path = Self::backup_dir()?.join(Self::backup_file_name(AUTO_BACKUP_PREFIX));
+            Self::create_archive(&path)?;
+            Some(path)
+        } else {
+            None
+        };
+
+        Self::prune(AUTO_BACKUP_PREFIX, config.retention.max(1))?;
+        Ok(created)
+    }
+
+    /// 启动自动备份任务，定期检查是否需要备份
+    pub fn start_scheduler() {
+        tauri::async_runtime::spawn(async {
+            loop {
+                tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+
+                let result = tokio::task::spawn_blocking(|| {
+                    let config = Self::load_config()?;
+                    Self::run_scheduled(&config)
+                })
+                .await;
+
+                let error = match result {
+                    Ok(Ok(_)) => continue,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) => e.to_string(),
+                };
+                let _ = Logger::write_log(LogEntry {
+                    level: "error".to_string(),
+                    message: "自动备份失败".to_string(),
+                    timestamp: String::new(),
+                    details: Some(error),
+                });
+            }
+        });
+    }
+
+    /// 读取自动备份配置，未配置时使用默认值
+    pub fn load_config() -> AppResult<BackupConfig> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM config WHERE key = ?1",
+                [BACKUP_CONFIG_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::Error(format!("读取备份配置失败: {}", e)))?;
+
+        match value {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Error(format!("解析备份配置失败: {}", e))),
+            None => Ok(BackupConfig::default()),
+        }
+    }
+
+    /// 删除超出保留数量的旧备份
+    fn prune(prefix: &str, retention: usize) -> AppResult<()> {
+        for (path, _) in Self::backup_files(Some(prefix))?
+            .into_iter()
+            .skip(retention)
+        {
+            fs::remove_file(&path)
+                .map_err(|e| AppError::Error(format!("删除旧备份失败: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// 获取备份目录中的备份文件及修改时间，按时间倒序
+    fn backup_files(prefix: Option<&str>) -> AppResult<Vec<(PathBuf, SystemTime)>> {
+        let dir = Self::backup_dir()?;
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir)
+            .map_err(|e| AppError::Error(format!("读取备份目录失败: {}", e)))?
+            .filter_map(Result::ok)
+        {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !path.is_file() || !name.ends_with(".zip") {
+                continue;
+            }
+            if prefix.is_some_and(|p| !name.starts_with(p)) {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((path, modified));
+        }
+        files.sort_by(|a, b| b.1.cmp(&a.1));
+        Ok(files)
+    }
+
+    fn backup_file_name(prefix: &str) -> String {
+        format!("{}{}.zip", prefix, Local::now().format("%Y%m%d-%H%M%S"))
+    }
+
+    /// 生成备份包：先写入临时文件，全部成功后再重命名
+    fn create_archive(target: &Path) -> AppResult<()> {
+        let base_dir = get_myhelper_path().map_err(AppError::from)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::Error(format!("创建备份目录失败: {}", e)))?;
+        }
+
+        // 通过 SQLite 在线备份获得一致的数据库快照
+        let snapshot_path = unique_temp_path(&target.with_extension("db"));
+        Self::snapshot_database(&snapshot_path)?;
+
+        let mut sources = vec![(DATABASE_FILE.to_string(), snapshot_path.clone())];
+        for dir in BACKUP_DIRS {
+            collect_files(&base_dir, &base_dir.join(dir), &mut sources)?;
+        }
+
+        let temp_path = unique_temp_path(target);
+        let result = Self::write_archive(&temp_path, &sources);
+        let _ = fs::remove_file(&snapshot_path);
+
+        if let Err(e) = result {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+
+        fs::rename(&temp_path, target)
+            .map_err(|e| AppError::Error(format!("保存备份文件失败: {}", e)))?;
+
+        Logger::write_log(LogEntry {
+            level: "info".to_string(),
+            message: "创建备份完成".to_string(),
+            timestamp: String::new(),
+            details: Some(format!("{}", target.display())),
+        })
+        .map_err(AppError::from)?;
+
+        Ok(())
+    }
+
+    fn write_archive(path: &Path, sources: &[(String, PathBuf)]) -> AppResult<()> {
+        let mut files = Vec::with_capacity(sources.len());
+        for (relative, source) in sources {
+            let (size, sha256) = hash_file(source)?;
+            files.push(BackupFileEntry {
+                path: relative.clone(),
+                size,
+                sha256,
+            });
+        }
+
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: current_schema_version()?,
+            created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            files,
+        };
+
+        let file =
+            File::create(path).map_err(|e| AppError::Error(format!("创建备份文件失败: {}", e)))?;
+        let mut writer = ZipWriter::new(file);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true);
+
+        let manifest_json = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| AppError::Error(format!("序列化备份清单失败: {}", e)))?;
+        writer
+            .start_file(MANIFEST_NAME, options)
+            .map_err(|e| AppError::Error(format!("写入备份清单失败: {}", e)))?;
+        writer
+            .write_all(&manifest_json)
+            .map_err(|e| AppError::Error(format!("写入备份清单失败: {}", e)))?;
+
+        for (relative, source) in sources {
+            writer
+                .start_file(format!("{}{}", DATA_PREFIX, relative), options)
+                .map_err(|e| AppError::Error(format!("写入备份文件失败: {}", e)))?;
+            let mut input = File::open(source)
+                .map_err(|e| AppError::Error(format!("读取文件 {} 失败: {}", relative, e)))?;
+            io::copy(&mut input, &mut writer)
+                .map_err(|e| AppError::Error(format!("写入备份文件失败: {}", e)))?;
+        }
+
+        let file = writer
+            .finish()
+            .map_err(|e| AppError::Error(format!("完成备份文件失败: {}", e)))?;
+        file.sync_all()
+            .map_err(|e| AppError::Error(format!("写入备份文件失败: {}", e)))?;
+        Ok(())
+    }
+
+    fn snapshot_database(path: &Path) -> AppResult<()> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        conn.backup(MAIN_DB, path, None)
+            .map_err(|e| AppError::Error(format!("数据库快照失败: {}", e)))
+    }
+
+    /// 通过 SQLite 在线备份接口把快照写回当前数据库，已打开的连接会看到恢复后的数据
+    fn restore_database<R: Read>(reader: &mut R, base_dir: &Path) -> AppResult<()> {
+        let snapshot_path = unique_temp_path(&base_dir.join(BACKUP_DIR).join("restore.db"));
+        write_file_replacing(&snapshot_path, reader)?;
+
+        let source = snapshot_path.clone();
+        let result = execute_write(move |conn| {
+            conn.restore(MAIN_DB, &source, None::<fn(_)>)
+                .map_err(|e| AppError::Error(format!("恢复数据库失败: {}", e)))
+        });
+        let _ = fs::remove_file(&snapshot_path);
+        result
+    }
+
+    fn read_manifest<R: Read + io::Seek>(archive: &mut ZipArchive<R>) -> AppResult<BackupManifest> {
+        let mut manifest_file = archive
+            .by_name(MANIFEST_NAME)
+            .map_err(|_| AppError::Error("备份文件缺少 manifest.json".into()))?;
+        let mut content = String::new();
+        manifest_file
+            .read_to_string(&mut content)
+            .map_err(|e| AppError::Error(format!("读取备份清单失败: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::Error(format!("解析备份清单失败: {}", e)))
+    }
+
+    fn check_compatibility(manifest: &BackupManifest) -> AppResult<()> {
+        if manifest.format_version > BACKUP_FORMAT_VERSION {
+            return Err(AppError::Error(format!(
+                "备份格式版本 {} 高于当前支持的版本 {}",
+                manifest.format_version, BACKUP_FORMAT_VERSION
+            )));
+        }
+        let schema_version = current_schema_version()?;
+        if manifest.schema_version > schema_version {
+            return Err(AppError::Error(format!(
+                "备份来自更新版本的 MyHelper（{}，数据库版本 {}），请先升级",
+                manifest.app_version, manifest.schema_version
+            )));
+        }
+        Ok(())
+    }
+
+    /// 清单中的路径只能指向数据目录内部
+    fn validate_entry_path(path: &str) -> AppResult<()> {
+        let is_safe = !path.is_empty()
+            && !path.starts_with('/')
+            && !path.contains('\\')
+            && !path.contains(':')
+            && path
+                .split('/')
+                .all(|part| !part.is_empty() && part != "." && part != "..");
+        if is_safe {
+            Ok(())
+        } else {
+            Err(AppError::Error(format!("备份清单包含非法路径: {}", path)))
+        }
+    }
+}
+
+/// 在指定路径后追加本次调用独占的后缀
+///
+/// 手动恢复、恢复预览和自动备份可能同时进行，各自使用独立的临时文件
+fn unique_temp_path(path: &Path) -> PathBuf {
+    static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+    let id = NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed);
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}-{}.tmp", std::process::id(), id));
+    PathBuf::from(name)
+}
+
+/// 递归收集目录下的所有文件
+fn collect_files(base_dir: &Path, dir: &Path, out: &mut Vec<(String, PathBuf)>) -> AppResult<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)
+        .map_err(|e| AppError::Error(format!("读取目录失败: {}", e)))?
+        .filter_map(Result::ok)
+    {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(base_dir, &path, out)?;
+        } else if path.is_file() {
+            if let Ok(relative) = path.strip_prefix(base_dir) {
+                out.push((relative.to_string_lossy().replace('\\', "/"), path.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 路径是否属于备份范围：数据库或备份目录中的文件
+fn is_backup_path(path: &str) -> bool {
+    path == DATABASE_FILE
+        || BACKUP_DIRS
+            .iter()
+            .any(|dir| path.starts_with(&format!("{}/", dir)))
+}
+
+/// 删除文件后清理变为空的上级目录，直到数据目录
+fn remove_empty_parents(base_dir: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == base_dir || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+fn hash_file(path: &Path) -> AppResult<(u64, String)> {
+    let mut file = File::open(path).map_err(|e| AppError::Error(format!("读取文件失败: {}", e)))?;
+    hash_reader(&mut file)
+}
+
+/// 计算内容的大小和 SHA-256
+fn hash_reader<R: Read>(reader: &mut R) -> AppResult<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| AppError::Error(format!("读取文件失败: {}", e)))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+/// 写入临时文件后重命名覆盖目标文件
+fn write_file_replacing<R: Read>(path: &Path, reader: &mut R) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::Error(format!("创建目录失败: {}", e)))?;
+    }
+    let temp_path = path.with_file_name(format!(
+        "{}.restore.tmp",
+        path.file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default()
+    ));
+    let mut output =
+        File::create(&temp_path).map_err(|e| AppError::Error(format!("创建文件失败: {}", e)))?;
+    io::copy(reader, &mut output).map_err(|e| AppError::Error(format!("写入文件失败: {}", e)))?;
+    output
+        .sync_all()
+        .map_err(|e| AppError::Error(format!("写入文件失败: {}", e)))?;
+    drop(output);
+    fs::rename(&temp_path, path).map_err(|e| AppError::Error(format!("替换文件失败: {}", e)))
+}
@@ -1,84 +1,256 @@
+use rusqlite::{params, Connection, OptionalExtension};
 use serde_json::{Map, Value};
-use simd_json;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::path::{Path, PathBuf};
+use std::fs;
 
+use crate::services::config_history::ConfigHistory;
+use crate::services::config_schema::{ConfigSchema, Validated};
+use crate::services::database::{execute_write, get_db_pool};
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::atomic_file::{backup_path, read_json_or_recover};
+use crate::utils::error::{AppError, AppResult};
 use crate::utils::path::get_myhelper_path;
 
-// 获取配置文件的路径并确保目录存在
-pub fn get_config_path(config_type: &str) -> Result<PathBuf, String> {
-    let myhelper_path = get_myhelper_path()?;
-    let file_name = match config_type {
-        "config" => "config.json",
-        // plugin这个暂时用不到，暂存下以后可能会拓展
-        "plugin" => "plugin.json",
-        _ => return Err("Invalid config type".to_string()),
-    };
-    let config_path = myhelper_path.join(file_name);
-
-    // 如果配置文件不存在，则创建它并初始化为空的 JSON 对象
-    if !config_path.exists() {
-        let mut file = fs::File::create(&config_path).map_err(|e| e.to_string())?;
-        file.write_all(b"{}").map_err(|e| e.to_string())?;
+/// 旧版文件配置，迁移到数据库后不再写入
+const LEGACY_CONFIG_FILE: &str = "config.json";
+
+/// 基于数据库 config 表的嵌套路径配置存储
+///
+/// 路径第一段为 config 表的键，其余各段为该键 JSON 值内部的字段路径，
+/// 如 `["settingConfig", "hotkey", "enabled"]`
+pub struct ConfigStore;
+
+impl ConfigStore {
+    /// 按路径读取配置，路径为空时返回整个 config 表
+    pub fn get(path: &[String]) -> AppResult<Option<Value>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+
+        let Some((key, rest)) = path.split_first() else {
+            return read_all(&conn).map(|map| Some(Value::Object(map)));
+        };
+
+        let mut current = match read_value(&conn, key)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        for field in rest {
+            match current.get_mut(field) {
+                Some(value) => current = value.take(),
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(current))
     }
 
-    Ok(config_path)
-}
+    /// 按路径写入配置，中间层级不存在时自动创建对象
+    ///
+    /// 写入前校验该键的完整值，成功时返回写入后的值；`source` 为发起变更的窗口标签
+    pub fn set(mut path: Vec<String>, value: Value, source: &str) -> AppResult<Validated<Value>> {
+        if path.is_empty() {
+            return Err(AppError::Error("缺少要设置的键".into()));
+        }
+        let key = path.remove(0);
+        let source = source.to_string();
 
-// 读取配置文件
-fn read_config(config_path: &Path) -> Result<HashMap<String, Value>, String> {
-    if !config_path.exists() {
-        return Ok(HashMap::with_capacity(8)); 
+        execute_write(move |conn| {
+            let current = if path.is_empty() {
+                value
+            } else {
+                let mut current =
+                    read_value(conn, &key)?.unwrap_or_else(|| Value::Object(Map::new()));
+                update_nested_value(&mut current, &path, value).map_err(AppError::Error)?;
+                current
+            };
+
+            if let Err(errors) = ConfigSchema::validate(&key, &current) {
+                return Ok(Validated::Invalid(errors));
+            }
+            ConfigHistory::write(conn, &key, Some(&current), &source)?;
+            Ok(Validated::Valid(current))
+        })
     }
 
-    let mut file = File::open(config_path).map_err(|e| e.to_string())?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+    /// 按路径删除配置，路径为空时删除所有配置
+    ///
+    /// 删除嵌套字段后同样校验该键的完整值，成功时返回删除后的值，整个键被删除时返回 None
+    pub fn delete(path: Vec<String>, source: &str) -> AppResult<Validated<Option<Value>>> {
+        let source = source.to_string();
+        execute_write(move |conn| {
+            let Some((key, rest)) = path.split_first() else {
+                let tx = conn
+                    .transaction()
+                    .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+                ConfigHistory::clear(&tx, &source)?;
+                tx.commit()
+                    .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
+                return Ok(Validated::Valid(None));
+            };
+
+            if rest.is_empty() {
+                ConfigHistory::write(conn, key, None, &source)?;
+                return Ok(Validated::Valid(None));
+            }
 
-    // 如果文件为空，返回空的 HashMap
-    if buffer.is_empty() {
-        return Ok(HashMap::with_capacity(8));
+            let mut current = read_value(conn, key)?
+                .ok_or_else(|| AppError::Error(format!("配置中不存在键: {}", key)))?;
+            delete_nested_key(&mut current, rest).map_err(AppError::Error)?;
+            if let Err(errors) = ConfigSchema::validate(key, &current) {
+                return Ok(Validated::Invalid(errors));
+            }
+            ConfigHistory::write(conn, key, Some(&current), &source)?;
+            Ok(Validated::Valid(Some(current)))
+        })
     }
 
-    // 使用simd-json加速解析
-    match simd_json::serde::from_slice::<HashMap<String, Value>>(&mut buffer) {
-        Ok(data) => Ok(data),
-        Err(_) => {
-            // 回退到标准serde_json
-            serde_json::from_slice(&buffer).map_err(|e| e.to_string())
+    /// 将旧版 config.json 一次性迁移到数据库
+    ///
+    /// 数据库中已存在的键保留数据库的值，迁移完成后原文件重命名为 config.json.migrated
+    pub fn migrate_legacy_file() -> AppResult<()> {
+        let config_path = get_myhelper_path()
+            .map_err(AppError::from)?
+            .join(LEGACY_CONFIG_FILE);
+        if !config_path.exists() {
+            return Ok(());
         }
+
+        let data: HashMap<String, Value> = read_json_or_recover(&config_path)
+            .map_err(|e| AppError::Error(format!("读取 config.json 失败: {}", e)))?
+            .unwrap_or_default();
+        let count = data.len();
+
+        execute_write(move |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+            for (key, value) in data {
+                let json_value = serde_json::to_string(&value)
+                    .map_err(|e| AppError::Error(format!("序列化配置值失败: {}", e)))?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO config (key, value) VALUES (?1, ?2)",
+                    params![key, json_value],
+                )
+                .map_err(|e| AppError::Error(format!("迁移配置 {} 失败: {}", key, e)))?;
+            }
+            tx.commit()
+                .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+        })?;
+
+        fs::rename(&config_path, config_path.with_extension("json.migrated"))
+            .map_err(|e| AppError::Error(format!("重命名 config.json 失败: {}", e)))?;
+        let _ = fs::remove_file(backup_path(&config_path));
+
+        let _ = Logger::write_log(LogEntry {
+            level: "info".to_string(),
+            message: "config.json 已迁移到数据库".to_string(),
+            timestamp: String::new(),
+            details: Some(format!("共 {} 个配置项", count)),
+        });
+        Ok(())
     }
 }
 
-// 保存配置数据
-pub fn utils_set_config(config_type: &str, new_data: HashMap<String, Value>) -> Result<(), String> {
-    let config_path = get_config_path(config_type)?;
-    let mut current_data = read_config(&config_path)?;
+fn read_all(conn: &Connection) -> AppResult<Map<String, Value>> {
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM config")
+        .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
 
-    // 合并新数据
-    current_data.extend(new_data);
+    let mut map = Map::new();
+    for row in rows {
+        let (key, json_str) = row.map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?;
+        map.insert(key, parse_value(json_str)?);
+    }
+    Ok(map)
+}
 
-    let config_data = serde_json::to_string(&current_data).map_err(|e| e.to_string())?;
-    std::fs::write(&config_path, config_data).map_err(|e| e.to_string())?;
+fn read_value(conn: &Connection, key: &str) -> AppResult<Option<Value>> {
+    conn.query_row("SELECT value FROM config WHERE key = ?1", [key], |row| {
+        row.get::<_, String>(0)
+    })
+    .optional()
+    .map_err(|e| AppError::Error(format!("获取配置值失败: {}", e)))?
+    .map(parse_value)
+    .transpose()
+}
 
-    Ok(())
+fn parse_value(json_str: String) -> AppResult<Value> {
+    // 使用simd-json加速解析
+    let mut json_bytes = json_str.into_bytes();
+    match simd_json::serde::from_slice::<Value>(&mut json_bytes) {
+        Ok(value) => Ok(value),
+        // 回退到标准serde_json
+        Err(_) => serde_json::from_slice(&json_bytes)
+            .map_err(|e| AppError::Error(format!("解析配置值失败: {}", e))),
+    }
 }
 
-// 获取配置数据，支持嵌套字段访问
-pub fn utils_get_config(config_type: &str, keys: Vec<String>) -> Result<Option<Value>, String> {
-    let config_path = get_config_path(config_type)?;
-    let data = read_config(&config_path)?;
+// 使用递归函数更新嵌套字段
+fn update_nested_value(data: &mut Value, keys: &[String], value: Value) -> Result<(), String> {
+    if keys.is_empty() {
+        return Err("缺少要设置的键".to_string());
+    }
 
-    let mut current_value = Value::Object(data.into_iter().collect::<Map<_, _>>());
+    if keys.len() == 1 {
+        // 到达最后一个键，更新值
+        match data {
+            Value::Object(map) => {
+                map.insert(keys[0].to_string(), value);
+            }
+            _ => {
+                return Err("无法更新非对象类型的配置数据".to_string());
+            }
+        }
+    } else {
+        // 确保父级键存在并是对象，如果不存在则创建一个新对象
+        let nested_data = {
+            let map = data
+                .as_object_mut()
+                .ok_or_else(|| "配置数据不是对象类型".to_string())?;
+            map.entry(keys[0].to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+        };
 
-    for key in &keys { 
-        match current_value.get(key) {
-            Some(value) => current_value = value.clone(),
-            None => return Ok(None),
+        if let Value::Object(_) = nested_data {
+            update_nested_value(nested_data, &keys[1..], value)?;
+        } else {
+            return Err(format!("配置中键 {} 的类型不是对象", &keys[0]));
         }
     }
 
-    Ok(Some(current_value))
+    Ok(())
+}
+
+// 使用递归函数删除嵌套字段
+fn delete_nested_key(data: &mut Value, keys: &[String]) -> Result<(), String> {
+    if keys.is_empty() {
+        return Err("缺少要删除的键".to_string());
+    }
+
+    if keys.len() == 1 {
+        match data {
+            Value::Object(map) => {
+                map.remove(&keys[0]);
+            }
+            _ => {
+                return Err("无法从非对象类型的配置数据中删除键".to_string());
+            }
+        }
+    } else {
+        // 递归删除嵌套字段
+        match data.get_mut(&keys[0]) {
+            Some(nested_data) => {
+                delete_nested_key(nested_data, &keys[1..])?;
+            }
+            None => {
+                return Err(format!("配置中不存在键: {}", &keys[0]));
+            }
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,143 @@
+use crate::core::app_handle::AppHandleManager;
+use crate::services::permission::PermissionPolicy;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tauri::{Emitter, Manager};
+
+/// 配置变更事件名
+pub const CONFIG_CHANGED_EVENT: &str = "config:changed";
+
+/// 配置所在的存储位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigTable {
+    /// 数据库 config 表
+    Config,
+    /// 数据库 plugin_config 表
+    PluginConfig,
+    /// 数据库 pet_config 表
+    PetConfig,
+}
+
+impl ConfigTable {
+    /// 读取该存储所需的能力，与对应 getter 命令的权限保持一致
+    pub fn read_capability(self) -> &'static str {
+        match self {
+            ConfigTable::Config => "config.read",
+            ConfigTable::PluginConfig => "plugin.config",
+            ConfigTable::PetConfig => "pet.config",
+        }
+    }
+
+    /// 窗口是否可以读取该存储
+    pub fn readable_by(self, window_label: &str) -> bool {
+        PermissionPolicy::allows(window_label, self.read_capability())
+    }
+}
+
+/// 配置变更事件
+///
+/// `key` 为空表示整个存储被清空；`value` 为 None 表示键被删除
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChangedEvent {
+    pub table: ConfigTable,
+    pub key: String,
+    pub value: Option<Value>,
+}
+
+/// 单个窗口在某个存储上的订阅，`keys` 为空表示订阅全部键
+#[derive(Debug, Default)]
+struct Subscription {
+    keys: HashSet<String>,
+}
+
+impl Subscription {
+    fn matches(&self, key: &str) -> bool {
+        self.keys.is_empty() || key.is_empty() || self.keys.contains(key)
+    }
+}
+
+/// 窗口标签 -> 存储 -> 订阅
+static SUBSCRIPTIONS: Lazy<RwLock<HashMap<String, HashMap<ConfigTable, Subscription>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+pub struct ConfigEvents;
+
+impl ConfigEvents {
+    /// 订阅配置变更，重复订阅会合并键
+    pub fn subscribe(window_label: &str, table: ConfigTable, keys: Vec<String>) {
+        let mut subscriptions = SUBSCRIPTIONS.write();
+        let subscription = subscriptions
+            .entry(window_label.to_string())
+            .or_default()
+            .entry(table)
+            .or_default();
+
+        if keys.is_empty() {
+            subscription.keys.clear();
+        } else {
+            subscription.keys.extend(keys);
+        }
+    }
+
+    /// 取消订阅，`table` 为 None 时取消该窗口的全部订阅
+    pub fn unsubscribe(window_label: &str, table: Option<ConfigTable>) {
+        let mut subscriptions = SUBSCRIPTIONS.write();
+        match table {
+            Some(table) => {
+                if let Some(tables) = subscriptions.get_mut(window_label) {
+                    tables.remove(&table);
+                    if tables.is_empty() {
+                        subscriptions.remove(window_label);
+                    }
+                }
+            }
+            None => {
+                subscriptions.remove(window_label);
+            }
+        }
+    }
+
+    /// 向订阅了该键的窗口发送变更事件
+    pub fn notify(table: ConfigTable, key: &str, value: Option<Value>) {
+        let Some(app_handle) = AppHandleManager::get() else {
+            return;
+        };
+
+        let event = ConfigChangedEvent {
+            table,
+            key: key.to_string(),
+            value,
+        };
+
+        let mut closed_windows = Vec::new();
+        {
+            let subscriptions = SUBSCRIPTIONS.read();
+            for (label, tables) in subscriptions.iter() {
+                let Some(subscription) = tables.get(&table) else {
+                    continue;
+                };
+                if !table.readable_by(label) || !subscription.matches(key) {
+                    continue;
+                }
+                if app_handle.get_webview_window(label).is_none() {
+                    closed_windows.push(label.clone());
+                    continue;
+                }
+                let _ = app_handle.emit_to(label.as_str(), CONFIG_CHANGED_EVENT, &event);
+            }
+        }
+
+        // 清理已关闭窗口的订阅
+        if !closed_windows.is_empty() {
+            let mut subscriptions = SUBSCRIPTIONS.write();
+            for label in closed_windows {
+                subscriptions.remove(&label);
+            }
+        }
+    }
+}
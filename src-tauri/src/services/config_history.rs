@@ -0,0 +1,423 @@
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::services::database::{execute_write, get_db_pool};
+use crate::utils::error::{AppError, AppResult};
+
+/// 最多保留的变更记录条数
+const MAX_HISTORY: i64 = 1000;
+
+/// 不记录历史的配置键，窗口位置在拖动时频繁写入
+const UNTRACKED_KEYS: &[&str] = &["position"];
+
+/// 时间戳格式，按字符串比较即可得到时间顺序
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// 单条配置变更记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub id: i64,
+    pub key: String,
+    /// 变更前的值，None 表示变更前该键不存在
+    pub previous_value: Option<Value>,
+    /// 变更后的值，None 表示该键被删除
+    pub new_value: Option<Value>,
+    /// 发起变更的窗口标签，后台任务为任务名
+    pub window_label: String,
+    pub changed_at: String,
+}
+
+/// 回滚后配置键的值，`value` 为 None 表示该键已被删除
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredValue {
+    pub key: String,
+    pub value: Option<Value>,
+}
+
+/// 配置变更历史
+///
+/// 所有对 config 表的写入都应通过 [`ConfigHistory::write`] 或 [`ConfigHistory::clear`]，
+/// 它们在同一连接上完成写入和记录，调用方负责在写入线程中执行
+pub struct ConfigHistory;
+
+impl ConfigHistory {
+    /// 写入单个配置键并记录变更，`value` 为 None 时删除该键
+    ///
+    /// 值未变化时不写入也不记录，返回是否发生了变更
+    pub fn write(conn: &Connection, key: &str, value: Option<&Value>, source: &str) -> AppResult<bool> {
+        let previous: Option<String> = conn
+            .query_row("SELECT value FROM config WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
+            .map_err(|e| AppError::Error(format!("获取配置值失败: {}", e)))?;
+        let new_value = value
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::Error(format!("序列化配置值失败: {}", e)))?;
+
+        if previous == new_value {
+            return Ok(false);
+        }
+
+        match &new_value {
+            Some(json_value) => conn.execute(
+                "INSERT OR REPLACE INTO config (key, value) VALUES (?1, ?2)",
+                params![key, json_value],
+            ),
+            None => conn.execute("DELETE FROM config WHERE key = ?1", [key]),
+        }
+        .map_err(|e| AppError::Error(format!("保存配置值失败: {}", e)))?;
+
+        record(conn, key, previous, new_value, source)?;
+        Ok(true)
+    }
+
+    /// 删除所有配置，逐键记录变更
+    pub fn clear(conn: &Connection, source: &str) -> AppResult<Vec<String>> {
+        let keys = {
+            let mut stmt = conn
+                .prepare("SELECT key FROM config")
+                .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?
+        };
+
+        for key in &keys {
+            Self::write(conn, key, None, source)?;
+        }
+        Ok(keys)
+    }
+
+    /// 获取最近的变更记录，按时间倒序
+    pub fn list(limit: u32, key: Option<&str>) -> AppResult<Vec<ConfigChange>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, key, previous_value, new_value, window_label, changed_at \
+                 FROM config_history WHERE ?1 IS NULL OR key = ?1 ORDER BY id DESC LIMIT ?2",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let rows = stmt
+            .query_map(params![key, limit], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            let (id, key, previous_value, new_value, window_label, changed_at) =
+                row.map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?;
+            changes.push(ConfigChange {
+                id,
+                key,
+                previous_value: parse_optional(previous_value)?,
+                new_value: parse_optional(new_value)?,
+                window_label,
+                changed_at,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// 撤销指定的变更，把对应键恢复为该次变更前的值
+    ///
+    /// 撤销本身也会记录为一次新的变更
+    pub fn revert(id: i64, source: &str) -> AppResult<RestoredValue> {
+        let source = source.to_string();
+        execute_write(move |conn| {
+            let (key, previous): (String, Option<String>) = conn
+                .query_row(
+                    "SELECT key, previous_value FROM config_history WHERE id = ?1",
+                    [id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|e| AppError::Error(format!("读取变更记录失败: {}", e)))?
+                .ok_or_else(|| AppError::Error(format!("变更记录 {} 不存在", id)))?;
+
+            let value = parse_optional(previous)?;
+            Self::write(conn, &key, value.as_ref(), &source)?;
+            Ok(RestoredValue { key, value })
+        })
+    }
+
+    /// 把配置恢复到指定时间点的状态
+    ///
+    /// 对该时间点之后变更过的每个键，取其之后第一次变更前的值；
+    /// 早于历史记录保留范围的状态无法恢复。`dry_run` 为 true 时只返回将要恢复的值
+    pub fn restore_as_of(timestamp: &str, dry_run: bool, source: &str) -> AppResult<Vec<RestoredValue>> {
+        let timestamp = timestamp.to_string();
+        let source = source.to_string();
+        execute_write(move |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+
+            let restored = restore_targets(&tx, &timestamp)?;
+            if !dry_run {
+                for target in &restored {
+                    Self::write(&tx, &target.key, target.value.as_ref(), &source)?;
+                }
+                tx.commit()
+                    .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
+            }
+            Ok(restored)
+        })
+    }
+}
+
+/// 计算恢复到指定时间点需要写入的值
+///
+/// 对该时间点之后变更过的每个键，取其之后第一次变更前的值，跳过与当前值相同的键，按键名排序
+fn restore_targets(conn: &Connection, timestamp: &str) -> AppResult<Vec<RestoredValue>> {
+    let targets = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT key, previous_value FROM config_history WHERE id IN (\
+                 SELECT MIN(id) FROM config_history WHERE changed_at > ?1 GROUP BY key) \
+                 ORDER BY key",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let rows = stmt
+            .query_map([timestamp], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?
+    };
+
+    let mut restored = Vec::new();
+    for (key, previous) in targets {
+        let value = parse_optional(previous)?;
+        let current: Option<String> = conn
+            .query_row("SELECT value FROM config WHERE key = ?1", [&key], |row| row.get(0))
+            .optional()
+            .map_err(|e| AppError::Error(format!("获取配置值失败: {}", e)))?;
+        if parse_optional(current)? == value {
+            continue;
+        }
+        restored.push(RestoredValue { key, value });
+    }
+    Ok(restored)
+}
+
+/// 插入变更记录并清理超出上限的旧记录
+fn record(
+    conn: &Connection,
+    key: &str,
+    previous: Option<String>,
+    new_value: Option<String>,
+    source: &str,
+) -> AppResult<()> {
+    if UNTRACKED_KEYS.contains(&key) {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO config_history (key, previous_value, new_value, window_label, changed_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            key,
+            previous,
+            new_value,
+            source,
+            Local::now().format(TIMESTAMP_FORMAT).to_string()
+        ],
+    )
+    .map_err(|e| AppError::Error(format!("记录配置变更失败: {}", e)))?;
+
+    conn.execute(
+        "DELETE FROM config_history WHERE id <= last_insert_rowid() - ?1",
+        [MAX_HISTORY],
+    )
+    .map_err(|e| AppError::Error(format!("清理配置变更记录失败: {}", e)))?;
+    Ok(())
+}
+
+fn parse_optional(json_str: Option<String>) -> AppResult<Option<Value>> {
+    json_str
+        .map(|json_str| {
+            serde_json::from_str(&json_str)
+                .map_err(|e| AppError::Error(format!("解析配置值失败: {}", e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("migrations/0001_init.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("migrations/0003_config_history.sql"))
+            .unwrap();
+        conn
+    }
+
+    fn set(conn: &Connection, key: &str, value: &str) {
+        conn.execute(
+            "INSERT OR REPLACE INTO config (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )
+        .unwrap();
+    }
+
+    fn change(
+        conn: &Connection,
+        key: &str,
+        previous: Option<&str>,
+        new_value: Option<&str>,
+        at: &str,
+    ) {
+        conn.execute(
+            "INSERT INTO config_history (key, previous_value, new_value, window_label, changed_at) \
+             VALUES (?1, ?2, ?3, 'test', ?4)",
+            params![key, previous, new_value, at],
+        )
+        .unwrap();
+    }
+
+    fn keys(restored: &[RestoredValue]) -> Vec<&str> {
+        restored.iter().map(|value| value.key.as_str()).collect()
+    }
+
+    #[test]
+    fn picks_first_change_after_timestamp() {
+        let conn = open();
+        change(
+            &conn,
+            "theme",
+            Some("\"light\""),
+            Some("\"dark\""),
+            "2026-01-01 10:00:00.000",
+        );
+        change(
+            &conn,
+            "theme",
+            Some("\"dark\""),
+            Some("\"blue\""),
+            "2026-01-02 10:00:00.000",
+        );
+        change(
+            &conn,
+            "theme",
+            Some("\"blue\""),
+            Some("\"red\""),
+            "2026-01-03 10:00:00.000",
+        );
+        set(&conn, "theme", "\"red\"");
+
+        let restored = restore_targets(&conn, "2026-01-01 12:00:00.000").unwrap();
+        assert_eq!(keys(&restored), ["theme"]);
+        assert_eq!(restored[0].value, Some(json!("dark")));
+    }
+
+    #[test]
+    fn ignores_changes_before_timestamp() {
+        let conn = open();
+        change(
+            &conn,
+            "theme",
+            None,
+            Some("\"dark\""),
+            "2026-01-01 10:00:00.000",
+        );
+        set(&conn, "theme", "\"dark\"");
+
+        assert!(restore_targets(&conn, "2026-01-01 10:00:00.000")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn missing_previous_value_deletes_key() {
+        let conn = open();
+        change(
+            &conn,
+            "shortcut",
+            None,
+            Some("\"Alt+Space\""),
+            "2026-01-02 10:00:00.000",
+        );
+        set(&conn, "shortcut", "\"Alt+Space\"");
+
+        let restored = restore_targets(&conn, "2026-01-01 10:00:00.000").unwrap();
+        assert_eq!(keys(&restored), ["shortcut"]);
+        assert_eq!(restored[0].value, None);
+    }
+
+    #[test]
+    fn skips_keys_already_at_target() {
+        let conn = open();
+        change(
+            &conn,
+            "theme",
+            Some("\"light\""),
+            Some("\"dark\""),
+            "2026-01-02 10:00:00.000",
+        );
+        change(
+            &conn,
+            "theme",
+            Some("\"dark\""),
+            Some("\"light\""),
+            "2026-01-03 10:00:00.000",
+        );
+        set(&conn, "theme", "\"light\"");
+        change(&conn, "deleted", Some("1"), None, "2026-01-02 10:00:00.000");
+        change(&conn, "deleted", None, Some("2"), "2026-01-03 10:00:00.000");
+        change(&conn, "deleted", Some("2"), None, "2026-01-04 10:00:00.000");
+
+        let restored = restore_targets(&conn, "2026-01-01 10:00:00.000").unwrap();
+        assert_eq!(keys(&restored), ["deleted"]);
+        assert_eq!(restored[0].value, Some(json!(1)));
+    }
+
+    #[test]
+    fn orders_targets_by_key() {
+        let conn = open();
+        change(
+            &conn,
+            "zoom",
+            Some("1"),
+            Some("2"),
+            "2026-01-02 10:00:00.000",
+        );
+        change(
+            &conn,
+            "alpha",
+            Some("true"),
+            Some("false"),
+            "2026-01-03 10:00:00.000",
+        );
+        change(
+            &conn,
+            "mode",
+            Some("\"a\""),
+            Some("\"b\""),
+            "2026-01-04 10:00:00.000",
+        );
+
+        let restored = restore_targets(&conn, "2026-01-01 10:00:00.000").unwrap();
+        assert_eq!(keys(&restored), ["alpha", "mode", "zoom"]);
+    }
+}
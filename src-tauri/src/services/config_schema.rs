@@ -0,0 +1,426 @@
+use crate::services::config_history::ConfigHistory;
+use crate::services::database::{execute_write, get_db_pool};
+use crate::utils::error::{AppError, AppResult};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 字段级校验错误
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// 字段路径，如 `settingConfig.hotkey.togglePanel.key`
+    pub path: String,
+    pub message: String,
+}
+
+/// 带校验结果的写入结果
+#[derive(Debug)]
+pub enum Validated<T> {
+    Valid(T),
+    Invalid(Vec<FieldError>),
+}
+
+/// 配置值的结构描述
+enum Schema {
+    Bool,
+    Number,
+    /// 整数，可限制最小值
+    Integer(Option<i64>),
+    String,
+    /// 字符串枚举
+    StringEnum(&'static [&'static str]),
+    /// 整数枚举
+    IntegerEnum(&'static [i64]),
+    Array(Box<Schema>),
+    /// 对象，`additional` 用于校验未声明的字段，为 None 时不校验未声明字段
+    Object {
+        fields: Vec<Field>,
+        additional: Option<Box<Schema>>,
+    },
+}
+
+struct Field {
+    name: &'static str,
+    schema: Schema,
+    required: bool,
+}
+
+fn required(name: &'static str, schema: Schema) -> Field {
+    Field {
+        name,
+        schema,
+        required: true,
+    }
+}
+
+fn optional(name: &'static str, schema: Schema) -> Field {
+    Field {
+        name,
+        schema,
+        required: false,
+    }
+}
+
+fn object(fields: Vec<Field>) -> Schema {
+    Schema::Object {
+        fields,
+        additional: None,
+    }
+}
+
+fn array(item: Schema) -> Schema {
+    Schema::Array(Box::new(item))
+}
+
+/// 快捷键配置，除 enabled 外的字段均为快捷键项，与 `command::hotkey::HotkeyConfig` 对应
+fn hotkey_schema() -> Schema {
+    Schema::Object {
+        fields: vec![required("enabled", Schema::Bool)],
+        additional: Some(Box::new(object(vec![
+            required("enabled", Schema::Bool),
+            required("key", Schema::String),
+        ]))),
+    }
+}
+
+/// 应用、网站列表配置
+fn select_list_schema() -> Schema {
+    object(vec![
+        optional(
+            "dataList",
+            array(object(vec![
+                required("id", Schema::Number),
+                required("title", Schema::String),
+                required("path", Schema::String),
+                optional("logo", Schema::String),
+            ])),
+        ),
+        optional("displayMode", Schema::IntegerEnum(&[0, 1])),
+    ])
+}
+
+/// 已知配置键的结构注册表，未注册的键不做校验
+static SCHEMAS: Lazy<HashMap<&'static str, Schema>> = Lazy::new(|| {
+    HashMap::from([
+        (
+            "settingConfig",
+            object(vec![
+                optional("autoStart", Schema::Bool),
+                optional("clipboardListening", Schema::Bool),
+                optional("hotkey", hotkey_schema()),
+            ]),
+        ),
+        ("appConfig", select_list_schema()),
+        ("webConfig", select_list_schema()),
+        (
+            "quickInputConfig",
+            array(object(vec![
+                required("id", Schema::Number),
+                required("text", Schema::String),
+            ])),
+        ),
+        (
+            "themeConfig",
+            object(vec![
+                required("mode", Schema::StringEnum(&["light", "dark", "custom"])),
+                optional("currentThemeId", Schema::String),
+                optional("customColors", object(vec![])),
+            ]),
+        ),
+        (
+            "searchConfig",
+            object(vec![
+                optional("searchType", Schema::StringEnum(&["web", "file"])),
+                optional("selectedEngine", Schema::String),
+                optional(
+                    "searchOptions",
+                    object(vec![optional("paths", array(Schema::String))]),
+                ),
+            ]),
+        ),
+        (
+            "position",
+            object(vec![
+                required("x", Schema::Number),
+                required("y", Schema::Number),
+            ]),
+        ),
+        (
+            "backupConfig",
+            object(vec![
+                optional("autoBackup", Schema::Bool),
+                optional("intervalHours", Schema::Integer(Some(1))),
+                optional("retention", Schema::Integer(Some(1))),
+            ]),
+        ),
+        (
+            "pluginUpdateConfig",
+            object(vec![
+                optional("autoCheck", Schema::Bool),
+                optional("intervalHours", Schema::Integer(Some(1))),
+                optional("indexUrl", Schema::String),
+            ]),
+        ),
+    ])
+});
+
+/// 无效配置的修复记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairEntry {
+    pub key: String,
+    pub errors: Vec<FieldError>,
+}
+
+/// 配置修复报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    pub dry_run: bool,
+    /// 校验失败的配置，非 dry_run 时这些键已被重置
+    pub invalid: Vec<RepairEntry>,
+}
+
+pub struct ConfigSchema;
+
+impl ConfigSchema {
+    /// 校验配置键的值，返回所有字段级错误
+    pub fn validate(key: &str, value: &Value) -> Result<(), Vec<FieldError>> {
+        let Some(schema) = SCHEMAS.get(key) else {
+            return Ok(());
+        };
+
+        let mut errors = Vec::new();
+        check(schema, value, key, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 将字段错误拼接为一条消息
+    pub fn format_errors(errors: &[FieldError]) -> String {
+        let details: Vec<String> = errors
+            .iter()
+            .map(|error| format!("{}: {}", error.path, error.message))
+            .collect();
+        format!("配置校验失败: {}", details.join("; "))
+    }
+
+    /// 检查已存储的配置，`dry_run` 为 false 时删除无效的值，使其回到默认配置
+    pub fn repair(dry_run: bool, source: &str) -> AppResult<RepairReport> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM config")
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+
+        let mut invalid = Vec::new();
+        for row in rows {
+            let (key, json_str) =
+                row.map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?;
+            let result = match serde_json::from_str::<Value>(&json_str) {
+                Ok(value) => Self::validate(&key, &value),
+                Err(e) => Err(vec![FieldError {
+                    path: key.clone(),
+                    message: format!("不是有效的 JSON: {}", e),
+                }]),
+            };
+            if let Err(errors) = result {
+                invalid.push(RepairEntry { key, errors });
+            }
+        }
+
+        if !dry_run && !invalid.is_empty() {
+            let keys: Vec<String> = invalid.iter().map(|entry| entry.key.clone()).collect();
+            let source = source.to_string();
+            execute_write(move |conn| {
+                let tx = conn
+                    .transaction()
+                    .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+                for key in &keys {
+                    ConfigHistory::write(&tx, key, None, &source)
+                        .map_err(|e| AppError::Error(format!("重置配置 {} 失败: {}", key, e)))?;
+                }
+                tx.commit()
+                    .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+            })?;
+        }
+
+        Ok(RepairReport { dry_run, invalid })
+    }
+}
+
+fn check(schema: &Schema, value: &Value, path: &str, errors: &mut Vec<FieldError>) {
+    match schema {
+        Schema::Bool if !value.is_boolean() => push_error(errors, path, "应为布尔值".to_string()),
+        Schema::Number if !value.is_number() => push_error(errors, path, "应为数字".to_string()),
+        Schema::Integer(min) => match value.as_i64() {
+            None => push_error(errors, path, "应为整数".to_string()),
+            Some(number) => {
+                if let Some(min) = min.filter(|min| number < *min) {
+                    push_error(errors, path, format!("不能小于 {}", min));
+                }
+            }
+        },
+        Schema::String if !value.is_string() => push_error(errors, path, "应为字符串".to_string()),
+        Schema::StringEnum(options) => {
+            if !value.as_str().is_some_and(|s| options.contains(&s)) {
+                push_error(errors, path, format!("应为以下值之一: {}", options.join(", ")));
+            }
+        }
+        Schema::IntegerEnum(options) => {
+            if !value.as_i64().is_some_and(|n| options.contains(&n)) {
+                let options: Vec<String> = options.iter().map(|n| n.to_string()).collect();
+                push_error(errors, path, format!("应为以下值之一: {}", options.join(", ")));
+            }
+        }
+        Schema::Array(item) => match value.as_array() {
+            None => push_error(errors, path, "应为数组".to_string()),
+            Some(items) => {
+                for (index, item_value) in items.iter().enumerate() {
+                    check(item, item_value, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        },
+        Schema::Object { fields, additional } => match value.as_object() {
+            None => push_error(errors, path, "应为对象".to_string()),
+            Some(map) => {
+                for field in fields {
+                    let field_path = format!("{}.{}", path, field.name);
+                    match map.get(field.name) {
+                        Some(field_value) => check(&field.schema, field_value, &field_path, errors),
+                        None if field.required => {
+                            push_error(errors, &field_path, "缺少必填字段".to_string())
+                        }
+                        None => {}
+                    }
+                }
+                if let Some(additional) = additional {
+                    for (name, field_value) in map {
+                        if fields.iter().all(|field| field.name != name) {
+                            check(additional, field_value, &format!("{}.{}", path, name), errors);
+                        }
+                    }
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+fn push_error(errors: &mut Vec<FieldError>, path: &str, message: String) {
+    errors.push(FieldError {
+        path: path.to_string(),
+        message,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn error_paths(key: &str, value: Value) -> Vec<String> {
+        ConfigSchema::validate(key, &value)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.path)
+            .collect()
+    }
+
+    #[test]
+    fn unknown_keys_are_not_validated() {
+        assert!(ConfigSchema::validate("customKey", &json!("anything")).is_ok());
+    }
+
+    #[test]
+    fn accepts_valid_values() {
+        let setting = json!({
+            "autoStart": true,
+            "hotkey": {
+                "enabled": true,
+                "togglePanel": { "enabled": true, "key": "Alt+Space" }
+            }
+        });
+        assert!(ConfigSchema::validate("settingConfig", &setting).is_ok());
+        assert!(ConfigSchema::validate("themeConfig", &json!({ "mode": "dark" })).is_ok());
+        assert!(ConfigSchema::validate("position", &json!({ "x": 1.5, "y": -2 })).is_ok());
+    }
+
+    #[test]
+    fn reports_every_invalid_field_with_its_path() {
+        let value = json!({
+            "dataList": [
+                { "id": 1, "title": "a", "path": "/a" },
+                { "id": "2", "path": 3 }
+            ],
+            "displayMode": 2
+        });
+        assert_eq!(
+            error_paths("appConfig", value),
+            [
+                "appConfig.dataList[1].id",
+                "appConfig.dataList[1].title",
+                "appConfig.dataList[1].path",
+                "appConfig.displayMode",
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_additional_hotkey_fields() {
+        let value = json!({
+            "hotkey": {
+                "enabled": true,
+                "togglePanel": { "enabled": "yes" }
+            }
+        });
+        assert_eq!(
+            error_paths("settingConfig", value),
+            [
+                "settingConfig.hotkey.togglePanel.enabled",
+                "settingConfig.hotkey.togglePanel.key",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_enums_and_integer_minimum() {
+        let errors = ConfigSchema::validate("themeConfig", &json!({ "mode": "blue" })).unwrap_err();
+        assert_eq!(errors[0].message, "应为以下值之一: light, dark, custom");
+
+        let errors = ConfigSchema::validate(
+            "backupConfig",
+            &json!({ "intervalHours": 0, "retention": 1.5 }),
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "不能小于 1");
+        assert_eq!(errors[1].message, "应为整数");
+    }
+
+    #[test]
+    fn rejects_wrong_top_level_type() {
+        assert_eq!(
+            error_paths("quickInputConfig", json!({})),
+            ["quickInputConfig"]
+        );
+    }
+
+    #[test]
+    fn formats_errors_into_one_message() {
+        let errors = ConfigSchema::validate("position", &json!({ "x": "1" })).unwrap_err();
+        assert_eq!(
+            ConfigSchema::format_errors(&errors),
+            "配置校验失败: position.x: 应为数字; position.y: 缺少必填字段"
+        );
+    }
+}
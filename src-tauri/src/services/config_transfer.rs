@@ -0,0 +1,542 @@
+use crate::services::config_history::ConfigHistory;
+use crate::services::config_schema::ConfigSchema;
+use crate::services::database::{execute_write, get_db_pool, PLUGIN_HOST_INFO_KEYS};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::get_myhelper_path;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 导出文档格式版本，结构不兼容变更时递增
+pub const TRANSFER_SCHEMA_VERSION: u32 = 1;
+
+/// 可移植路径前缀，代表 MyHelper 数据目录
+const PORTABLE_DATA_PREFIX: &str = "${MYHELPER_DATA}";
+/// 随文档一起导出的资源目录（图标等）
+const ASSET_DIR: &str = "Image";
+/// 单个资源文件的大小上限
+const MAX_ASSET_SIZE: u64 = 2 * 1024 * 1024;
+/// 不允许导出的配置键（账号信息等）
+const PRIVATE_CONFIG_KEYS: [&str; 1] = ["userConfig"];
+
+/// 导出范围
+///
+/// 字段为空表示不导出该部分，空数组表示导出该部分的全部条目
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSelection {
+    /// config 表中的键
+    pub config: Option<Vec<String>>,
+    /// plugin_config 表中的插件 windowId
+    pub plugin_config: Option<Vec<String>>,
+    /// pet_config 表中的配置类型
+    pub pet_config: Option<Vec<String>>,
+}
+
+/// 导出文档
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDocument {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub exported_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<BTreeMap<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin_config: Option<BTreeMap<String, PluginConfigEntry>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pet_config: Option<BTreeMap<String, Value>>,
+    /// 被配置引用的资源文件，键为相对于数据目录的路径，值为 base64 内容
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assets: BTreeMap<String, String>,
+}
+
+/// 导出的插件配置
+///
+/// info 中由宿主维护的字段（校验结果、能力授权等）和按插件清单生成的 data 不导出，
+/// 旧版本文档中的这些字段在导入时忽略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginConfigEntry {
+    pub info: Value,
+    pub config: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// 只导入本地不存在的条目，冲突时保留本地值
+    Merge,
+    /// 导入的条目覆盖本地同名条目
+    Replace,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// 新增的条目，格式为 `表.键`
+    pub added: Vec<String>,
+    /// 被覆盖的条目
+    pub updated: Vec<String>,
+    /// 内容一致无需处理的条目
+    pub unchanged: Vec<String>,
+    /// 冲突或被跳过的条目
+    pub conflicts: Vec<ImportConflict>,
+    /// 写入的资源文件
+    pub assets: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflict {
+    pub key: String,
+    pub reason: String,
+}
+
+pub struct ConfigTransfer;
+
+impl ConfigTransfer {
+    /// 导出配置
+    pub fn export(selection: &ExportSelection) -> AppResult<ConfigDocument> {
+        let data_dir = get_myhelper_path().map_err(AppError::from)?;
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let mut assets = BTreeMap::new();
+
+        let config = match &selection.config {
+            Some(keys) => {
+                let mut section = BTreeMap::new();
+                let mut stmt = conn
+                    .prepare("SELECT key, value FROM config ORDER BY key")
+                    .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })
+                    .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+                for row in rows {
+                    let (key, value) =
+                        row.map_err(|e| AppError::Error(format!("读取配置失败: {}", e)))?;
+                    if PRIVATE_CONFIG_KEYS.contains(&key.as_str())
+                        || (!keys.is_empty() && !keys.contains(&key))
+                    {
+                        continue;
+                    }
+                    let value = parse_json(&value, &key)?;
+                    section.insert(key, to_portable(value, &data_dir, &mut assets));
+                }
+                Some(section)
+            }
+            None => None,
+        };
+
+        let plugin_config = match &selection.plugin_config {
+            Some(ids) => {
+                let mut section = BTreeMap::new();
+                let mut stmt = conn
+                    .prepare("SELECT window_id, info, config FROM plugin_config ORDER BY window_id")
+                    .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                        ))
+                    })
+                    .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+                for row in rows {
+                    let (window_id, info, config) =
+                        row.map_err(|e| AppError::Error(format!("读取插件配置失败: {}", e)))?;
+                    if !ids.is_empty() && !ids.contains(&window_id) {
+                        continue;
+                    }
+                    let mut info = parse_json(&info, &window_id)?;
+                    strip_host_info(&mut info);
+                    let entry = PluginConfigEntry {
+                        info: to_portable(info, &data_dir, &mut assets),
+                        config: to_portable(
+                            parse_json(&config, &window_id)?,
+                            &data_dir,
+                            &mut assets,
+                        ),
+                    };
+                    section.insert(window_id, entry);
+                }
+                Some(section)
+            }
+            None => None,
+        };
+
+        let pet_config = match &selection.pet_config {
+            Some(types) => {
+                let mut section = BTreeMap::new();
+                let mut stmt = conn
+                    .prepare("SELECT config_type, config_data FROM pet_config ORDER BY config_type")
+                    .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })
+                    .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+                for row in rows {
+                    let (config_type, data) =
+                        row.map_err(|e| AppError::Error(format!("读取宠物配置失败: {}", e)))?;
+                    if !types.is_empty() && !types.contains(&config_type) {
+                        continue;
+                    }
+                    let value = parse_json(&data, &config_type)?;
+                    section.insert(config_type, to_portable(value, &data_dir, &mut assets));
+                }
+                Some(section)
+            }
+            None => None,
+        };
+
+        Ok(ConfigDocument {
+            schema_version: TRANSFER_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            config,
+            plugin_config,
+            pet_config,
+            assets,
+        })
+    }
+
+    /// 导入配置
+    ///
+    /// `dry_run` 为 true 时只返回导入报告，不写入任何数据
+    pub fn import(
+        document: ConfigDocument,
+        mode: ImportMode,
+        dry_run: bool,
+        source: &str,
+    ) -> AppResult<ImportReport> {
+        if document.schema_version > TRANSFER_SCHEMA_VERSION {
+            return Err(AppError::Error(format!(
+                "配置文档版本 {} 高于当前支持的版本 {}",
+                document.schema_version, TRANSFER_SCHEMA_VERSION
+            )));
+        }
+
+        let data_dir = get_myhelper_path().map_err(AppError::from)?;
+        let source = source.to_string();
+        execute_write(move |conn| {
+            Self::apply_import(conn, document, mode, dry_run, &data_dir, &source)
+        })
+    }
+
+    /// 在写入线程中执行导入，所有数据库写入处于同一事务
+    fn apply_import(
+        conn: &mut Connection,
+        document: ConfigDocument,
+        mode: ImportMode,
+        dry_run: bool,
+        data_dir: &Path,
+        source: &str,
+    ) -> AppResult<ImportReport> {
+        let mut report = ImportReport {
+            dry_run,
+            ..Default::default()
+        };
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+
+        for (key, value) in document.config.unwrap_or_default() {
+            let report_key = format!("config.{}", key);
+            if PRIVATE_CONFIG_KEYS.contains(&key.as_str()) {
+                report.conflicts.push(ImportConflict {
+                    key: report_key,
+                    reason: "该配置不允许导入".to_string(),
+                });
+                continue;
+            }
+
+            let value = from_portable(value, data_dir);
+            if let Err(errors) = ConfigSchema::validate(&key, &value) {
+                report.conflicts.push(ImportConflict {
+                    key: report_key,
+                    reason: ConfigSchema::format_errors(&errors),
+                });
+                continue;
+            }
+            let existing: Option<String> = tx
+                .query_row("SELECT value FROM config WHERE key = ?1", [&key], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .map_err(|e| AppError::Error(format!("读取配置失败: {}", e)))?;
+            let existing = existing.map(|v| parse_json(&v, &key)).transpose()?;
+
+            if Self::resolve(&mut report, report_key, existing.as_ref(), &value, mode) && !dry_run {
+                ConfigHistory::write(&tx, &key, Some(&value), source)?;
+            }
+        }
+
+        for (window_id, entry) in document.plugin_config.unwrap_or_default() {
+            let report_key = format!("pluginConfig.{}", window_id);
+            let existing = tx
+                .query_row(
+                    "SELECT info, config FROM plugin_config WHERE window_id = ?1",
+                    [&window_id],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()
+                .map_err(|e| AppError::Error(format!("读取插件配置失败: {}", e)))?;
+            // 插件配置只导入到本机已安装的插件，窗口数据由插件清单生成，不会被导入
+            let Some((local_info, local_config)) = existing else {
+                report.conflicts.push(ImportConflict {
+                    key: report_key,
+                    reason: "插件未安装".to_string(),
+                });
+                continue;
+            };
+            let mut local_info = parse_json(&local_info, &window_id)?;
+            let local_config = parse_json(&local_config, &window_id)?;
+
+            // 宿主维护的字段保留本地值，忽略文档中的值
+            let mut info = from_portable(entry.info, data_dir);
+            let config = from_portable(entry.config, data_dir);
+            strip_host_info(&mut info);
+            let host_info = strip_host_info(&mut local_info);
+
+            let existing = serde_json::json!({ "info": local_info, "config": local_config });
+            let incoming = serde_json::json!({ "info": info, "config": config });
+            if Self::resolve(&mut report, report_key, Some(&existing), &incoming, mode) && !dry_run
+            {
+                if let Some(info) = info.as_object_mut() {
+                    info.extend(host_info);
+                }
+                tx.execute(
+                    "UPDATE plugin_config SET info = ?2, config = ?3 WHERE window_id = ?1",
+                    params![window_id, to_json_string(&info)?, to_json_string(&config)?],
+                )
+                .map_err(|e| AppError::Error(format!("保存插件配置失败: {}", e)))?;
+            }
+        }
+
+        for (config_type, value) in document.pet_config.unwrap_or_default() {
+            let report_key = format!("petConfig.{}", config_type);
+            let value = from_portable(value, data_dir);
+            let existing: Option<String> = tx
+                .query_row(
+                    "SELECT config_data FROM pet_config WHERE config_type = ?1",
+                    [&config_type],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| AppError::Error(format!("读取宠物配置失败: {}", e)))?;
+            let existing = existing.map(|v| parse_json(&v, &config_type)).transpose()?;
+
+            if Self::resolve(&mut report, report_key, existing.as_ref(), &value, mode) && !dry_run {
+                tx.execute(
+                    "INSERT OR REPLACE INTO pet_config (config_type, config_data, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+                    params![config_type, to_json_string(&value)?],
+                )
+                .map_err(|e| AppError::Error(format!("保存宠物配置失败: {}", e)))?;
+            }
+        }
+
+        // 先写入资源文件，确保配置引用的图标在提交后可用
+        for (relative, content) in &document.assets {
+            let Some(path) = asset_path(data_dir, relative) else {
+                report.conflicts.push(ImportConflict {
+                    key: format!("assets.{}", relative),
+                    reason: "资源路径无效".to_string(),
+                });
+                continue;
+            };
+            let bytes = STANDARD
+                .decode(content)
+                .map_err(|e| AppError::Error(format!("解析资源文件 {} 失败: {}", relative, e)))?;
+
+            let should_write = match fs::read(&path) {
+                Ok(local) if local == bytes => false,
+                Ok(_) => mode == ImportMode::Replace,
+                Err(_) => true,
+            };
+            if !should_write {
+                continue;
+            }
+            if !dry_run {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| AppError::Error(format!("创建目录失败: {}", e)))?;
+                }
+                fs::write(&path, &bytes).map_err(|e| {
+                    AppError::Error(format!("写入资源文件 {} 失败: {}", relative, e))
+                })?;
+            }
+            report.assets.push(relative.clone());
+        }
+
+        if !dry_run {
+            tx.commit()
+                .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
+        }
+
+        Ok(report)
+    }
+
+    /// 根据导入模式记录结果，返回是否需要写入
+    fn resolve(
+        report: &mut ImportReport,
+        key: String,
+        existing: Option<&Value>,
+        incoming: &Value,
+        mode: ImportMode,
+    ) -> bool {
+        match existing {
+            None => {
+                report.added.push(key);
+                true
+            }
+            Some(local) if local == incoming => {
+                report.unchanged.push(key);
+                false
+            }
+            Some(_) if mode == ImportMode::Replace => {
+                report.updated.push(key);
+                true
+            }
+            Some(_) => {
+                report.conflicts.push(ImportConflict {
+                    key,
+                    reason: "本地已存在不同的值，合并模式下保留本地值".to_string(),
+                });
+                false
+            }
+        }
+    }
+}
+
+fn parse_json(content: &str, key: &str) -> AppResult<Value> {
+    serde_json::from_str(content)
+        .map_err(|e| AppError::Error(format!("解析配置 {} 失败: {}", key, e)))
+}
+
+fn to_json_string(value: &Value) -> AppResult<String> {
+    serde_json::to_string(value).map_err(|e| AppError::Error(format!("序列化配置失败: {}", e)))
+}
+
+/// 移除 info 中由宿主维护的字段，返回被移除的字段
+fn strip_host_info(info: &mut Value) -> Map<String, Value> {
+    let mut removed = Map::new();
+    if let Some(info) = info.as_object_mut() {
+        for key in PLUGIN_HOST_INFO_KEYS {
+            if let Some(value) = info.remove(key) {
+                removed.insert(key.to_string(), value);
+            }
+        }
+    }
+    removed
+}
+
+/// 统一使用 `/` 作为分隔符，便于跨平台比较
+fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// 把指向数据目录的绝对路径改写为可移植引用，并收集被引用的资源文件
+fn to_portable(value: Value, data_dir: &Path, assets: &mut BTreeMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => {
+            let base = format!(
+                "{}/",
+                normalize_separators(&data_dir.to_string_lossy()).trim_end_matches('/')
+            );
+            let normalized = normalize_separators(&s);
+            match normalized.strip_prefix(&base) {
+                Some(relative) => {
+                    collect_asset(data_dir, relative, assets);
+                    Value::String(format!("{}/{}", PORTABLE_DATA_PREFIX, relative))
+                }
+                None => Value::String(s),
+            }
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| to_portable(item, data_dir, assets))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, to_portable(v, data_dir, assets)))
+                .collect::<Map<_, _>>(),
+        ),
+        other => other,
+    }
+}
+
+/// 把可移植引用还原为本机数据目录下的绝对路径
+fn from_portable(value: Value, data_dir: &Path) -> Value {
+    match value {
+        Value::String(s) => match s.strip_prefix(&format!("{}/", PORTABLE_DATA_PREFIX)) {
+            Some(relative) => {
+                let mut path = data_dir.to_path_buf();
+                path.extend(relative.split('/').filter(|part| !part.is_empty()));
+                Value::String(path.to_string_lossy().to_string())
+            }
+            None => Value::String(s),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| from_portable(item, data_dir))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, from_portable(v, data_dir)))
+                .collect::<Map<_, _>>(),
+        ),
+        other => other,
+    }
+}
+
+/// 把资源目录下被引用的文件以 base64 形式加入导出文档
+fn collect_asset(data_dir: &Path, relative: &str, assets: &mut BTreeMap<String, String>) {
+    if assets.contains_key(relative) || !relative.starts_with(&format!("{}/", ASSET_DIR)) {
+        return;
+    }
+    let Some(path) = asset_path(data_dir, relative) else {
+        return;
+    };
+    let is_small_file = fs::metadata(&path)
+        .map(|m| m.is_file() && m.len() <= MAX_ASSET_SIZE)
+        .unwrap_or(false);
+    if !is_small_file {
+        return;
+    }
+    if let Ok(bytes) = fs::read(&path) {
+        assets.insert(relative.to_string(), STANDARD.encode(bytes));
+    }
+}
+
+/// 校验资源的相对路径并转换为本机路径，只允许资源目录内的路径
+fn asset_path(data_dir: &Path, relative: &str) -> Option<PathBuf> {
+    let parts: Vec<&str> = relative.split('/').collect();
+    let is_safe = parts.first() == Some(&ASSET_DIR)
+        && parts.len() > 1
+        && parts.iter().all(|part| {
+            !part.is_empty() && *part != "." && *part != ".." && !part.contains(['\\', ':'])
+        });
+    if !is_safe {
+        return None;
+    }
+    let mut path = data_dir.to_path_buf();
+    path.extend(parts);
+    Some(path)
+}
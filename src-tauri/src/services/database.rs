@@ -1,81 +1,349 @@
+use crate::services::logger::{LogEntry, Logger};
 use crate::utils::error::{AppError, AppResult};
 use crate::utils::path::get_myhelper_path;
 use once_cell::sync::OnceCell;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result, MAIN_DB};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use tokio::sync::oneshot;
 
 type DatabasePool = Pool<SqliteConnectionManager>;
+type WriteJob = Box<dyn FnOnce(&mut Connection) + Send>;
 
 static DB_POOL: OnceCell<DatabasePool> = OnceCell::new();
+static DB_WRITER: OnceCell<Sender<WriteJob>> = OnceCell::new();
 
-pub fn init_database() -> Result<()> {
-    let app_dir =
-        get_myhelper_path().map_err(|e| rusqlite::Error::InvalidPath(PathBuf::from(e)))?;
+/// 每个连接打开时执行的设置
+///
+/// - WAL 模式允许读写并发，读操作不会被写操作阻塞
+/// - busy_timeout 让短暂的锁冲突等待重试而不是直接返回 SQLITE_BUSY
+const CONNECTION_PRAGMAS: &str = "
+    PRAGMA journal_mode = WAL;
+    PRAGMA synchronous = NORMAL;
+    PRAGMA busy_timeout = 5000;
+    PRAGMA foreign_keys = ON;
+";
+
+/// 数据库迁移
+///
+/// 迁移按 `version` 升序执行，已执行的最新版本记录在 `PRAGMA user_version` 中。
+/// 新增表结构变更时只能在末尾追加新的迁移，不能修改已发布的迁移。
+struct Migration {
+    version: i32,
+    description: &'static str,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "初始化 config、plugin_config、pet_config 表",
+        sql: include_str!("migrations/0001_init.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "新增配置方案 profile、profile_config 表",
+        sql: include_str!("migrations/0002_profiles.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "新增配置变更历史 config_history 表",
+        sql: include_str!("migrations/0003_config_history.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "新增权限拒绝审计 permission_audit 表",
+        sql: include_str!("migrations/0004_permission_audit.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "plugin_config 表新增插件启用状态 enabled 列",
+        sql: include_str!("migrations/0005_plugin_enabled.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "新增插件键值存储 plugin_storage 表",
+        sql: include_str!("migrations/0006_plugin_storage.sql"),
+    },
+];
+
+/// 当前程序支持的数据库版本
+pub fn latest_schema_version() -> i32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub fn init_database() -> AppResult<()> {
+    let app_dir = get_myhelper_path().map_err(AppError::from)?;
     let db_path = app_dir.join("myhelper.db");
-    ensure_parent_dir_exists(&db_path);
+    ensure_parent_dir_exists(&db_path)?;
+    let db_existed = db_path.exists();
 
     // 创建连接池配置
-    let manager = SqliteConnectionManager::file(&db_path);
+    let manager = SqliteConnectionManager::file(&db_path)
+        .with_init(|conn| configure_connection(conn));
     let pool = Pool::builder()
         .max_size(10)  // 最大连接数
         .min_idle(Some(2))  // 最小空闲连接数
         .max_lifetime(Some(std::time::Duration::from_secs(3600))) // 连接最大存活时间1小时
         .idle_timeout(Some(std::time::Duration::from_secs(600))) // 空闲超时10分钟
         .build(manager)
-        .map_err(|e| rusqlite::Error::SqliteFailure(
-            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
-            Some(format!("创建连接池失败: {}", e))
-        ))?;
+        .map_err(|e| AppError::Error(format!("创建连接池失败: {}", e)))?;
 
-    // 初始化数据库表
+    // 执行数据库迁移
     {
-        let conn = pool.get().map_err(|e| rusqlite::Error::SqliteFailure(
-            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
-            Some(format!("获取连接失败: {}", e))
-        ))?;
+        let mut conn = pool
+            .get()
+            .map_err(|e| AppError::Error(format!("获取连接失败: {}", e)))?;
 
-        // 创建配置表
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS config (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-            [],
-        )?;
+        let current_version = read_schema_version(&conn)?;
+        let latest_version = latest_schema_version();
 
-        // 创建插件配置表
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS plugin_config (
-                window_id TEXT PRIMARY KEY,
-                info TEXT NOT NULL,
-                config TEXT NOT NULL,
-                data TEXT NOT NULL
-            )",
-            [],
-        )?;
+        if current_version > latest_version {
+            return Err(AppError::Error(format!(
+                "数据库版本 {} 高于当前程序支持的版本 {}，请升级 MyHelper",
+                current_version, latest_version
+            )));
+        }
 
-        // 创建宠物配置表
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS pet_config (
-                config_type TEXT PRIMARY KEY,
-                config_data TEXT NOT NULL,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-            [],
-        )?;
+        if current_version < latest_version {
+            // 迁移前自动备份已有的数据库
+            let backup_path = if db_existed {
+                Some(backup_database(&conn, &db_path)?)
+            } else {
+                None
+            };
+
+            if let Err(e) = run_migrations(&mut conn, current_version) {
+                // 关闭所有连接后再用备份覆盖数据库文件
+                drop(conn);
+                drop(pool);
+                if let Some(backup_path) = backup_path {
+                    restore_database_backup(&db_path, &backup_path).map_err(|restore_err| {
+                        AppError::Error(format!(
+                            "{}；从备份恢复失败: {}，备份文件: {}",
+                            e,
+                            restore_err,
+                            backup_path.display()
+                        ))
+                    })?;
+                }
+                return Err(AppError::Error(format!("{}，数据库已回滚到迁移前状态", e)));
+            }
+        }
     }
 
+    start_writer(&db_path)?;
     DB_POOL.get_or_init(|| pool);
     Ok(())
 }
 
-fn ensure_parent_dir_exists(path: &PathBuf) {
+fn configure_connection(conn: &Connection) -> Result<()> {
+    conn.execute_batch(CONNECTION_PRAGMAS)
+}
+
+/// 启动专用的写入线程
+///
+/// 所有写操作都通过该线程上的唯一连接串行执行，避免多个连接同时写入导致 SQLITE_BUSY
+fn start_writer(db_path: &Path) -> AppResult<()> {
+    if DB_WRITER.get().is_some() {
+        return Ok(());
+    }
+
+    let mut conn = Connection::open(db_path)
+        .and_then(|conn| configure_connection(&conn).map(|_| conn))
+        .map_err(|e| AppError::Error(format!("打开写入连接失败: {}", e)))?;
+    let (sender, receiver) = mpsc::channel::<WriteJob>();
+
+    std::thread::Builder::new()
+        .name("db-writer".to_string())
+        .spawn(move || {
+            for job in receiver {
+                job(&mut conn);
+            }
+        })
+        .map_err(|e| AppError::Error(format!("启动数据库写入线程失败: {}", e)))?;
+
+    let _ = DB_WRITER.set(sender);
+    Ok(())
+}
+
+/// 在写入线程上执行写操作并等待结果
+///
+/// 写操作不能嵌套调用本函数，否则会互相等待。会阻塞当前线程，
+/// 异步上下文中使用 [`execute_write_async`]
+///
+/// # Example
+///
+/// ```rust
+/// execute_write(move |conn| {
+///     conn.execute("DELETE FROM config WHERE key = ?1", [key])
+///         .map_err(|e| AppError::Error(format!("删除配置值失败: {}", e)))
+/// })?;
+/// ```
+pub fn execute_write<T, F>(job: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> AppResult<T> + Send + 'static,
+{
+    let (result_sender, result_receiver) = mpsc::sync_channel(1);
+    submit_write(move |conn| {
+        let _ = result_sender.send(run_write_job(conn, job));
+    })?;
+
+    result_receiver
+        .recv()
+        .map_err(|_| AppError::Error("数据库写入线程已停止".into()))?
+}
+
+/// 在写入线程上执行写操作，等待结果时不阻塞异步运行时
+///
+/// 与 [`execute_write`] 共用写入线程，同样不能在写操作中嵌套调用
+pub async fn execute_write_async<T, F>(job: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> AppResult<T> + Send + 'static,
+{
+    let (result_sender, result_receiver) = oneshot::channel();
+    submit_write(move |conn| {
+        let _ = result_sender.send(run_write_job(conn, job));
+    })?;
+
+    result_receiver
+        .await
+        .map_err(|_| AppError::Error("数据库写入线程已停止".into()))?
+}
+
+/// 在写入线程上执行写操作，不等待结果
+///
+/// 用于审计记录等不影响调用方结果的写入，写操作失败时记录到日志
+pub fn execute_write_detached<F>(job: F) -> AppResult<()>
+where
+    F: FnOnce(&mut Connection) -> AppResult<()> + Send + 'static,
+{
+    submit_write(move |conn| {
+        if let Err(e) = run_write_job(conn, job) {
+            let _ = Logger::write_log(LogEntry {
+                level: "error".to_string(),
+                message: "后台数据库写入失败".to_string(),
+                timestamp: String::new(),
+                details: Some(e.to_string()),
+            });
+        }
+    })
+}
+
+fn submit_write(job: impl FnOnce(&mut Connection) + Send + 'static) -> AppResult<()> {
+    DB_WRITER
+        .get()
+        .ok_or_else(|| AppError::Error("数据库未初始化".into()))?
+        .send(Box::new(job))
+        .map_err(|_| AppError::Error("数据库写入线程已停止".into()))
+}
+
+/// 执行单个写操作，写操作 panic 时向调用方返回错误，写入线程继续处理后续任务
+fn run_write_job<T>(
+    conn: &mut Connection,
+    job: impl FnOnce(&mut Connection) -> AppResult<T>,
+) -> AppResult<T> {
+    match catch_unwind(AssertUnwindSafe(|| job(conn))) {
+        Ok(result) => result,
+        Err(_) => {
+            // 未结束的事务在 panic 展开时已回滚，这里兜底处理手动开启的事务
+            if !conn.is_autocommit() {
+                let _ = conn.execute_batch("ROLLBACK");
+            }
+            Err(AppError::Error("数据库写入任务异常终止".into()))
+        }
+    }
+}
+
+/// 读取数据库当前的 schema 版本
+fn read_schema_version(conn: &Connection) -> AppResult<i32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| AppError::Error(format!("读取数据库版本失败: {}", e)))
+}
+
+/// 获取已打开数据库的 schema 版本
+pub fn current_schema_version() -> AppResult<i32> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+    read_schema_version(&conn)
+}
+
+/// 将已打开的数据库迁移到最新版本
+///
+/// 用于运行期间替换了数据库内容的场景（如从旧版本备份恢复）
+pub fn migrate_to_latest() -> AppResult<()> {
+    execute_write(|conn| {
+        let current_version = read_schema_version(conn)?;
+        run_migrations(conn, current_version)
+    })
+}
+
+/// 依次执行版本号大于 `current_version` 的迁移
+fn run_migrations(conn: &mut Connection, current_version: i32) -> AppResult<()> {
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current_version) {
+        apply_migration(conn, migration).map_err(|e| {
+            AppError::Error(format!(
+                "数据库迁移 v{}（{}）失败: {}",
+                migration.version, migration.description, e
+            ))
+        })?;
+
+        let _ = Logger::write_log(LogEntry {
+            level: "info".to_string(),
+            message: format!("数据库迁移完成: v{}", migration.version),
+            timestamp: String::new(),
+            details: Some(migration.description.to_string()),
+        });
+    }
+
+    Ok(())
+}
+
+/// 在事务中执行单个迁移并更新 user_version
+fn apply_migration(conn: &mut Connection, migration: &Migration) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute_batch(migration.sql)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+/// 通过 SQLite 在线备份接口备份数据库，WAL 中尚未合并的数据也会包含在内
+fn backup_database(conn: &Connection, db_path: &Path) -> AppResult<PathBuf> {
+    let backup_path = db_path.with_extension("db.bak");
+    conn.backup(MAIN_DB, &backup_path, None)
+        .map_err(|e| AppError::Error(format!("迁移前备份数据库失败: {}", e)))?;
+    Ok(backup_path)
+}
+
+/// 用备份文件覆盖数据库文件
+fn restore_database_backup(db_path: &Path, backup_path: &Path) -> AppResult<()> {
+    // 删除残留的日志文件，避免 SQLite 打开时把旧日志回放到恢复后的文件上
+    for suffix in ["db-journal", "db-wal", "db-shm"] {
+        let path = db_path.with_extension(suffix);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| AppError::Error(format!("删除数据库日志失败: {}", e)))?;
+        }
+    }
+    fs::copy(backup_path, db_path)
+        .map_err(|e| AppError::Error(format!("恢复数据库备份失败: {}", e)))?;
+    Ok(())
+}
+
+fn ensure_parent_dir_exists(path: &Path) -> AppResult<()> {
     if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).expect("无法创建数据库目录");
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::Error(format!("无法创建数据库目录: {}", e)))?;
     }
+    Ok(())
 }
 
 pub fn get_db_pool() -> &'static DatabasePool {
@@ -100,8 +368,84 @@ pub fn query_plugin_ids() -> AppResult<HashSet<String>> {
     Ok(window_ids)
 }
 
+/// 从数据库中查询所有已启用插件的 window_id
+pub fn query_enabled_plugin_ids() -> AppResult<HashSet<String>> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+
+    let mut stmt = conn
+        .prepare_cached("SELECT window_id FROM plugin_config WHERE enabled = 1")
+        .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+
+    let window_ids = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(window_ids)
+}
+
+/// 从数据库中查询所有开发模式插件的 window_id
+pub fn query_dev_plugin_ids() -> AppResult<HashSet<String>> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "SELECT window_id FROM plugin_config \
+             WHERE json_valid(info) AND json_extract(info, '$.dev') IS NOT NULL",
+        )
+        .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+
+    let window_ids = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(window_ids)
+}
+
+/// 查询插件的启用状态，插件不存在时返回 None
+pub fn query_plugin_enabled(window_id: &str) -> AppResult<Option<bool>> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+
+    conn.query_row(
+        "SELECT enabled FROM plugin_config WHERE window_id = ?1",
+        [window_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| AppError::Error(format!("读取插件启用状态失败: {}", e)))
+}
+
+/// 设置插件的启用状态
+///
+/// # Returns
+///
+/// * `AppResult<bool>` - 插件记录不存在时返回 false
+pub fn update_plugin_enabled(window_id: &str, enabled: bool) -> AppResult<bool> {
+    let window_id = window_id.to_string();
+    execute_write(move |conn| {
+        conn.execute(
+            "UPDATE plugin_config SET enabled = ?2 WHERE window_id = ?1",
+            params![window_id, enabled],
+        )
+        .map(|updated| updated > 0)
+        .map_err(|e| AppError::Error(format!("更新插件启用状态失败: {}", e)))
+    })
+}
+
 /// 批量插入插件配置
 ///
+/// 已有记录保持不变，避免覆盖同步期间写入的配置和启用状态。
+/// 新记录只有 `config.isEnabled` 为 false 时禁用，未设置时默认启用，与迁移 0005 的规则一致
+///
 /// # Arguments
 ///
 /// * `configs` - 插件配置数组，每项包含 (window_id, info, config, data)
@@ -109,34 +453,36 @@ pub fn query_plugin_ids() -> AppResult<HashSet<String>> {
 /// # Returns
 ///
 /// * `AppResult<()>` - 操作结果
-pub fn batch_insert_plugin_configs(configs: &[(String, String, String, String)]) -> AppResult<()> {
+pub async fn batch_insert_plugin_configs(configs: &[(String, String, String, String)]) -> AppResult<()> {
     if configs.is_empty() {
         return Ok(());
     }
 
-    let pool = get_db_pool();
-    let mut conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-    
-    let tx = conn
-        .transaction()
-        .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+    let configs = configs.to_vec();
+    execute_write_async(move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
 
-    {
-        let mut stmt = tx
-            .prepare_cached(
-                "INSERT INTO plugin_config (window_id, info, config, data) VALUES (?1, ?2, ?3, ?4)",
-            )
-            .map_err(|e| AppError::Error(format!("准备语句失败: {}", e)))?;
+        {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT INTO plugin_config (window_id, info, config, data, enabled) \
+                     VALUES (?1, ?2, ?3, ?4, CASE WHEN json_type(?3, '$.isEnabled') = 'false' THEN 0 ELSE 1 END) \
+                     ON CONFLICT(window_id) DO NOTHING",
+                )
+                .map_err(|e| AppError::Error(format!("准备语句失败: {}", e)))?;
 
-        for (window_id, info, config, data) in configs {
-            stmt.execute(params![window_id, info, config, data])
-                .map_err(|e| AppError::Error(format!("插入插件配置失败: {}", e)))?;
+            for (window_id, info, config, data) in &configs {
+                stmt.execute(params![window_id, info, config, data])
+                    .map_err(|e| AppError::Error(format!("插入插件配置失败: {}", e)))?;
+            }
         }
-    }
 
-    tx.commit()
-        .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
-    Ok(())
+        tx.commit()
+            .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+    })
+    .await
 }
 
 /// 批量删除插件配置
@@ -148,27 +494,310 @@ pub fn batch_insert_plugin_configs(configs: &[(String, String, String, String)])
 /// # Returns
 ///
 /// * `AppResult<()>` - 操作结果
-pub fn batch_remove_plugin_configs(window_ids: &[String]) -> AppResult<()> {
+pub async fn batch_remove_plugin_configs(window_ids: &[String]) -> AppResult<()> {
     if window_ids.is_empty() {
         return Ok(());
     }
 
-    let pool = get_db_pool();
-    let mut conn = pool.get().map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
-    
-    let tx = conn
-        .transaction()
-        .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+    let window_ids = window_ids.to_vec();
+    execute_write_async(move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+
+        for window_id in &window_ids {
+            tx.execute(
+                "DELETE FROM plugin_config WHERE window_id = ?1",
+                params![window_id],
+            )
+            .map_err(|e| AppError::Error(format!("删除插件配置失败: {}", e)))?;
+        }
+
+        tx.commit()
+            .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+    })
+    .await
+}
+
+/// 更新插件清单无效的标记
+///
+/// 在同一事务中清除所有插件的 info.invalid，再为 `invalid` 中的插件写入无效原因
+///
+/// # Arguments
+///
+/// * `invalid` - 清单无效的插件，每项包含 (window_id, 原因)
+///
+/// # Returns
+///
+/// * `AppResult<()>` - 操作结果
+pub async fn batch_update_plugin_invalid(invalid: &[(String, String)]) -> AppResult<()> {
+    let invalid = invalid.to_vec();
+    execute_write_async(move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
 
-    for window_id in window_ids {
         tx.execute(
-            "DELETE FROM plugin_config WHERE window_id = ?1",
+            "UPDATE plugin_config SET info = json_remove(info, '$.invalid') \
+             WHERE json_valid(info) AND json_extract(info, '$.invalid') IS NOT NULL",
+            [],
+        )
+        .map_err(|e| AppError::Error(format!("清除插件无效标记失败: {}", e)))?;
+
+        for (window_id, reason) in &invalid {
+            tx.execute(
+                "UPDATE plugin_config SET info = json_set(info, '$.invalid', ?2) \
+                 WHERE window_id = ?1 AND json_valid(info)",
+                params![window_id, reason],
+            )
+            .map_err(|e| AppError::Error(format!("标记插件无效失败: {}", e)))?;
+        }
+
+        tx.commit()
+            .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+    })
+    .await
+}
+
+/// 查询插件清单无效的原因，插件不存在或清单有效时返回 None
+pub fn query_plugin_invalid(window_id: &str) -> AppResult<Option<String>> {
+    let conn = get_db_pool()
+        .get()
+        .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+
+    conn.query_row(
+        "SELECT json_extract(info, '$.invalid') FROM plugin_config \
+         WHERE window_id = ?1 AND json_valid(info)",
+        [window_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| AppError::Error(format!("读取插件无效标记失败: {}", e)))
+}
+
+/// plugin_config.info 中由宿主维护的字段，前端写入插件配置时保留原值
+///
+/// - `verification`: 当前版本插件包的校验结果
+/// - `previous`: 可回滚的上一个版本，包含 `version` 和 `verification`
+/// - `grants`: 用户授予插件的能力
+/// - `dev`: 开发模式插件的本地目录或开发服务器地址
+/// - `storageQuota`: 插件键值存储的配额，单位为字节
+/// - `invalid`: 插件清单无效的原因，同步插件时写入
+pub const PLUGIN_HOST_INFO_KEYS: [&str; 6] = [
+    "verification",
+    "previous",
+    "grants",
+    "dev",
+    "storageQuota",
+    "invalid",
+];
+
+/// 插件安装或升级后更新插件配置，所有修改在同一事务中完成
+///
+/// # Arguments
+///
+/// * `window_id` - 插件ID
+/// * `verification` - 新版本插件包的校验结果
+/// * `grants` - 用户授予的能力，为 None 时保留已有的授权
+/// * `record` - 按新版本插件目录生成的 (info, config, data)，记录不存在时用于创建记录
+///
+/// # Returns
+///
+/// * `AppResult<()>` - 操作结果
+pub async fn save_installed_plugin(
+    window_id: &str,
+    verification: serde_json::Value,
+    grants: Option<Vec<String>>,
+    record: (serde_json::Value, serde_json::Value, serde_json::Value),
+) -> AppResult<()> {
+    let window_id = window_id.to_string();
+    execute_write_async(move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+        let (mut new_info, config, new_data) = record;
+
+        let (info, data) = match read_plugin_info_data(&tx, &window_id)? {
+            // 升级：当前版本成为可回滚的上一个版本，用户配置保持不变
+            Some((mut info, data)) => {
+                let previous = serde_json::json!({
+                    "version": data.get("version").cloned(),
+                    "verification": info.get("verification").cloned(),
+                });
+                if let Some(info) = info.as_object_mut() {
+                    info.insert("verification".to_string(), verification);
+                    info.insert("previous".to_string(), previous);
+                }
+                (info, new_data)
+            }
+            None => {
+                if let Some(info) = new_info.as_object_mut() {
+                    info.insert("verification".to_string(), verification);
+                }
+                tx.execute(
+                    "INSERT INTO plugin_config (window_id, info, config, data) VALUES (?1, '{}', ?2, '{}')",
+                    params![window_id, to_plugin_json(&config)?],
+                )
+                .map_err(|e| AppError::Error(format!("插入插件配置失败: {}", e)))?;
+                (new_info, new_data)
+            }
+        };
+
+        let mut info = info;
+        if let Some(info) = info.as_object_mut() {
+            // 新版本已通过校验
+            info.remove("invalid");
+            match grants {
+                Some(grants) => {
+                    info.insert("grants".to_string(), serde_json::json!(grants));
+                }
+                None => {
+                    info.entry("grants").or_insert_with(|| serde_json::json!([]));
+                }
+            }
+        }
+
+        write_plugin_info_data(&tx, &window_id, &info, &data)?;
+        tx.commit()
+            .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+    })
+    .await
+}
+
+/// 插件回滚后更新插件配置，交换当前版本与上一个版本的校验结果
+///
+/// # Arguments
+///
+/// * `window_id` - 插件ID
+/// * `data` - 按回滚后插件目录生成的窗口数据
+///
+/// # Returns
+///
+/// * `AppResult<()>` - 操作结果
+pub async fn save_rolled_back_plugin(window_id: &str, data: serde_json::Value) -> AppResult<()> {
+    let window_id = window_id.to_string();
+    execute_write_async(move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+        let (mut info, current_data) = read_plugin_info_data(&tx, &window_id)?
+            .ok_or_else(|| AppError::Error(format!("插件 {} 的配置记录不存在", window_id)))?;
+
+        if let Some(info) = info.as_object_mut() {
+            let verification = info
+                .get("previous")
+                .and_then(|previous| previous.get("verification"))
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            let previous = serde_json::json!({
+                "version": current_data.get("version").cloned(),
+                "verification": info.get("verification").cloned(),
+            });
+            info.insert("verification".to_string(), verification);
+            info.insert("previous".to_string(), previous);
+            info.remove("invalid");
+        }
+
+        write_plugin_info_data(&tx, &window_id, &info, &data)?;
+        tx.commit()
+            .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+    })
+    .await
+}
+
+/// 注册开发模式插件或更新其窗口数据，所有修改在同一事务中完成
+///
+/// # Arguments
+///
+/// * `window_id` - 插件ID
+/// * `dev` - 插件来源，记录在 info.dev 中
+/// * `grants` - 用户授予的能力，为 None 时保留已有的授权
+/// * `data` - 按插件清单生成的窗口数据
+///
+/// # Returns
+///
+/// * `AppResult<()>` - 操作结果
+pub async fn save_dev_plugin(
+    window_id: &str,
+    dev: serde_json::Value,
+    grants: Option<Vec<String>>,
+    data: serde_json::Value,
+) -> AppResult<()> {
+    let window_id = window_id.to_string();
+    execute_write_async(move |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+
+        let mut info = match read_plugin_info_data(&tx, &window_id)? {
+            Some((info, _)) => info,
+            None => {
+                tx.execute(
+                    "INSERT INTO plugin_config (window_id, info, config, data) VALUES (?1, '{}', '{}', '{}')",
+                    params![window_id],
+                )
+                .map_err(|e| AppError::Error(format!("插入插件配置失败: {}", e)))?;
+                serde_json::json!({})
+            }
+        };
+        if let Some(info) = info.as_object_mut() {
+            info.insert("dev".to_string(), dev);
+            match grants {
+                Some(grants) => {
+                    info.insert("grants".to_string(), serde_json::json!(grants));
+                }
+                None => {
+                    info.entry("grants").or_insert_with(|| serde_json::json!([]));
+                }
+            }
+        }
+
+        write_plugin_info_data(&tx, &window_id, &info, &data)?;
+        tx.commit()
+            .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+    })
+    .await
+}
+
+fn read_plugin_info_data(
+    conn: &Connection,
+    window_id: &str,
+) -> AppResult<Option<(serde_json::Value, serde_json::Value)>> {
+    let row: Option<(String, String)> = conn
+        .query_row(
+            "SELECT info, data FROM plugin_config WHERE window_id = ?1",
             params![window_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
-        .map_err(|e| AppError::Error(format!("删除插件配置失败: {}", e)))?;
-    }
+        .optional()
+        .map_err(|e| AppError::Error(format!("读取插件配置失败: {}", e)))?;
 
-    tx.commit()
-        .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
+    Ok(row.map(|(info, data)| {
+        let parse = |json: &str| {
+            serde_json::from_str::<serde_json::Value>(json)
+                .ok()
+                .filter(|value| value.is_object())
+                .unwrap_or_else(|| serde_json::json!({}))
+        };
+        (parse(&info), parse(&data))
+    }))
+}
+
+fn write_plugin_info_data(
+    conn: &Connection,
+    window_id: &str,
+    info: &serde_json::Value,
+    data: &serde_json::Value,
+) -> AppResult<()> {
+    conn.execute(
+        "UPDATE plugin_config SET info = ?2, data = ?3 WHERE window_id = ?1",
+        params![window_id, to_plugin_json(info)?, to_plugin_json(data)?],
+    )
+    .map_err(|e| AppError::Error(format!("更新插件配置失败: {}", e)))?;
     Ok(())
 }
+
+fn to_plugin_json(value: &serde_json::Value) -> AppResult<String> {
+    serde_json::to_string(value).map_err(|e| AppError::Error(format!("序列化插件配置失败: {}", e)))
+}
@@ -0,0 +1,20 @@
+-- 配置表
+CREATE TABLE IF NOT EXISTS config (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- 插件配置表
+CREATE TABLE IF NOT EXISTS plugin_config (
+    window_id TEXT PRIMARY KEY,
+    info TEXT NOT NULL,
+    config TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+-- 宠物配置表
+CREATE TABLE IF NOT EXISTS pet_config (
+    config_type TEXT PRIMARY KEY,
+    config_data TEXT NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,17 @@
+-- 配置方案表
+CREATE TABLE IF NOT EXISTS profile (
+    name TEXT PRIMARY KEY,
+    is_active INTEGER NOT NULL DEFAULT 0,
+    auto_switch TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 未激活配置方案的配置快照，激活方案的配置保存在 config 表中
+CREATE TABLE IF NOT EXISTS profile_config (
+    profile TEXT NOT NULL REFERENCES profile(name) ON UPDATE CASCADE ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (profile, key)
+);
+
+INSERT OR IGNORE INTO profile (name, is_active) VALUES ('default', 1);
@@ -0,0 +1,12 @@
+-- 配置变更历史，previous_value / new_value 为 NULL 表示键不存在
+CREATE TABLE IF NOT EXISTS config_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key TEXT NOT NULL,
+    previous_value TEXT,
+    new_value TEXT,
+    window_label TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_config_history_changed_at ON config_history (changed_at);
+CREATE INDEX IF NOT EXISTS idx_config_history_key ON config_history (key);
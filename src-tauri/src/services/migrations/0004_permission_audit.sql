@@ -0,0 +1,10 @@
+-- 被拒绝的命令调用，capability 为 NULL 表示按窗口标识拒绝
+CREATE TABLE IF NOT EXISTS permission_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    window_label TEXT NOT NULL,
+    command TEXT NOT NULL,
+    capability TEXT,
+    denied_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_permission_audit_denied_at ON permission_audit (denied_at);
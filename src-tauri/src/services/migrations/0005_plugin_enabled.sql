@@ -0,0 +1,7 @@
+-- 插件启用状态，禁用的插件保留文件和配置，但不能打开窗口、接收快捷键或执行启动钩子
+ALTER TABLE plugin_config ADD COLUMN enabled INTEGER NOT NULL DEFAULT 1;
+
+-- 沿用此前记录在 config.isEnabled 中的状态：只有 isEnabled 为 false 时禁用，未设置时视为启用，
+-- 与同步插件目录和保存插件配置时新记录的规则一致
+UPDATE plugin_config SET enabled = 0
+WHERE json_valid(config) AND json_type(config, '$.isEnabled') = 'false';
@@ -0,0 +1,11 @@
+-- 插件键值存储，value 为 JSON 文本或二进制数据，size 为键和值的字节数之和
+-- 不随插件记录级联删除，同步时删除记录不应丢失插件数据，存储只在卸载插件时显式清理
+CREATE TABLE IF NOT EXISTS plugin_storage (
+    window_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value BLOB NOT NULL,
+    binary INTEGER NOT NULL DEFAULT 0,
+    size INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (window_id, key)
+);
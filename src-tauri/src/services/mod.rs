@@ -1,4 +1,11 @@
+pub mod backup;
 pub mod config;
+pub mod config_events;
+pub mod config_history;
+pub mod config_schema;
+pub mod config_transfer;
 pub mod database;
 pub mod logger;
+pub mod permission;
+pub mod profile;
 pub mod fd_search;
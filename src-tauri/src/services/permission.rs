@@ -0,0 +1,267 @@
+use chrono::Local;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::mh_plugin::capability::has_capability;
+use crate::mh_plugin::manifest::PLUGIN_CAPABILITIES;
+use crate::services::database::{execute_write_detached, get_db_pool};
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+
+/// 最多保留的拒绝记录条数
+const MAX_AUDIT: i64 = 1000;
+
+/// 内置窗口的默认策略：能力 -> 拥有该能力的窗口
+///
+/// 插件窗口的能力来自用户授权，只能获得 [`PLUGIN_CAPABILITIES`] 中的能力
+const BUILTIN_POLICY: &[(&str, &[&str])] = &[
+    (
+        "config.read",
+        &["main", "setting", "my", "pluginMarket", "label"],
+    ),
+    ("config.write", &["main", "setting", "my", "label"]),
+    ("config.manage", &["main", "setting"]),
+    ("pet.config", &["main", "setting", "my"]),
+    ("pet.model", &["main", "setting"]),
+    ("backup.manage", &["main", "setting"]),
+    ("profile.manage", &["main", "setting"]),
+    ("state.read", &["main", "setting", "my", "pluginMarket"]),
+    ("state.write", &["main", "setting", "my", "pluginMarket"]),
+    ("state.delete", &["main", "setting", "my"]),
+    ("url.open", &["main"]),
+    ("window.resize", &["main", "setting", "my"]),
+    ("window.create", &["main", "setting", "my", "pluginMarket"]),
+    ("window.devtools", &["main", "setting"]),
+    ("icon.read", &["main", "setting", "my"]),
+    ("icon.write", &["main", "my"]),
+    ("clipboard.read", &["main", "setting"]),
+    ("clipboard.write", &["main"]),
+    ("input.paste", &["main"]),
+    ("file.search", &["main", "my"]),
+    ("hotkey.manage", &["main", "setting", "my"]),
+    ("plugin.config", &["main", "pluginMarket"]),
+    ("plugin.manage", &["main", "pluginMarket"]),
+    ("plugin.grant", &["main", "setting", "pluginMarket"]),
+    (
+        "plugin.publishers.read",
+        &["main", "setting", "pluginMarket"],
+    ),
+    ("plugin.publishers.write", &["main", "setting"]),
+    ("permission.audit", &["main", "setting"]),
+];
+
+/// 所有窗口（包括插件窗口）都拥有的能力，这些命令只作用于调用者自身，
+/// 或者像消息总线一样按插件清单的声明自行检查权限
+const PUBLIC_CAPABILITIES: &[&str] = &["window.self", "plugin.self", "log.write", "bus.message"];
+
+/// 判断能力是否已定义，供权限宏在编译期校验
+pub const fn is_defined_capability(name: &str) -> bool {
+    let mut i = 0;
+    while i < BUILTIN_POLICY.len() {
+        if str_eq(BUILTIN_POLICY[i].0, name) {
+            return true;
+        }
+        i += 1;
+    }
+    let mut i = 0;
+    while i < PUBLIC_CAPABILITIES.len() {
+        if str_eq(PUBLIC_CAPABILITIES[i], name) {
+            return true;
+        }
+        i += 1;
+    }
+    let mut i = 0;
+    while i < PLUGIN_CAPABILITIES.len() {
+        if str_eq(PLUGIN_CAPABILITIES[i].0, name) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// 窗口标签 -> 能力
+static POLICY: Lazy<RwLock<HashMap<String, HashSet<String>>>> = Lazy::new(|| {
+    let mut policy: HashMap<String, HashSet<String>> = HashMap::new();
+    for (capability, labels) in BUILTIN_POLICY {
+        for label in *labels {
+            policy
+                .entry(label.to_string())
+                .or_default()
+                .insert(capability.to_string());
+        }
+    }
+    RwLock::new(policy)
+});
+
+/// 命令调用被拒绝
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionDenied {
+    pub window_label: String,
+    pub command: String,
+    /// 命令要求的能力，按窗口标识拒绝时为 None
+    pub capability: Option<String>,
+}
+
+impl std::fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.capability {
+            Some(capability) => write!(
+                f,
+                "窗口 {} 没有调用 {} 所需的 {} 权限",
+                self.window_label, self.command, capability
+            ),
+            None => write!(
+                f,
+                "窗口 {} 没有权限调用 {}",
+                self.window_label, self.command
+            ),
+        }
+    }
+}
+
+impl From<PermissionDenied> for String {
+    fn from(denied: PermissionDenied) -> Self {
+        denied.to_string()
+    }
+}
+
+impl From<PermissionDenied> for AppError {
+    fn from(denied: PermissionDenied) -> Self {
+        AppError::Error(denied.to_string())
+    }
+}
+
+/// 拒绝记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionAuditEntry {
+    pub id: i64,
+    pub window_label: String,
+    pub command: String,
+    pub capability: Option<String>,
+    pub denied_at: String,
+}
+
+/// 命令权限策略
+///
+/// 权限宏在每次调用时通过 [`PermissionPolicy::check`] 检查，内置窗口按策略表授权，
+/// 插件窗口按用户授予的能力授权；被拒绝的调用记录到审计日志
+pub struct PermissionPolicy;
+
+impl PermissionPolicy {
+    /// 检查窗口是否拥有调用命令所需的能力
+    pub fn check(
+        window_label: &str,
+        command: &str,
+        capability: &str,
+    ) -> Result<(), PermissionDenied> {
+        if Self::allows(window_label, capability) {
+            return Ok(());
+        }
+        Err(Self::deny(window_label, command, Some(capability)))
+    }
+
+    /// 窗口是否拥有指定能力
+    pub fn allows(window_label: &str, capability: &str) -> bool {
+        if PUBLIC_CAPABILITIES.contains(&capability) {
+            return true;
+        }
+        if let Some(capabilities) = POLICY.read().get(window_label) {
+            return capabilities.contains(capability);
+        }
+        has_capability(window_label, capability)
+    }
+
+    /// 记录并返回拒绝结果
+    pub fn deny(window_label: &str, command: &str, capability: Option<&str>) -> PermissionDenied {
+        let denied = PermissionDenied {
+            window_label: window_label.to_string(),
+            command: command.to_string(),
+            capability: capability.map(str::to_string),
+        };
+        // 审计记录在写入线程上异步写入，不阻塞调用方，写入失败不影响拒绝结果
+        if let Err(e) = Self::record(&denied) {
+            let _ = Logger::write_log(LogEntry {
+                level: "error".to_string(),
+                message: "记录权限拒绝失败".to_string(),
+                timestamp: String::new(),
+                details: Some(e.to_string()),
+            });
+        }
+        denied
+    }
+
+    /// 获取最近的拒绝记录，按时间倒序
+    pub fn audit_log(
+        limit: u32,
+        window_label: Option<&str>,
+    ) -> AppResult<Vec<PermissionAuditEntry>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, window_label, command, capability, denied_at FROM permission_audit \
+                 WHERE ?1 IS NULL OR window_label = ?1 ORDER BY id DESC LIMIT ?2",
+            )
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let rows = stmt
+            .query_map(params![window_label, limit], |row| {
+                Ok(PermissionAuditEntry {
+                    id: row.get(0)?,
+                    window_label: row.get(1)?,
+                    command: row.get(2)?,
+                    capability: row.get(3)?,
+                    denied_at: row.get(4)?,
+                })
+            })
+            .map_err(|e| AppError::Error(format!("执行查询失败: {}", e)))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))
+    }
+
+    /// 提交审计记录，不等待写入完成
+    fn record(denied: &PermissionDenied) -> AppResult<()> {
+        let denied = denied.clone();
+        let denied_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        execute_write_detached(move |conn| {
+            conn.execute(
+                "INSERT INTO permission_audit (window_label, command, capability, denied_at) \
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    denied.window_label,
+                    denied.command,
+                    denied.capability,
+                    denied_at
+                ],
+            )
+            .map_err(|e| AppError::Error(format!("记录权限拒绝失败: {}", e)))?;
+            conn.execute(
+                "DELETE FROM permission_audit WHERE id <= last_insert_rowid() - ?1",
+                [MAX_AUDIT],
+            )
+            .map_err(|e| AppError::Error(format!("清理权限拒绝记录失败: {}", e)))?;
+            Ok(())
+        })
+    }
+}
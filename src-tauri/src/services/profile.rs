@@ -0,0 +1,477 @@
+use crate::command::hotkey::{apply_hotkey_config, HotkeyConfig};
+use crate::core::app_handle::AppHandleManager;
+use crate::services::config::ConfigStore;
+use crate::services::config_events::{ConfigEvents, ConfigTable};
+use crate::services::config_history::ConfigHistory;
+use crate::services::database::{execute_write, get_db_pool};
+use crate::services::logger::{LogEntry, Logger};
+use crate::utils::error::{AppError, AppResult};
+use chrono::{Datelike, Local, NaiveTime};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::{IpAddr, UdpSocket};
+use std::time::Duration;
+use tauri::Emitter;
+
+/// 配置方案切换后通知所有窗口重新加载配置
+pub const PROFILE_SWITCHED_EVENT: &str = "profile:switched";
+
+/// 随配置方案切换的配置键，其余配置（用户信息、备份设置、窗口位置等）在方案间共享
+const PROFILE_KEYS: [&str; 6] = [
+    "appConfig",
+    "webConfig",
+    "quickInputConfig",
+    "themeConfig",
+    "settingConfig",
+    "searchConfig",
+];
+
+/// 配置方案名称最大长度
+const MAX_PROFILE_NAME_LEN: usize = 32;
+/// 自动切换检查间隔
+const AUTO_SWITCH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 自动切换在配置变更历史中记录的来源
+const AUTO_SWITCH_SOURCE: &str = "autoSwitch";
+
+/// 上一次自动切换匹配到的方案，只在匹配结果变化时切换，避免覆盖用户的手动切换
+static LAST_AUTO_MATCH: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// 自动切换规则，同时设置时间段和网络时需要同时满足
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSwitchRule {
+    /// 开始时间，格式 HH:MM
+    pub start_time: Option<String>,
+    /// 结束时间，格式 HH:MM，早于开始时间时表示跨天
+    pub end_time: Option<String>,
+    /// 生效的星期，1 为周一，7 为周日，为空表示每天
+    #[serde(default)]
+    pub weekdays: Vec<u32>,
+    /// 本机 IP 前缀，如 `192.168.1.`，任一匹配即视为处于该网络
+    #[serde(default)]
+    pub ip_prefixes: Vec<String>,
+}
+
+/// 配置方案信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+    pub auto_switch: Option<AutoSwitchRule>,
+    pub created_at: String,
+}
+
+/// 配置方案切换事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSwitchedEvent {
+    pub name: String,
+    pub previous: String,
+    /// 是否由自动切换规则触发
+    pub auto: bool,
+}
+
+pub struct ProfileService;
+
+impl ProfileService {
+    /// 获取所有配置方案
+    pub fn list() -> AppResult<Vec<ProfileInfo>> {
+        let conn = get_db_pool()
+            .get()
+            .map_err(|e| AppError::Error(format!("获取数据库连接失败: {}", e)))?;
+        let mut stmt = conn
+            .prepare("SELECT name, is_active, auto_switch, created_at FROM profile ORDER BY created_at, name")
+            .map_err(|e| AppError::Error(format!("准备查询语句失败: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, bool>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|e| AppError::Error(format!("查询配置方案失败: {}", e)))?;
+
+        let mut profiles = Vec::new();
+        for row in rows {
+            let (name, active, auto_switch, created_at) =
+                row.map_err(|e| AppError::Error(format!("获取查询结果失败: {}", e)))?;
+            let auto_switch = auto_switch
+                .map(|json| serde_json::from_str(&json))
+                .transpose()
+                .map_err(|e| AppError::Error(format!("解析自动切换规则失败: {}", e)))?;
+            profiles.push(ProfileInfo {
+                name,
+                active,
+                auto_switch,
+                created_at,
+            });
+        }
+        Ok(profiles)
+    }
+
+    /// 创建配置方案，`clone_from` 为空时创建空白方案（使用默认配置）
+    pub fn create(name: &str, clone_from: Option<String>) -> AppResult<()> {
+        let name = validate_name(name)?;
+        execute_write(move |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+            ensure_absent(&tx, &name)?;
+            tx.execute("INSERT INTO profile (name) VALUES (?1)", [&name])
+                .map_err(|e| AppError::Error(format!("创建配置方案失败: {}", e)))?;
+
+            if let Some(source) = clone_from {
+                ensure_exists(&tx, &source)?;
+                // 激活方案的配置在 config 表中，其余方案在快照表中
+                let result = if active_profile(&tx)? == source {
+                    tx.execute(
+                        &format!(
+                            "INSERT INTO profile_config (profile, key, value) \
+                             SELECT ?1, key, value FROM config WHERE key IN ({})",
+                            profile_keys_sql()
+                        ),
+                        [&name],
+                    )
+                } else {
+                    tx.execute(
+                        "INSERT INTO profile_config (profile, key, value) \
+                         SELECT ?1, key, value FROM profile_config WHERE profile = ?2",
+                        params![name, source],
+                    )
+                };
+                result.map_err(|e| AppError::Error(format!("复制配置方案失败: {}", e)))?;
+            }
+
+            tx.commit()
+                .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))
+        })
+    }
+
+    /// 重命名配置方案
+    pub fn rename(name: &str, new_name: &str) -> AppResult<()> {
+        let name = name.to_string();
+        let new_name = validate_name(new_name)?;
+        execute_write(move |conn| {
+            ensure_exists(conn, &name)?;
+            ensure_absent(conn, &new_name)?;
+            // profile_config 通过外键级联更新
+            conn.execute(
+                "UPDATE profile SET name = ?1 WHERE name = ?2",
+                params![new_name, name],
+            )
+            .map_err(|e| AppError::Error(format!("重命名配置方案失败: {}", e)))?;
+            Ok(())
+        })
+    }
+
+    /// 删除配置方案，不能删除当前激活的方案
+    pub fn delete(name: &str) -> AppResult<()> {
+        let name = name.to_string();
+        execute_write(move |conn| {
+            ensure_exists(conn, &name)?;
+            if active_profile(conn)? == name {
+                return Err(AppError::Error("不能删除当前使用的配置方案".into()));
+            }
+            conn.execute("DELETE FROM profile WHERE name = ?1", [&name])
+                .map_err(|e| AppError::Error(format!("删除配置方案失败: {}", e)))?;
+            Ok(())
+        })
+    }
+
+    /// 设置配置方案的自动切换规则，为 None 时关闭自动切换
+    pub fn set_auto_switch(name: &str, rule: Option<AutoSwitchRule>) -> AppResult<()> {
+        if let Some(rule) = &rule {
+            validate_rule(rule)?;
+        }
+        let name = name.to_string();
+        let rule_json = rule
+            .map(|rule| serde_json::to_string(&rule))
+            .transpose()
+            .map_err(|e| AppError::Error(format!("序列化自动切换规则失败: {}", e)))?;
+
+        execute_write(move |conn| {
+            ensure_exists(conn, &name)?;
+            conn.execute(
+                "UPDATE profile SET auto_switch = ?1 WHERE name = ?2",
+                params![rule_json, name],
+            )
+            .map_err(|e| AppError::Error(format!("保存自动切换规则失败: {}", e)))?;
+            Ok(())
+        })
+    }
+
+    /// 切换到指定配置方案，重新应用快捷键并通知所有窗口重新加载
+    pub fn switch(name: &str, source: &str) -> AppResult<()> {
+        Self::switch_inner(name, source, false)
+    }
+
+    fn switch_inner(name: &str, source: &str, auto: bool) -> AppResult<()> {
+        let target = name.to_string();
+        let source = source.to_string();
+        let previous = execute_write(move |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| AppError::Error(format!("创建事务失败: {}", e)))?;
+            ensure_exists(&tx, &target)?;
+            let previous = active_profile(&tx)?;
+            if previous == target {
+                return Ok(previous);
+            }
+
+            let keys = profile_keys_sql();
+            let statements = [
+                // 保存当前方案的配置快照
+                "DELETE FROM profile_config WHERE profile = ?1".to_string(),
+                format!(
+                    "INSERT INTO profile_config (profile, key, value) \
+                     SELECT ?1, key, value FROM config WHERE key IN ({})",
+                    keys
+                ),
+            ];
+            for sql in &statements {
+                tx.execute(sql, [&previous])
+                    .map_err(|e| AppError::Error(format!("保存配置方案失败: {}", e)))?;
+            }
+
+            // 载入目标方案的配置，方案中没有的键恢复为默认配置
+            for key in PROFILE_KEYS {
+                let value: Option<String> = tx
+                    .query_row(
+                        "SELECT value FROM profile_config WHERE profile = ?1 AND key = ?2",
+                        params![target, key],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(|e| AppError::Error(format!("读取配置方案失败: {}", e)))?;
+                let value = value
+                    .map(|json| serde_json::from_str::<Value>(&json))
+                    .transpose()
+                    .map_err(|e| AppError::Error(format!("解析配置方案失败: {}", e)))?;
+                ConfigHistory::write(&tx, key, value.as_ref(), &source)?;
+            }
+            tx.execute("DELETE FROM profile_config WHERE profile = ?1", [&target])
+                .map_err(|e| AppError::Error(format!("切换配置方案失败: {}", e)))?;
+            tx.execute(
+                "UPDATE profile SET is_active = (name = ?1)",
+                [&target],
+            )
+            .map_err(|e| AppError::Error(format!("切换配置方案失败: {}", e)))?;
+
+            tx.commit()
+                .map_err(|e| AppError::Error(format!("提交事务失败: {}", e)))?;
+            Ok(previous)
+        })?;
+
+        if previous == name {
+            return Ok(());
+        }
+
+        Self::apply_hotkeys()?;
+
+        for key in PROFILE_KEYS {
+            ConfigEvents::notify(ConfigTable::Config, key, ConfigStore::get(&[key.to_string()])?);
+        }
+        if let Some(app_handle) = AppHandleManager::get() {
+            let _ = app_handle.emit(
+                PROFILE_SWITCHED_EVENT,
+                ProfileSwitchedEvent {
+                    name: name.to_string(),
+                    previous,
+                    auto,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// 按当前方案的快捷键配置重新注册全局快捷键
+    fn apply_hotkeys() -> AppResult<()> {
+        let config = match ConfigStore::get(&["settingConfig".to_string(), "hotkey".to_string()])? {
+            Some(value) => serde_json::from_value::<HotkeyConfig>(value)
+                .map_err(|e| AppError::Error(format!("解析快捷键配置失败: {}", e)))?,
+            // 方案中没有快捷键配置时关闭全局快捷键
+            None => HotkeyConfig {
+                enabled: false,
+                items: Default::default(),
+            },
+        };
+        apply_hotkey_config(&config);
+        Ok(())
+    }
+
+    /// 启动自动切换任务，定期按规则检查应使用的配置方案
+    pub fn start_auto_switch() {
+        tauri::async_runtime::spawn(async {
+            loop {
+                tokio::time::sleep(AUTO_SWITCH_CHECK_INTERVAL).await;
+
+                let result = tokio::task::spawn_blocking(Self::run_auto_switch).await;
+                let error = match result {
+                    Ok(Ok(())) => continue,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) => e.to_string(),
+                };
+                let _ = Logger::write_log(LogEntry {
+                    level: "error".to_string(),
+                    message: "自动切换配置方案失败".to_string(),
+                    timestamp: String::new(),
+                    details: Some(error),
+                });
+            }
+        });
+    }
+
+    /// 检查自动切换规则，匹配结果变化时切换到第一个匹配的方案
+    fn run_auto_switch() -> AppResult<()> {
+        let profiles = Self::list()?;
+        if profiles.iter().all(|profile| profile.auto_switch.is_none()) {
+            return Ok(());
+        }
+
+        let local_ip = local_ip();
+        let matched = profiles
+            .iter()
+            .find(|profile| {
+                profile
+                    .auto_switch
+                    .as_ref()
+                    .is_some_and(|rule| rule_matches(rule, local_ip))
+            })
+            .map(|profile| profile.name.clone());
+
+        let mut last_match = LAST_AUTO_MATCH.lock();
+        if *last_match == matched {
+            return Ok(());
+        }
+        *last_match = matched.clone();
+        drop(last_match);
+
+        match matched {
+            Some(name) if !profiles.iter().any(|p| p.active && p.name == name) => {
+                Self::switch_inner(&name, AUTO_SWITCH_SOURCE, true)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn profile_keys_sql() -> String {
+    PROFILE_KEYS
+        .iter()
+        .map(|key| format!("'{}'", key))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn active_profile(conn: &Connection) -> AppResult<String> {
+    conn.query_row("SELECT name FROM profile WHERE is_active = 1", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| AppError::Error(format!("查询当前配置方案失败: {}", e)))?
+        .ok_or_else(|| AppError::Error("没有激活的配置方案".into()))
+}
+
+fn profile_exists(conn: &Connection, name: &str) -> AppResult<bool> {
+    conn.query_row("SELECT 1 FROM profile WHERE name = ?1", [name], |_| Ok(()))
+        .optional()
+        .map(|row| row.is_some())
+        .map_err(|e| AppError::Error(format!("查询配置方案失败: {}", e)))
+}
+
+fn ensure_exists(conn: &Connection, name: &str) -> AppResult<()> {
+    if profile_exists(conn, name)? {
+        Ok(())
+    } else {
+        Err(AppError::Error(format!("配置方案 {} 不存在", name)))
+    }
+}
+
+fn ensure_absent(conn: &Connection, name: &str) -> AppResult<()> {
+    if profile_exists(conn, name)? {
+        Err(AppError::Error(format!("配置方案 {} 已存在", name)))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Error("配置方案名称不能为空".into()));
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_LEN {
+        return Err(AppError::Error(format!(
+            "配置方案名称不能超过 {} 个字符",
+            MAX_PROFILE_NAME_LEN
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_rule(rule: &AutoSwitchRule) -> AppResult<()> {
+    for time in [&rule.start_time, &rule.end_time].into_iter().flatten() {
+        parse_time(time)?;
+    }
+    if rule.start_time.is_some() != rule.end_time.is_some() {
+        return Err(AppError::Error("开始时间和结束时间需要同时设置".into()));
+    }
+    if rule.weekdays.iter().any(|day| !(1..=7).contains(day)) {
+        return Err(AppError::Error("星期的取值范围为 1-7".into()));
+    }
+    if rule.start_time.is_none() && rule.weekdays.is_empty() && rule.ip_prefixes.is_empty() {
+        return Err(AppError::Error("自动切换规则至少需要一个条件".into()));
+    }
+    Ok(())
+}
+
+fn parse_time(time: &str) -> AppResult<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| AppError::Error(format!("时间格式错误: {}，应为 HH:MM", time)))
+}
+
+fn rule_matches(rule: &AutoSwitchRule, local_ip: Option<IpAddr>) -> bool {
+    let now = Local::now();
+
+    if !rule.weekdays.is_empty() && !rule.weekdays.contains(&now.weekday().number_from_monday()) {
+        return false;
+    }
+
+    if let (Some(start), Some(end)) = (&rule.start_time, &rule.end_time) {
+        let (Ok(start), Ok(end)) = (parse_time(start), parse_time(end)) else {
+            return false;
+        };
+        let time = now.time();
+        let in_range = if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        };
+        if !in_range {
+            return false;
+        }
+    }
+
+    if !rule.ip_prefixes.is_empty() {
+        let Some(ip) = local_ip.map(|ip| ip.to_string()) else {
+            return false;
+        };
+        if !rule.ip_prefixes.iter().any(|prefix| ip.starts_with(prefix.as_str())) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 获取本机出口 IP，UDP connect 不会实际发送数据
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
@@ -0,0 +1,123 @@
+use crate::services::logger::{LogEntry, Logger};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 滚动备份文件后缀
+const BACKUP_SUFFIX: &str = "bak";
+/// 临时文件后缀
+const TEMP_SUFFIX: &str = "tmp";
+
+/// 获取同目录下追加后缀的文件路径，如 config.json -> config.json.bak
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// 获取文件的滚动备份路径
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling_path(path, BACKUP_SUFFIX)
+}
+
+/// 原子写入文件：先写入临时文件并落盘，再重命名替换目标文件
+///
+/// 写入过程中崩溃或断电时，目标文件保持旧内容或新内容，不会出现半写状态
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let temp_path = sibling_path(path, TEMP_SUFFIX);
+
+    let result = (|| -> std::io::Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, path)?;
+        sync_parent_dir(path);
+        Ok(())
+    })();
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("写入文件 {} 失败: {}", path.display(), e)
+    })
+}
+
+/// 原子写入 JSON 文件，并在替换前把当前有效内容保存为 `.bak`
+pub fn write_json_atomic<T: Serialize>(path: &Path, value: &T, pretty: bool) -> Result<(), String> {
+    let content = if pretty {
+        serde_json::to_vec_pretty(value)
+    } else {
+        serde_json::to_vec(value)
+    }
+    .map_err(|e| e.to_string())?;
+
+    // 只备份能正常解析的旧文件，避免损坏的内容覆盖掉可用的备份
+    if let Ok(current) = fs::read(path) {
+        if !current.is_empty() && serde_json::from_slice::<IgnoredAny>(&current).is_ok() {
+            write_atomic(&backup_path(path), &current)?;
+        }
+    }
+
+    write_atomic(path, &content)
+}
+
+/// 读取 JSON 文件，解析失败时自动从 `.bak` 恢复
+///
+/// 文件不存在或为空且没有可用备份时返回 None
+pub fn read_json_or_recover<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let error = match fs::read(path) {
+        Ok(buffer) if buffer.is_empty() => "文件为空".to_string(),
+        Ok(buffer) => match parse_json(buffer) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => e,
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let backup = backup_path(path);
+    let recovered = fs::read(&backup)
+        .ok()
+        .filter(|buffer| !buffer.is_empty())
+        .and_then(|buffer| parse_json::<T>(buffer.clone()).ok().map(|value| (value, buffer)));
+
+    match recovered {
+        Some((value, buffer)) => {
+            write_atomic(path, &buffer)?;
+            let _ = Logger::write_log(LogEntry {
+                level: "warn".to_string(),
+                message: format!("配置文件已从备份恢复: {}", path.display()),
+                timestamp: String::new(),
+                details: Some(error),
+            });
+            Ok(Some(value))
+        }
+        // 空文件且没有备份时视为新文件
+        None if fs::metadata(path).map(|m| m.len() == 0).unwrap_or(false) => Ok(None),
+        None => Err(format!("解析文件 {} 失败: {}", path.display(), error)),
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(mut buffer: Vec<u8>) -> Result<T, String> {
+    let fallback = buffer.clone();
+    // 使用simd-json加速解析，失败时回退到标准serde_json
+    match simd_json::serde::from_slice::<T>(&mut buffer) {
+        Ok(data) => Ok(data),
+        Err(_) => serde_json::from_slice(&fallback).map_err(|e| e.to_string()),
+    }
+}
+
+/// 同步父目录，确保重命名操作本身已落盘
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
@@ -31,4 +31,11 @@ impl From<&str> for AppError {
     }
 }
 
+// 插件清单错误的消息直接作为错误消息
+impl From<plugin_manifest::error::ManifestError> for AppError {
+    fn from(error: plugin_manifest::error::ManifestError) -> Self {
+        AppError::Error(error.to_string())
+    }
+}
+
 pub type AppResult<T> = Result<T, AppError>;
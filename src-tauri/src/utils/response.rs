@@ -85,6 +85,11 @@ impl<T> ApiResponse<T> {
         }
     }
 
+    /// 创建权限拒绝响应
+    pub fn permission_denied(message: impl Into<String>) -> ApiResponse<T> {
+        Self::error(ApiStatusCode::ErrPermission, message)
+    }
+
     /// 从 AppResult 转换为 ApiResponse
     pub fn from_app_result(result: AppResult<T>) -> Self {
         match result {
@@ -0,0 +1,30 @@
+import { BackupInfo, RestoreReport } from "../../types/backup";
+import { invokeApi } from "./wrapper";
+
+/**
+ * 创建备份
+ * @param targetPath 备份文件保存路径，不传时保存到数据目录的 Backup 文件夹
+ * @returns 备份文件路径
+ */
+export const ipcBackupCreate = async (targetPath?: string): Promise<string> => {
+  return await invokeApi("backup_create", { targetPath });
+};
+
+/**
+ * 从备份恢复
+ * @param filePath 备份文件路径
+ * @param dryRun 为 true 时只预演，返回将要发生的变更
+ */
+export const ipcBackupRestore = async (
+  filePath: string,
+  dryRun = false,
+): Promise<RestoreReport> => {
+  return await invokeApi("backup_restore", { filePath, dryRun });
+};
+
+/**
+ * 列出已有的备份
+ */
+export const ipcBackupList = async (): Promise<BackupInfo[]> => {
+  return await invokeApi("backup_list");
+};
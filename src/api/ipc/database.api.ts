@@ -1,4 +1,11 @@
 import { invokeApi } from "./wrapper";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import {
+  ConfigChange,
+  ConfigChangeTable,
+  ConfigChangedEvent,
+  RestoredConfigValue,
+} from "@/types/database";
 
 /**
  * 设置配置值
@@ -118,3 +125,161 @@ export async function ipcGetPetConfig<T = any>(
 export async function ipcDeletePetConfig(configType: string): Promise<void> {
   await invokeApi<void>("delete_pet_config", { configType });
 }
+
+/**
+ * 按路径获取配置
+ * @param path 配置路径，第一段为配置键，其余为嵌套字段，如 ["settingConfig", "hotkey"]
+ * @returns 配置值，如果不存在返回 null
+ */
+export async function ipcGetConfigByPath<T = any>(
+  path: string[],
+): Promise<T | null> {
+  return await invokeApi<T | null>("get_config_by_path", { path });
+}
+
+/**
+ * 按路径设置配置，中间层级不存在时自动创建
+ * @param path 配置路径
+ * @param value 配置值
+ */
+export async function ipcSetConfigByPath(
+  path: string[],
+  value: any,
+): Promise<void> {
+  await invokeApi<void>("set_config_by_path", { path, value });
+}
+
+/**
+ * 按路径删除配置
+ * @param path 配置路径，空数组表示删除所有配置
+ */
+export async function ipcDeleteConfigByPath(path: string[]): Promise<void> {
+  await invokeApi<void>("delete_config_by_path", { path });
+}
+
+/**
+ * 导出配置为可移植的 JSON 文档
+ * @param selection 导出范围，未指定的部分不导出，空数组表示导出该部分全部条目
+ * @returns 配置文档
+ */
+export async function ipcConfigExport(selection: {
+  config?: string[];
+  pluginConfig?: string[];
+  petConfig?: string[];
+}): Promise<Record<string, any>> {
+  return await invokeApi<Record<string, any>>("config_export", { selection });
+}
+
+/**
+ * 导入配置文档
+ * @param document 由 ipcConfigExport 导出的配置文档
+ * @param mode merge：冲突时保留本地值；replace：覆盖本地值
+ * @param dryRun 为 true 时只返回导入报告，不写入数据
+ * @returns 导入报告
+ */
+export async function ipcConfigImport(
+  document: Record<string, any>,
+  mode: "merge" | "replace",
+  dryRun = false,
+): Promise<{
+  dryRun: boolean;
+  added: string[];
+  updated: string[];
+  unchanged: string[];
+  conflicts: { key: string; reason: string }[];
+  assets: string[];
+}> {
+  return await invokeApi("config_import", { document, mode, dryRun });
+}
+
+/**
+ * 订阅配置变更，变更通过 config:changed 事件推送到当前窗口
+ * @param table 配置所在的存储
+ * @param keys 要订阅的键，空数组表示订阅全部键
+ */
+export async function ipcSubscribeConfigChanges(
+  table: ConfigChangeTable,
+  keys: string[] = [],
+): Promise<void> {
+  await invokeApi<void>("subscribe_config_changes", { table, keys });
+}
+
+/**
+ * 取消订阅配置变更
+ * @param table 配置所在的存储，不传时取消当前窗口的全部订阅
+ */
+export async function ipcUnsubscribeConfigChanges(
+  table?: ConfigChangeTable,
+): Promise<void> {
+  await invokeApi<void>("unsubscribe_config_changes", { table });
+}
+
+/**
+ * 订阅并监听配置变更
+ * @param table 配置所在的存储
+ * @param keys 要订阅的键，空数组表示订阅全部键
+ * @param handler 变更回调
+ * @returns 取消监听函数
+ */
+export async function onConfigChanged(
+  table: ConfigChangeTable,
+  keys: string[],
+  handler: (event: ConfigChangedEvent) => void,
+): Promise<UnlistenFn> {
+  const unlisten = await listen<ConfigChangedEvent>("config:changed", (event) => {
+    if (event.payload.table === table) {
+      handler(event.payload);
+    }
+  });
+  await ipcSubscribeConfigChanges(table, keys);
+  return unlisten;
+}
+
+/**
+ * 检查已存储的配置并重置无效值
+ * @param dryRun 为 true 时只返回检查报告，不修改数据
+ * @returns 修复报告，invalid 中为校验失败的配置及字段错误
+ */
+export async function ipcRepairConfigValues(dryRun = false): Promise<{
+  dryRun: boolean;
+  invalid: { key: string; errors: { path: string; message: string }[] }[];
+}> {
+  return await invokeApi("repair_config_values", { dryRun });
+}
+
+/**
+ * 获取最近的配置变更记录，按时间倒序
+ * @param limit 返回的最大条数，默认 100
+ * @param key 只返回指定配置键的变更
+ */
+export async function ipcConfigHistoryList(
+  limit?: number,
+  key?: string,
+): Promise<ConfigChange[]> {
+  return await invokeApi<ConfigChange[]>("config_history_list", { limit, key });
+}
+
+/**
+ * 撤销指定的配置变更，恢复为该次变更前的值
+ * @param id 变更记录ID
+ */
+export async function ipcConfigHistoryRevert(
+  id: number,
+): Promise<RestoredConfigValue> {
+  return await invokeApi<RestoredConfigValue>("config_history_revert", { id });
+}
+
+/**
+ * 把配置恢复到指定时间点的状态
+ * @param timestamp 时间点，格式 YYYY-MM-DD HH:MM:SS
+ * @param dryRun 为 true 时只返回将要恢复的配置
+ */
+export async function ipcConfigHistoryRestore(
+  timestamp: string,
+  dryRun = false,
+): Promise<RestoredConfigValue[]> {
+  return await invokeApi<RestoredConfigValue[]>("config_history_restore", {
+    timestamp,
+    dryRun,
+  });
+}
@@ -0,0 +1,14 @@
+import { PermissionAuditEntry } from "../../types/permission";
+import { invokeApi } from "./wrapper";
+
+/**
+ * 获取最近被拒绝的命令调用，按时间倒序
+ * @param limit 返回的最大条数，默认 100
+ * @param windowLabel 只返回指定窗口的记录
+ */
+export const ipcPermissionAuditList = async (
+  limit?: number,
+  windowLabel?: string,
+): Promise<PermissionAuditEntry[]> => {
+  return await invokeApi("permission_audit_list", { limit, windowLabel });
+};
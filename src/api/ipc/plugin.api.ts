@@ -1,24 +1,75 @@
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { invokeApi } from "./wrapper";
+import {
+  CapabilityGrant,
+  DevSource,
+  PluginChangedEvent,
+  PluginDevEvent,
+  PluginEnabledEvent,
+  PluginStorageUsage,
+  PluginUpdate,
+  PluginUpdateResult,
+  TrustedPublisher,
+  UnresolvedDependency,
+} from "@/types/plugin";
 
 /**
  * 安装插件
  * @param url 插件下载地址
  * @param windowId 插件的窗口ID
+ * @param sha256 插件市场提供的插件包 SHA-256
+ * @param signature 插件包的 Ed25519 签名（Base64），提供时必须由信任的发布者签名
+ * @param grants 用户授予的能力，不传时保留已有的授权
+ * @param allowUnsigned 用户已确认安装未签名的插件包，不传时拒绝安装未签名的插件
  */
-export const ipcInstallPlugin = async (url: string, windowId: string) => {
-  return invokeApi("mh_plugin_install", { url, windowId });
+export const ipcInstallPlugin = async (
+  url: string,
+  windowId: string,
+  sha256: string,
+  signature?: string,
+  grants?: string[],
+  allowUnsigned?: boolean,
+) => {
+  return invokeApi("mh_plugin_install", {
+    url,
+    windowId,
+    sha256,
+    signature,
+    grants,
+    allowUnsigned,
+  });
+};
+
+/**
+ * 取消正在进行的插件安装，安装进度通过 plugin:install-progress 事件通知
+ * @param windowId 插件的窗口ID
+ */
+export const ipcCancelInstallPlugin = async (windowId: string) => {
+  return invokeApi("mh_plugin_cancel_install", { windowId });
 };
 
 /**
  * 安装本地插件
  * @param filePath 本地插件文件路径
  * @param windowId 插件的窗口ID
+ * @param signature 插件包的 Ed25519 签名（Base64），提供时必须由信任的发布者签名
+ * @param grants 用户授予的能力，不传时保留已有的授权
+ * @param allowUnsigned 用户已确认安装未签名的插件包，不传时拒绝安装未签名的插件
  */
 export const ipcInstallLocalPlugin = async (
   filePath: string,
   windowId: string,
+  signature?: string,
+  grants?: string[],
+  allowUnsigned?: boolean,
 ) => {
-  return invokeApi("mh_plugin_install_local", { filePath, windowId });
+  return invokeApi("mh_plugin_install_local", {
+    filePath,
+    windowId,
+    signature,
+    grants,
+    allowUnsigned,
+  });
 };
 
 /**
@@ -40,3 +91,230 @@ export const ipcUninstallPlugin = async (windowId: string) => {
 export const ipcAnalyzePluginPackage = async (filePath: string) => {
   return invokeApi("mh_plugin_analyze_package", { filePath });
 };
+
+/**
+ * 获取信任的插件发布者
+ */
+export const ipcGetTrustedPublishers = async (): Promise<TrustedPublisher[]> => {
+  return invokeApi<TrustedPublisher[]>("mh_plugin_trusted_publishers");
+};
+
+/**
+ * 添加信任的插件发布者，标识已存在时更新公钥
+ * @param publisher 发布者信息
+ */
+export const ipcTrustPublisher = async (publisher: TrustedPublisher) => {
+  return invokeApi("mh_plugin_trust_publisher", { publisher });
+};
+
+/**
+ * 移除信任的插件发布者
+ * @param id 发布者标识
+ */
+export const ipcUntrustPublisher = async (id: string) => {
+  return invokeApi("mh_plugin_untrust_publisher", { id });
+};
+
+/**
+ * 回滚插件到上一个版本
+ * @param windowId 插件的窗口ID
+ * @returns 回滚后的插件版本
+ */
+export const ipcRollbackPlugin = async (windowId: string): Promise<string> => {
+  return invokeApi<string>("mh_plugin_rollback", { windowId });
+};
+
+/**
+ * 对比插件市场索引检查已安装插件的更新
+ */
+export const ipcCheckPluginUpdates = async (): Promise<PluginUpdate[]> => {
+  return invokeApi<PluginUpdate[]>("mh_plugin_check_updates");
+};
+
+/**
+ * 更新所有可更新的插件
+ * @param allowUnsigned 用户已确认更新未签名的插件包，不传时未签名的更新会失败
+ * @returns 每个插件的更新结果，单个插件失败不影响其余插件
+ */
+export const ipcUpdateAllPlugins = async (
+  allowUnsigned?: boolean,
+): Promise<PluginUpdateResult[]> => {
+  return invokeApi<PluginUpdateResult[]>("mh_plugin_update_all", {
+    allowUnsigned,
+  });
+};
+
+/**
+ * 获取插件申请的能力及授权状态
+ * @param windowId 插件的窗口ID
+ */
+export const ipcGetPluginCapabilities = async (
+  windowId: string,
+): Promise<CapabilityGrant[]> => {
+  return invokeApi<CapabilityGrant[]>("mh_plugin_capabilities", { windowId });
+};
+
+/**
+ * 设置插件的能力授权，未列出的能力会被撤销
+ * @param windowId 插件的窗口ID
+ * @param grants 授予的全部能力，只能是插件申请了的能力
+ */
+export const ipcSetPluginGrants = async (windowId: string, grants: string[]) => {
+  return invokeApi("mh_plugin_set_grants", { windowId, grants });
+};
+
+/**
+ * 获取插件的存储用量
+ * @param windowId 插件的窗口ID，不传时返回所有插件
+ */
+export const ipcGetPluginStorageUsage = async (
+  windowId?: string,
+): Promise<PluginStorageUsage[]> => {
+  return invokeApi<PluginStorageUsage[]>("mh_plugin_storage_usage", {
+    windowId,
+  });
+};
+
+/**
+ * 设置插件的存储配额，调低配额不会删除已有数据
+ * @param windowId 插件的窗口ID
+ * @param quota 配额（字节），不传时恢复默认配额
+ */
+export const ipcSetPluginStorageQuota = async (
+  windowId: string,
+  quota?: number,
+) => {
+  return invokeApi("mh_plugin_set_storage_quota", { windowId, quota });
+};
+
+/**
+ * 清理插件记录已不存在的存储数据，已安装插件的存储不会被删除
+ * @param windowId 插件的窗口ID，不传时清理所有残留数据
+ * @returns 删除的键数量
+ */
+export const ipcClearOrphanedPluginStorage = async (
+  windowId?: string,
+): Promise<number> => {
+  return invokeApi<number>("mh_plugin_storage_clear_orphaned", { windowId });
+};
+
+/**
+ * 获取已安装插件中未安装或版本不满足的依赖
+ */
+export const ipcGetUnresolvedDependencies = async (): Promise<
+  UnresolvedDependency[]
+> => {
+  return invokeApi<UnresolvedDependency[]>(
+    "mh_plugin_unresolved_dependencies",
+  );
+};
+
+/**
+ * 启用或禁用插件，禁用的插件保留文件和配置，但不能打开窗口、接收快捷键或执行启动钩子
+ * @param windowId 插件的窗口ID
+ * @param enabled 是否启用
+ */
+export const ipcSetPluginEnabled = async (windowId: string, enabled: boolean) => {
+  return invokeApi("mh_plugin_set_enabled", { windowId, enabled });
+};
+
+/**
+ * 以开发模式加载插件，文件不会被复制，本地目录中的文件变化后自动重新加载插件窗口
+ * @param source 插件目录或开发服务器地址，只能设置一个
+ * @param grants 用户授予的能力，不传时保留已有的授权
+ * @returns 插件的窗口ID
+ */
+export const ipcRegisterDevPlugin = async (
+  source: DevSource,
+  grants?: string[],
+): Promise<string> => {
+  return invokeApi<string>("mh_plugin_dev_register", {
+    path: source.path,
+    url: source.url,
+    grants,
+  });
+};
+
+/**
+ * 移除开发模式插件，本地目录保持不变
+ * @param windowId 插件的窗口ID
+ */
+export const ipcUnregisterDevPlugin = async (windowId: string) => {
+  return invokeApi("mh_plugin_dev_unregister", { windowId });
+};
+
+/**
+ * 通知宿主生命周期钩子已执行完成，在隐藏窗口中执行的钩子完成后窗口会被关闭
+ */
+export const ipcPluginLifecycleDone = async () => {
+  return invokeApi("mh_plugin_lifecycle_done");
+};
+
+/**
+ * 监听插件安装、升级和卸载
+ * @param handler 变更回调，type 为 installed、updated 或 uninstalled
+ * @returns 取消监听函数
+ */
+export async function onPluginChanged(
+  handler: (
+    type: "installed" | "updated" | "uninstalled",
+    event: PluginChangedEvent,
+  ) => void,
+): Promise<UnlistenFn> {
+  const unlisteners = await Promise.all(
+    (["installed", "updated", "uninstalled"] as const).map((type) =>
+      listen<PluginChangedEvent>(`plugin:${type}`, (event) =>
+        handler(type, event.payload),
+      ),
+    ),
+  );
+  return () => unlisteners.forEach((unlisten) => unlisten());
+}
+
+/**
+ * 监听插件启用和禁用
+ * @param handler 变更回调
+ * @returns 取消监听函数
+ */
+export async function onPluginEnabled(
+  handler: (event: PluginEnabledEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<PluginEnabledEvent>("plugin:enabled", (event) =>
+    handler(event.payload),
+  );
+}
+
+/**
+ * 监听同步插件后发现的无法满足的依赖
+ * @param handler 回调参数为所有无法满足的依赖
+ * @returns 取消监听函数
+ */
+export async function onPluginDependenciesUnresolved(
+  handler: (dependencies: UnresolvedDependency[]) => void,
+): Promise<UnlistenFn> {
+  return listen<UnresolvedDependency[]>(
+    "plugin:dependencies-unresolved",
+    (event) => handler(event.payload),
+  );
+}
+
+/**
+ * 监听开发模式插件重新加载
+ * @param handler 重新加载成功的回调
+ * @param onError 重新加载失败的回调，通常是清单校验不通过
+ * @returns 取消监听函数
+ */
+export async function onPluginDevReloaded(
+  handler: (event: PluginDevEvent) => void,
+  onError?: (event: PluginDevEvent) => void,
+): Promise<UnlistenFn> {
+  const unlisteners = await Promise.all([
+    listen<PluginDevEvent>("plugin:dev-reloaded", (event) =>
+      handler(event.payload),
+    ),
+    listen<PluginDevEvent>("plugin:dev-error", (event) =>
+      onError?.(event.payload),
+    ),
+  ]);
+  return () => unlisteners.forEach((unlisten) => unlisten());
+}
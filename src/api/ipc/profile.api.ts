@@ -0,0 +1,56 @@
+import { AutoSwitchRule, ProfileInfo } from "../../types/profile";
+import { invokeApi } from "./wrapper";
+
+/**
+ * 获取所有配置方案
+ */
+export const ipcProfileList = async (): Promise<ProfileInfo[]> => {
+  return await invokeApi("profile_list");
+};
+
+/**
+ * 创建配置方案
+ * @param name 方案名称
+ * @param cloneFrom 要复制的方案名称，不传时创建使用默认配置的空白方案
+ */
+export const ipcProfileCreate = async (
+  name: string,
+  cloneFrom?: string,
+): Promise<void> => {
+  await invokeApi("profile_create", { name, cloneFrom });
+};
+
+/**
+ * 重命名配置方案
+ */
+export const ipcProfileRename = async (
+  name: string,
+  newName: string,
+): Promise<void> => {
+  await invokeApi("profile_rename", { name, newName });
+};
+
+/**
+ * 删除配置方案，不能删除当前使用的方案
+ */
+export const ipcProfileDelete = async (name: string): Promise<void> => {
+  await invokeApi("profile_delete", { name });
+};
+
+/**
+ * 切换配置方案，切换后所有窗口会收到 profile:switched 事件
+ */
+export const ipcProfileSwitch = async (name: string): Promise<void> => {
+  await invokeApi("profile_switch", { name });
+};
+
+/**
+ * 设置配置方案的自动切换规则
+ * @param rule 自动切换规则，不传时关闭自动切换
+ */
+export const ipcProfileSetAutoSwitch = async (
+  name: string,
+  rule?: AutoSwitchRule,
+): Promise<void> => {
+  await invokeApi("profile_set_auto_switch", { name, rule });
+};
@@ -0,0 +1,49 @@
+/** 自动备份配置（config表 backupConfig） */
+export type BackupConfig = {
+  /** 是否启用自动备份 */
+  autoBackup: boolean;
+  /** 自动备份间隔（小时） */
+  intervalHours: number;
+  /** 保留的自动备份数量 */
+  retention: number;
+};
+
+/** 备份清单中的文件 */
+export type BackupFileEntry = {
+  path: string;
+  size: number;
+  sha256: string;
+};
+
+/** 备份清单 */
+export type BackupManifest = {
+  formatVersion: number;
+  appVersion: string;
+  schemaVersion: number;
+  createdAt: string;
+  files: BackupFileEntry[];
+};
+
+/** 备份文件信息 */
+export type BackupInfo = {
+  path: string;
+  fileName: string;
+  size: number;
+  isAuto: boolean;
+  modifiedAt: string;
+};
+
+/** 恢复时单个文件的变更 */
+export type RestoreChange = {
+  path: string;
+  action: "add" | "update" | "unchanged" | "remove";
+  size: number;
+};
+
+/** 恢复报告 */
+export type RestoreReport = {
+  dryRun: boolean;
+  manifest: BackupManifest;
+  changes: RestoreChange[];
+  safetyBackup: string | null;
+};
@@ -62,3 +62,35 @@ export type WebConfig = {
 };
 
 export type QuickInputConfig = QuickInputItem[];
+
+/** 配置所在的存储 */
+export type ConfigChangeTable = "config" | "pluginConfig" | "petConfig";
+
+/** config:changed 事件载荷 */
+export type ConfigChangedEvent = {
+  /** 存储位置 */
+  table: ConfigChangeTable;
+  /** 变更的键，为空表示整个存储被清空 */
+  key: string;
+  /** 新值，键被删除时为 null */
+  value: any | null;
+};
+
+/** 配置变更记录 */
+export type ConfigChange = {
+  id: number;
+  key: string;
+  /** 变更前的值，变更前键不存在时为 null */
+  previousValue: any | null;
+  /** 变更后的值，键被删除时为 null */
+  newValue: any | null;
+  /** 发起变更的窗口标签 */
+  windowLabel: string;
+  changedAt: string;
+};
+
+/** 回滚后配置键的值，键被删除时 value 为 null */
+export type RestoredConfigValue = {
+  key: string;
+  value: any | null;
+};
@@ -2,11 +2,16 @@ declare module "mh-plugin/mhPlugin.json" {
   const content: {
     windowId: string;
     title: string;
+    version: string;
+    minHostVersion?: string;
+    entry?: string;
     size: [number, number];
     position: [number, number];
     alwaysOnTop: boolean;
     resizable: boolean;
     icon: string;
+    permissions?: string[];
+    hotkeys?: { id: string; key: string; description?: string }[];
   };
   export default content;
 }
@@ -0,0 +1,11 @@
+/** 被拒绝的命令调用记录 */
+export type PermissionAuditEntry = {
+  id: number;
+  windowLabel: string;
+  /** 被拒绝的命令名 */
+  command: string;
+  /** 命令要求的能力，按窗口标识拒绝时为 null */
+  capability: string | null;
+  /** 拒绝时间，格式 YYYY-MM-DD HH:MM:SS */
+  deniedAt: string;
+};
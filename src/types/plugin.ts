@@ -26,6 +26,10 @@ export type PluginConfig = {
     category: PluginCategory;
     createTime: string;
     updateTime: string;
+    /** 开发模式插件的来源 */
+    dev?: DevSource;
+    /** 插件清单无效的原因，同步插件时写入 */
+    invalid?: string;
   };
 };
 
@@ -96,10 +100,18 @@ export type Plugin = {
   Resizable: boolean;
   Icon: string;
   FileUrl: string;
+  /** 插件包 SHA-256 */
+  Sha256: string;
+  /** 插件包的 Ed25519 签名（Base64） */
+  Signature?: string;
+  /** 插件申请的能力 */
+  Permissions?: string[];
   Message: string | null;
   CreateTime: string;
   UpdateTime: string;
   installTime?: string;
+  /** 插件清单无效的原因 */
+  Invalid?: string;
   config?: {
     isEnabled: boolean;
   };
@@ -159,3 +171,133 @@ export type PluginCreateDTO = {
   Resizable: boolean;
   FileUrl: string;
 };
+
+/** 受信任的插件发布者 */
+export type TrustedPublisher = {
+  id: string;
+  name: string;
+  /** Base64 编码的 Ed25519 公钥 */
+  publicKey: string;
+};
+
+/** 插件包校验结果，记录在插件配置的 info.verification 中 */
+export type PluginVerification = {
+  sha256: string;
+  /** 是否与插件市场提供的校验和一致 */
+  checksumVerified: boolean;
+  /** 签名是否通过信任的发布者公钥校验 */
+  signatureVerified: boolean;
+  publisher: string | null;
+  verifiedAt: string;
+};
+
+/** 插件安装阶段 */
+export type InstallPhase = "downloading" | "verifying" | "extracting";
+
+/** plugin:install-progress 事件载荷 */
+export type InstallProgress = {
+  windowId: string;
+  phase: InstallPhase;
+  /** 已下载的字节数 */
+  bytes: number;
+  /** 插件包总大小，服务器未返回长度时为 null */
+  total: number | null;
+};
+
+/** 可更新的插件，plugin:updates-available 事件载荷为该类型的数组 */
+export type PluginUpdate = {
+  windowId: string;
+  currentVersion: string;
+  latestVersion: string;
+  url: string;
+  sha256: string;
+  signature: string | null;
+};
+
+/** 单个插件的更新结果 */
+export type PluginUpdateResult = {
+  windowId: string;
+  version: string;
+  /** 更新失败的原因，成功时为 null */
+  error: string | null;
+};
+
+/** 插件更新配置（config表 pluginUpdateConfig） */
+export type PluginUpdateConfig = {
+  /** 是否定期检查更新 */
+  autoCheck?: boolean;
+  /** 检查间隔（小时） */
+  intervalHours?: number;
+  /** 插件索引地址，为空时使用插件市场的地址 */
+  indexUrl?: string;
+};
+
+/** 插件能力及说明 */
+export type PluginCapability = {
+  name: string;
+  description: string;
+};
+
+/** 插件申请的能力及授权状态 */
+export type CapabilityGrant = PluginCapability & {
+  granted: boolean;
+};
+
+/** plugin:installed、plugin:updated、plugin:uninstalled 事件载荷 */
+export type PluginChangedEvent = {
+  windowId: string;
+  /** 当前版本，卸载时为 null */
+  version: string | null;
+  /** 变更前的版本，首次安装时为 null */
+  previousVersion: string | null;
+};
+
+/** 插件启用状态变更事件 */
+export type PluginEnabledEvent = {
+  windowId: string;
+  enabled: boolean;
+};
+
+/** 插件键值存储用量，单位为字节 */
+export type PluginStorageUsage = {
+  windowId: string;
+  keys: number;
+  used: number;
+  quota: number;
+  /** 插件记录已不存在，存储项是残留数据 */
+  orphaned: boolean;
+};
+
+/** 未安装或版本不满足的插件依赖 */
+export type UnresolvedDependency = {
+  /** 声明依赖的插件 */
+  windowId: string;
+  /** 被依赖的插件或共享运行库 */
+  dependency: string;
+  /** 要求的 semver 版本范围 */
+  required: string;
+  /** 已安装的版本，未安装时为 null */
+  installed: string | null;
+};
+
+/** 开发模式插件的来源，本地目录和开发服务器地址只能设置一个 */
+export type DevSource = {
+  path?: string | null;
+  url?: string | null;
+};
+
+/** 开发模式插件重新加载事件，message 为重新加载失败的原因 */
+export type PluginDevEvent = {
+  windowId: string;
+  message: string | null;
+};
+
+/** 插件生命周期钩子 */
+export type LifecycleHook = "onInstall" | "onUpgrade" | "onUninstall" | "onStartup";
+
+/** plugin:lifecycle 事件载荷，插件窗口已打开时发送到插件窗口 */
+export type LifecycleEvent = {
+  hook: LifecycleHook;
+  /** 升级前的版本，只有 onUpgrade 时有值 */
+  previousVersion: string | null;
+};
@@ -1,4 +1,9 @@
-import { PluginStatus, PluginCategory, PluginSortType } from "./plugin";
+import {
+  PluginStatus,
+  PluginCategory,
+  PluginSortType,
+  PluginCapability,
+} from "./plugin";
 
 export enum MenuKey {
   MyPlugins = 0,
@@ -83,6 +88,10 @@ export type LocalPluginInfo = {
   alwaysOnTop?: boolean;
   resizable?: boolean;
   icon?: string;
+  /** 插件申请的能力 */
+  capabilities?: PluginCapability[];
+  /** 用户勾选授予的能力 */
+  grants?: string[];
 };
 
 export type DeveloperPlugin = {
@@ -140,7 +149,11 @@ export type PluginAnalysisResult = {
     windowId: string;
     name: string;
     description: string;
-    version?: string;
+    version: string;
+    /** 插件要求的最低宿主版本 */
+    minHostVersion?: string;
+    /** 入口页面 */
+    entry: string;
     author?: string;
     email?: string;
     tags?: string[];
@@ -150,6 +163,12 @@ export type PluginAnalysisResult = {
     alwaysOnTop?: boolean;
     resizable?: boolean;
     icon?: string;
+    /** 插件申请的能力 */
+    permissions: string[];
+    /** 插件申请的能力及说明 */
+    capabilities: PluginCapability[];
+    /** 插件声明的快捷键 */
+    hotkeys: { id: string; key: string; description?: string }[];
   };
 };
 
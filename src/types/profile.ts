@@ -0,0 +1,28 @@
+/** 配置方案自动切换规则，同时设置时间段和网络时需要同时满足 */
+export type AutoSwitchRule = {
+  /** 开始时间，格式 HH:MM */
+  startTime?: string;
+  /** 结束时间，格式 HH:MM，早于开始时间时表示跨天 */
+  endTime?: string;
+  /** 生效的星期，1 为周一，7 为周日，为空表示每天 */
+  weekdays?: number[];
+  /** 本机 IP 前缀，如 192.168.1. */
+  ipPrefixes?: string[];
+};
+
+/** 配置方案 */
+export type ProfileInfo = {
+  name: string;
+  /** 是否为当前使用的方案 */
+  active: boolean;
+  autoSwitch: AutoSwitchRule | null;
+  createdAt: string;
+};
+
+/** profile:switched 事件载荷 */
+export type ProfileSwitchedEvent = {
+  name: string;
+  previous: string;
+  /** 是否由自动切换规则触发 */
+  auto: boolean;
+};
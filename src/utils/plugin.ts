@@ -173,21 +173,37 @@ export const deletePluginConfig = async (
  * 安装插件
  * @param {string} url - 插件下载地址，必须是以 https://helper.ialtone.xyz/ 开头的URL
  * @param {string} windowId - 插件的窗口ID
+ * @param {string} sha256 - 插件市场提供的插件包 SHA-256
+ * @param {string} [signature] - 插件包的 Ed25519 签名（Base64）
+ * @param {string[]} [grants] - 用户授予的能力，不传时保留已有的授权
+ * @param {boolean} [allowUnsigned] - 用户已确认安装未签名的插件包
  * @returns {Promise<void>} - 安装成功返回void
- * @throws 如果安装失败或参数无效，将抛出错误
+ * @throws 如果安装失败、参数无效或插件包校验失败，将抛出错误
  *
  * @example
  * await installPlugin(
  *   'https://helper.ialtone.xyz/plugins/my-plugin.zip',
  *   'my-plugin-window',
+ *   '9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08',
  * );
  */
 export const installPlugin = async (
   url: string,
   windowId: string,
+  sha256: string,
+  signature?: string,
+  grants?: string[],
+  allowUnsigned?: boolean,
 ): Promise<void> => {
   try {
-    await ipcInstallPlugin(url, windowId);
+    await ipcInstallPlugin(
+      url,
+      windowId,
+      sha256,
+      signature,
+      grants,
+      allowUnsigned,
+    );
   } catch (error) {
     Logger.error(error, "安装插件失败:");
     throw error;